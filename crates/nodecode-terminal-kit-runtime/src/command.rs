@@ -1,8 +1,35 @@
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use ratatui::text::Line;
+
+use crate::executor::Panicked;
+
 /// Effect command produced by `Model::init` and `Model::update`.
+///
+/// Closures run on the program's command workers, never on the render loop; each
+/// returned message is fed back into `Model::update` once it completes.
 pub enum Command<Msg> {
     None,
     One(Box<dyn FnOnce() -> Msg + Send>),
     Batch(Vec<Box<dyn FnOnce() -> Msg + Send>>),
+    /// Print lines into the scrollback above an inline viewport.
+    ///
+    /// Each line occupies one terminal row; this is a no-op in fullscreen mode.
+    InsertBefore(Vec<Line<'static>>),
+    /// Stop the program once the current batch of messages has been applied.
+    Quit,
+    /// Run commands strictly one after another.
+    ///
+    /// Each command starts only after the message produced by the previous one
+    /// went through `Model::update`. Closures of a `Batch` inside a sequence run
+    /// one by one on the same worker.
+    Sequence(Vec<Command<Msg>>),
+    /// Deliver a message once the duration has elapsed; receives the fire time.
+    Tick(Duration, Box<dyn FnOnce(Instant) -> Msg + Send>),
+    /// Open text in the user's external editor and deliver what they saved.
+    EditText(String, Box<dyn FnOnce(io::Result<String>) -> Msg + Send>),
 }
 
 impl<Msg> Command<Msg> {
@@ -32,6 +59,99 @@ impl<Msg> Command<Msg> {
                 .collect(),
         )
     }
+
+    #[must_use]
+    pub fn insert_before<I, L>(lines: I) -> Self
+    where
+        I: IntoIterator<Item = L>,
+        L: Into<Line<'static>>,
+    {
+        Self::InsertBefore(lines.into_iter().map(Into::into).collect())
+    }
+
+    #[must_use]
+    pub fn quit() -> Self {
+        Self::Quit
+    }
+
+    #[must_use]
+    pub fn sequence<I>(commands: I) -> Self
+    where
+        I: IntoIterator<Item = Command<Msg>>,
+    {
+        Self::Sequence(commands.into_iter().collect())
+    }
+
+    #[must_use]
+    pub fn tick<F>(duration: Duration, f: F) -> Self
+    where
+        F: FnOnce(Instant) -> Msg + Send + 'static,
+    {
+        Self::Tick(duration, Box::new(f))
+    }
+
+    /// Suspend the program, edit `seed` in `$VISUAL`/`$EDITOR` (falling back to
+    /// `vi`) and deliver the saved text once the editor exits.
+    ///
+    /// The editor command is split on whitespace, so `EDITOR="code --wait"`
+    /// works but quoted arguments do not. A trailing newline added by the
+    /// editor is dropped unless `seed` ended with one.
+    #[must_use]
+    pub fn edit_text<F>(seed: impl Into<String>, f: F) -> Self
+    where
+        F: FnOnce(io::Result<String>) -> Msg + Send + 'static,
+    {
+        Self::EditText(seed.into(), Box::new(f))
+    }
+}
+
+impl<Msg: 'static> Command<Msg> {
+    /// Convert every message this command produces, e.g. to lift a child
+    /// component's command into its parent's message type.
+    #[must_use]
+    pub fn map<B: 'static, F>(self, f: F) -> Command<B>
+    where
+        F: Fn(Msg) -> B + Send + Sync + 'static,
+    {
+        self.map_with(&Arc::new(f))
+    }
+
+    fn map_with<B: 'static, F>(self, f: &Arc<F>) -> Command<B>
+    where
+        F: Fn(Msg) -> B + Send + Sync + 'static,
+    {
+        match self {
+            Self::None => Command::None,
+            Self::One(op) => {
+                let f = Arc::clone(f);
+                Command::One(Box::new(move || f(op())))
+            }
+            Self::Batch(ops) => Command::Batch(
+                ops.into_iter()
+                    .map(|op| {
+                        let f = Arc::clone(f);
+                        Box::new(move || f(op())) as Box<dyn FnOnce() -> B + Send>
+                    })
+                    .collect(),
+            ),
+            Self::InsertBefore(lines) => Command::InsertBefore(lines),
+            Self::Quit => Command::Quit,
+            Self::Sequence(commands) => Command::Sequence(
+                commands
+                    .into_iter()
+                    .map(|command| command.map_with(f))
+                    .collect(),
+            ),
+            Self::Tick(duration, op) => {
+                let f = Arc::clone(f);
+                Command::Tick(duration, Box::new(move |at| f(op(at))))
+            }
+            Self::EditText(seed, op) => {
+                let f = Arc::clone(f);
+                Command::EditText(seed, Box::new(move |edited| f(op(edited))))
+            }
+        }
+    }
 }
 
 impl<Msg> Default for Command<Msg> {
@@ -39,3 +159,43 @@ impl<Msg> Default for Command<Msg> {
         Self::None
     }
 }
+
+/// Terminal-level effect applied by the event loop itself.
+pub(crate) enum Effect<Msg> {
+    InsertBefore(Vec<Line<'static>>),
+    EditText(EditRequest<Msg>),
+}
+
+/// Pending `Command::edit_text`; hand the result to `Runtime::complete_edit`.
+pub(crate) struct EditRequest<Msg> {
+    pub(crate) seed: String,
+    pub(crate) to_msg: Box<dyn FnOnce(io::Result<String>) -> Msg + Send>,
+    pub(crate) rest: Vec<Command<Msg>>,
+}
+
+/// Item delivered back to the event loop by the executor.
+pub(crate) enum Outcome<Msg> {
+    Msg(Msg),
+    Effect(Effect<Msg>),
+    Quit,
+    /// A sequence step finished; `rest` starts once `msgs` have been applied.
+    Step {
+        msgs: Vec<Msg>,
+        rest: Vec<Command<Msg>>,
+    },
+    /// A command panicked; the program stops with the message.
+    Panicked(String),
+}
+
+impl<Msg> From<Panicked> for Outcome<Msg> {
+    fn from(Panicked(message): Panicked) -> Self {
+        Self::Panicked(message)
+    }
+}
+
+/// `Command::tick` waiting for its deadline, with the sequence it resumes.
+pub(crate) struct Delayed<Msg> {
+    pub(crate) due: Instant,
+    pub(crate) to_msg: Box<dyn FnOnce(Instant) -> Msg + Send>,
+    pub(crate) rest: Vec<Command<Msg>>,
+}
@@ -1,5 +1,7 @@
+use std::sync::Arc;
 use std::time::Duration;
 
+use nodecode_terminal_kit::keymap::Keymap;
 use nodecode_terminal_kit::theme::ThemeFacade;
 
 #[derive(Debug, Clone)]
@@ -19,12 +21,37 @@ impl Default for ExitKeys {
     }
 }
 
+/// How the program occupies the terminal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ViewportMode {
+    /// Take over the alternate screen for the lifetime of the program.
+    #[default]
+    Fullscreen,
+    /// Render a fixed-height region below the shell prompt; lines printed with
+    /// `Command::insert_before` scroll into the terminal history above it.
+    Inline { height: u16 },
+}
+
 #[derive(Debug, Clone)]
 pub struct ProgramConfig {
     pub title: String,
     pub theme: ThemeFacade,
     pub tick_rate: Duration,
     pub exit_keys: ExitKeys,
+    /// Number of worker threads that execute command closures off the event loop.
+    pub command_workers: usize,
+    /// Capture mouse events and deliver them to `Model::on_mouse`.
+    pub mouse_capture: bool,
+    /// Deliver pastes as a single `Model::on_paste` call instead of key events.
+    pub bracketed_paste: bool,
+    /// Report terminal focus changes to `Model::on_focus`.
+    pub focus_reporting: bool,
+    pub viewport: ViewportMode,
+    /// Keymap for the `program` context; the active keymap when `None`.
+    ///
+    /// Raw mode stops the terminal from turning Ctrl-Z into `SIGTSTP`, so keys
+    /// bound to `suspend` here are what suspend the program (Unix only).
+    pub keymap: Option<Arc<Keymap>>,
 }
 
 impl ProgramConfig {
@@ -35,6 +62,12 @@ impl ProgramConfig {
             theme: ThemeFacade::default(),
             tick_rate: Duration::from_millis(100),
             exit_keys: ExitKeys::default(),
+            command_workers: 4,
+            mouse_capture: false,
+            bracketed_paste: false,
+            focus_reporting: false,
+            viewport: ViewportMode::Fullscreen,
+            keymap: None,
         }
     }
 
@@ -55,6 +88,50 @@ impl ProgramConfig {
         self.exit_keys = exit_keys;
         self
     }
+
+    #[must_use]
+    pub fn command_workers(mut self, command_workers: usize) -> Self {
+        self.command_workers = command_workers.max(1);
+        self
+    }
+
+    #[must_use]
+    pub fn mouse_capture(mut self, enabled: bool) -> Self {
+        self.mouse_capture = enabled;
+        self
+    }
+
+    #[must_use]
+    pub fn bracketed_paste(mut self, enabled: bool) -> Self {
+        self.bracketed_paste = enabled;
+        self
+    }
+
+    #[must_use]
+    pub fn focus_reporting(mut self, enabled: bool) -> Self {
+        self.focus_reporting = enabled;
+        self
+    }
+
+    #[must_use]
+    pub fn viewport(mut self, viewport: ViewportMode) -> Self {
+        self.viewport = viewport;
+        self
+    }
+
+    #[must_use]
+    pub fn keymap(mut self, keymap: Arc<Keymap>) -> Self {
+        self.keymap = Some(keymap);
+        self
+    }
+
+    /// Shorthand for `ViewportMode::Inline` with the given height in rows.
+    #[must_use]
+    pub fn inline(self, height: u16) -> Self {
+        self.viewport(ViewportMode::Inline {
+            height: height.max(1),
+        })
+    }
 }
 
 impl Default for ProgramConfig {
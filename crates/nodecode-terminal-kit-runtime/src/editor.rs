@@ -0,0 +1,97 @@
+//! Launching the user's external editor for `Command::edit_text`.
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(windows)]
+const FALLBACK_EDITOR: &str = "notepad";
+#[cfg(not(windows))]
+const FALLBACK_EDITOR: &str = "vi";
+
+/// Edit `seed` in a temp file and return what the user saved.
+///
+/// The terminal must already be released; this blocks until the editor exits.
+pub(crate) fn edit_text(seed: &str) -> io::Result<String> {
+    let (path, mut file) = create_temp_file()?;
+    let result = file
+        .write_all(seed.as_bytes())
+        .and_then(|()| file.flush())
+        .and_then(|()| {
+            drop(file);
+            run_editor(&path)
+        })
+        .and_then(|()| fs::read_to_string(&path));
+    let _ = fs::remove_file(&path);
+    result.map(|edited| strip_added_newline(seed, edited))
+}
+
+/// `$VISUAL`, then `$EDITOR`, then a platform fallback, split into program and arguments.
+fn editor_command() -> Vec<String> {
+    ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|var| env::var(var).ok())
+        .map(|value| {
+            value
+                .split_whitespace()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .find(|parts| !parts.is_empty())
+        .unwrap_or_else(|| vec![FALLBACK_EDITOR.to_string()])
+}
+
+fn run_editor(path: &Path) -> io::Result<()> {
+    let mut parts = editor_command().into_iter();
+    let program = parts.next().unwrap_or_else(|| FALLBACK_EDITOR.to_string());
+    let status = process::Command::new(&program)
+        .args(parts)
+        .arg(path)
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "editor `{program}` exited with {status}"
+        )))
+    }
+}
+
+/// A new file in the temp dir that only the current user can read, since
+/// the draft may hold anything typed into the prompt.
+pub(crate) fn create_temp_file() -> io::Result<(PathBuf, fs::File)> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.subsec_nanos())
+        .unwrap_or_default();
+    loop {
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = env::temp_dir().join(format!("nodecode-edit-{}-{nanos}-{n}.txt", process::id()));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        match options.open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Editors like vim always end the file with a newline; drop it unless the
+/// seed had one, so single-line prompts round-trip unchanged.
+fn strip_added_newline(seed: &str, mut edited: String) -> String {
+    if !seed.ends_with('\n') && edited.ends_with('\n') {
+        edited.pop();
+        if edited.ends_with('\r') {
+            edited.pop();
+        }
+    }
+    edited
+}
@@ -3,4 +3,12 @@
 pub enum ProgramError {
     #[error("i/o error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("command panicked: {0}")]
+    CommandPanicked(String),
+}
+
+impl From<std::convert::Infallible> for ProgramError {
+    fn from(never: std::convert::Infallible) -> Self {
+        match never {}
+    }
 }
@@ -0,0 +1,123 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+type Job<T> = Box<dyn FnOnce() -> T + Send>;
+
+/// Name prefix of the pool's worker threads.
+pub(crate) const WORKER_PREFIX: &str = "nodecode-command-";
+
+/// A job that panicked instead of producing a result, with its message.
+pub(crate) struct Panicked(pub(crate) String);
+
+/// Run `job`, turning a panic into a result so it still reaches the event
+/// loop.
+fn run<T: From<Panicked>>(job: Job<T>) -> T {
+    panic::catch_unwind(AssertUnwindSafe(job)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| (*message).to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic payload".to_string());
+        T::from(Panicked(message))
+    })
+}
+
+/// Runs command closures and collects their results for the event loop.
+///
+/// A pool executor hands jobs to worker threads so slow effects never block
+/// rendering; an inline executor runs them on the caller thread, which keeps
+/// ordering deterministic for tests.
+pub(crate) struct Executor<T> {
+    jobs: Option<Sender<Job<T>>>,
+    results_tx: Sender<T>,
+    results_rx: Receiver<T>,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl<T: From<Panicked> + Send + 'static> Executor<T> {
+    /// Start a pool with `workers` threads (at least one).
+    pub(crate) fn pool(workers: usize) -> Self {
+        let (jobs_tx, jobs_rx) = mpsc::channel::<Job<T>>();
+        let (results_tx, results_rx) = mpsc::channel();
+        let jobs_rx = Arc::new(Mutex::new(jobs_rx));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+
+        for idx in 0..workers.max(1) {
+            let jobs_rx = Arc::clone(&jobs_rx);
+            let results_tx = results_tx.clone();
+            let in_flight = Arc::clone(&in_flight);
+            let spawned = thread::Builder::new()
+                .name(format!("{WORKER_PREFIX}{idx}"))
+                .spawn(move || loop {
+                    let job = {
+                        let Ok(jobs) = jobs_rx.lock() else {
+                            return;
+                        };
+                        match jobs.recv() {
+                            Ok(job) => job,
+                            Err(_) => return,
+                        }
+                    };
+                    let _ = results_tx.send(run(job));
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                });
+            if spawned.is_err() && idx == 0 {
+                // Without a single worker there is nowhere to run jobs; fall back to inline.
+                return Self::inline();
+            }
+        }
+
+        Self {
+            jobs: Some(jobs_tx),
+            results_tx,
+            results_rx,
+            in_flight,
+        }
+    }
+
+    /// Executor that runs every job immediately on the calling thread.
+    pub(crate) fn inline() -> Self {
+        let (results_tx, results_rx) = mpsc::channel();
+        Self {
+            jobs: None,
+            results_tx,
+            results_rx,
+            in_flight: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub(crate) fn spawn(&mut self, job: Job<T>) {
+        let Some(jobs) = &self.jobs else {
+            let _ = self.results_tx.send(run(job));
+            return;
+        };
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        if let Err(mpsc::SendError(job)) = jobs.send(job) {
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            let _ = self.results_tx.send(run(job));
+        }
+    }
+
+    /// Queue a ready result behind everything that has already completed.
+    pub(crate) fn emit(&self, result: T) {
+        let _ = self.results_tx.send(result);
+    }
+
+    /// Sender that injects results into the same queue as command results.
+    pub(crate) fn sender(&self) -> Sender<T> {
+        self.results_tx.clone()
+    }
+
+    /// Pop the next completed result without blocking.
+    pub(crate) fn try_next(&mut self) -> Option<T> {
+        self.results_rx.try_recv().ok()
+    }
+
+    /// True when no job is queued or running on a worker.
+    pub(crate) fn is_idle(&self) -> bool {
+        self.in_flight.load(Ordering::SeqCst) == 0
+    }
+}
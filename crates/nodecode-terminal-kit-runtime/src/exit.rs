@@ -0,0 +1,43 @@
+use crossterm::event::KeyEvent;
+
+use crate::ProgramError;
+
+/// Why `Program::run` returned.
+#[derive(Debug)]
+pub enum ExitReason {
+    /// One of the configured `ExitKeys` was pressed.
+    ExitKey(KeyEvent),
+    /// The model returned `Command::quit`.
+    Quit,
+    /// The process received SIGTERM, SIGHUP or SIGINT (Unix only).
+    Signal(i32),
+    /// The terminal could not be set up, read or drawn, or a command panicked.
+    Error(ProgramError),
+}
+
+/// Final model state handed back by `Program::run`.
+///
+/// The model is returned even when the program stopped on an error, so callers
+/// can still recover whatever the user had entered.
+#[derive(Debug)]
+#[must_use]
+pub struct ProgramExit<M> {
+    pub model: M,
+    pub reason: ExitReason,
+}
+
+impl<M> ProgramExit<M> {
+    /// True when the model itself asked to stop via `Command::quit`.
+    #[must_use]
+    pub fn is_quit(&self) -> bool {
+        matches!(self.reason, ExitReason::Quit)
+    }
+
+    /// The final model, or the error that stopped the program.
+    pub fn into_result(self) -> Result<M, ProgramError> {
+        match self.reason {
+            ExitReason::Error(err) => Err(err),
+            ExitReason::ExitKey(_) | ExitReason::Quit | ExitReason::Signal(_) => Ok(self.model),
+        }
+    }
+}
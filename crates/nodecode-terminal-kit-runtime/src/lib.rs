@@ -5,16 +5,29 @@
 
 mod command;
 mod config;
+mod editor;
 mod error;
+mod executor;
+mod exit;
 mod model;
 mod program;
+mod session;
+mod signals;
+mod subscription;
+mod testing;
 
 pub use command::Command;
-pub use config::{ExitKeys, ProgramConfig};
+pub use config::{ExitKeys, ProgramConfig, ViewportMode};
 pub use error::ProgramError;
+pub use exit::{ExitReason, ProgramExit};
 pub use model::Model;
 pub use program::Program;
+pub use subscription::{shared_channel, ProcessOutput, SharedReceiver, Subscription};
+pub use testing::TestProgram;
 
 pub mod prelude {
-    pub use crate::{Command, ExitKeys, Model, Program, ProgramConfig, ProgramError};
+    pub use crate::{
+        shared_channel, Command, ExitKeys, ExitReason, Model, ProcessOutput, Program,
+        ProgramConfig, ProgramError, ProgramExit, SharedReceiver, Subscription, ViewportMode,
+    };
 }
@@ -1,9 +1,9 @@
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyEvent, MouseEvent};
 use nodecode_terminal_kit::theme::Theme;
 use ratatui::layout::Rect;
 use ratatui::Frame;
 
-use crate::Command;
+use crate::{Command, Subscription};
 
 /// Runtime contract for terminal app state transitions and rendering.
 pub trait Model {
@@ -20,8 +20,36 @@ pub trait Model {
     /// Render the full application content inside the provided area.
     fn view(&self, frame: &mut Frame, area: Rect, theme: &Theme);
 
+    /// Declarative message sources to keep alive for the current state.
+    ///
+    /// Called after every update; the program diffs the returned ids against the
+    /// running set, starting new sources and stopping ones no longer listed.
+    fn subscriptions(&self) -> Vec<Subscription<Self::Msg>> {
+        Vec::new()
+    }
+
     /// Optional key handler that maps keyboard events to messages.
     fn on_key(&mut self, _key: KeyEvent) -> Option<Self::Msg> {
         None
     }
+
+    /// Optional mouse handler; requires `ProgramConfig::mouse_capture`.
+    fn on_mouse(&mut self, _mouse: MouseEvent) -> Option<Self::Msg> {
+        None
+    }
+
+    /// Optional handler for terminal resizes (new width and height in cells).
+    fn on_resize(&mut self, _width: u16, _height: u16) -> Option<Self::Msg> {
+        None
+    }
+
+    /// Optional handler for pasted text; requires `ProgramConfig::bracketed_paste`.
+    fn on_paste(&mut self, _text: String) -> Option<Self::Msg> {
+        None
+    }
+
+    /// Optional handler for terminal focus changes; requires `ProgramConfig::focus_reporting`.
+    fn on_focus(&mut self, _focused: bool) -> Option<Self::Msg> {
+        None
+    }
 }
@@ -1,38 +1,24 @@
-use std::collections::VecDeque;
 use std::io;
+use std::time::{Duration, Instant};
 
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
-use crossterm::execute;
-use crossterm::terminal::{
-    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
-};
-use ratatui::backend::CrosstermBackend;
+use nodecode_terminal_kit::keymap::{self, action, context, KeyChord, Keymap};
+use ratatui::backend::Backend;
+use ratatui::text::Line;
+use ratatui::widgets::{Paragraph, Widget};
 use ratatui::Terminal;
 
-use crate::{Command, ExitKeys, Model, ProgramConfig, ProgramError};
+use crate::command::{Delayed, EditRequest, Effect, Outcome};
+use crate::editor;
+use crate::executor::Executor;
+use crate::session::TerminalSession;
+use crate::signals::{Signal, Signals, CAN_SUSPEND};
+use crate::subscription::Subscriptions;
+use crate::{Command, ExitKeys, ExitReason, Model, ProgramConfig, ProgramError, ProgramExit};
 
-struct TerminalSession {
-    terminal: Terminal<CrosstermBackend<io::Stdout>>,
-}
-
-impl TerminalSession {
-    fn new() -> Result<Self, ProgramError> {
-        enable_raw_mode()?;
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen)?;
-        let backend = CrosstermBackend::new(stdout);
-        let terminal = Terminal::new(backend)?;
-        Ok(Self { terminal })
-    }
-}
-
-impl Drop for TerminalSession {
-    fn drop(&mut self) {
-        let _ = disable_raw_mode();
-        let _ = execute!(self.terminal.backend_mut(), LeaveAlternateScreen);
-        let _ = self.terminal.show_cursor();
-    }
-}
+/// Poll interval used while commands are running or streams are subscribed, so
+/// their messages are applied promptly even when `tick_rate` is slow.
+const COMMAND_POLL_INTERVAL: Duration = Duration::from_millis(16);
 
 pub struct Program<M: Model> {
     model: M,
@@ -45,80 +31,397 @@ impl<M: Model> Program<M> {
         Self { model, config }
     }
 
-    pub fn run(mut self) -> Result<(), ProgramError> {
-        let mut session = TerminalSession::new()?;
-        let mut pending = VecDeque::new();
+    /// Run until an exit key, `Command::quit` or a panicking command, then hand
+    /// back the model.
+    ///
+    /// The terminal is restored before this returns, so callers can print the
+    /// result straight away.
+    pub fn run(self) -> ProgramExit<M> {
+        let executor = Executor::pool(self.config.command_workers);
+        let mut runtime = Runtime::new(self.model, self.config, executor);
+        let reason = drive(&mut runtime).unwrap_or_else(ExitReason::Error);
+        ProgramExit {
+            model: runtime.model,
+            reason,
+        }
+    }
+}
+
+fn drive<M: Model>(runtime: &mut Runtime<M>) -> Result<ExitReason, ProgramError> {
+    let signals = Signals::register()?;
+    let mut session = TerminalSession::new(&runtime.config)?;
+    runtime.start(Instant::now());
+
+    loop {
+        match signals.take() {
+            Some(Signal::Suspend) => session.suspend(|| signals.stop_process())?,
+            Some(Signal::Resumed) => session.resume()?,
+            Some(Signal::Terminate(signal)) => return Ok(ExitReason::Signal(signal)),
+            None => {}
+        }
+        let mut settling = true;
+        while settling {
+            settling = false;
+            for effect in runtime.settle(Instant::now()) {
+                match effect {
+                    Effect::InsertBefore(lines) => insert_lines(&mut session.terminal, lines)?,
+                    Effect::EditText(request) => {
+                        let edited = session.release(|| editor::edit_text(&request.seed))?;
+                        runtime.complete_edit(request, edited, Instant::now());
+                        // Apply the edited text before the next frame.
+                        settling = true;
+                    }
+                }
+            }
+        }
+        if let Some(err) = runtime.take_failure() {
+            return Err(err);
+        }
+        if runtime.quit_requested() {
+            return Ok(ExitReason::Quit);
+        }
+        runtime.render(&mut session.terminal)?;
+
+        if !event::poll(runtime.poll_timeout(Instant::now()))? {
+            continue;
+        }
+        let event = event::read()?;
+        if runtime.suspends(&event) {
+            session.suspend(|| signals.stop_process())?;
+            continue;
+        }
+        if let Some(reason) = runtime.handle_event(event, Instant::now()) {
+            return Ok(reason);
+        }
+    }
+}
+
+/// A model together with the machinery that feeds it messages.
+///
+/// Shared by `Program` and `TestProgram` so both drive models identically; only
+/// the terminal, executor and clock differ.
+pub(crate) struct Runtime<M: Model> {
+    pub(crate) model: M,
+    pub(crate) config: ProgramConfig,
+    executor: Executor<Outcome<M::Msg>>,
+    subscriptions: Subscriptions<M::Msg>,
+    delayed: Vec<Delayed<M::Msg>>,
+    quit: bool,
+    /// Set when a command panicked; the program stops with it.
+    failure: Option<ProgramError>,
+}
+
+impl<M: Model> Runtime<M> {
+    pub(crate) fn new(
+        model: M,
+        config: ProgramConfig,
+        executor: Executor<Outcome<M::Msg>>,
+    ) -> Self {
+        Self {
+            model,
+            config,
+            executor,
+            subscriptions: Subscriptions::new(),
+            delayed: Vec::new(),
+            quit: false,
+            failure: None,
+        }
+    }
+
+    /// Whether `event` suspends the program instead of reaching the model.
+    pub(crate) fn suspends(&self, event: &Event) -> bool {
+        let Event::Key(key) = event else {
+            return false;
+        };
+        let keymap = self.config.keymap.clone().unwrap_or_else(keymap::active);
+        CAN_SUSPEND && keymap.action_for(context::PROGRAM, key) == Some(action::SUSPEND)
+    }
+
+    /// Queue the model's startup command.
+    pub(crate) fn start(&mut self, now: Instant) {
+        let command = self.model.init();
+        self.enqueue(command, now);
+    }
+
+    pub(crate) fn dispatch(&mut self, msg: M::Msg, now: Instant) {
+        let command = self.model.update(msg);
+        self.enqueue(command, now);
+    }
+
+    /// The error that stops the program, once a command has panicked.
+    pub(crate) fn take_failure(&mut self) -> Option<ProgramError> {
+        self.failure.take()
+    }
 
-        enqueue_command(self.model.init(), &mut pending);
-        drain_pending(&mut self.model, &mut pending);
+    /// True once a `Command::quit` has been applied.
+    pub(crate) fn quit_requested(&self) -> bool {
+        self.quit
+    }
 
-        loop {
-            session.terminal.draw(|f| {
-                let area = f.area();
-                self.model.view(f, area, self.config.theme.theme());
-            })?;
+    /// Fire due timers, apply every completed message and re-sync subscriptions.
+    ///
+    /// Terminal effects are returned in the order they were produced.
+    pub(crate) fn settle(&mut self, now: Instant) -> Vec<Effect<M::Msg>> {
+        for msg in self.subscriptions.fire_due(now) {
+            self.dispatch(msg, now);
+        }
+        self.fire_delayed(now);
 
-            if !event::poll(self.config.tick_rate)? {
-                continue;
+        let mut effects = Vec::new();
+        while let Some(outcome) = self.executor.try_next() {
+            match outcome {
+                Outcome::Msg(msg) => self.dispatch(msg, now),
+                Outcome::Effect(effect) => effects.push(effect),
+                Outcome::Quit => self.quit = true,
+                Outcome::Panicked(message) => {
+                    self.failure
+                        .get_or_insert(ProgramError::CommandPanicked(message));
+                }
+                Outcome::Step { msgs, rest } => {
+                    for msg in msgs {
+                        self.dispatch(msg, now);
+                    }
+                    self.enqueue_sequence(rest, now);
+                }
             }
+        }
+
+        self.subscriptions
+            .sync(self.model.subscriptions(), &self.executor.sender(), now);
+        effects
+    }
 
-            let Event::Key(key) = event::read()? else {
-                continue;
-            };
+    pub(crate) fn render<B>(&self, terminal: &mut Terminal<B>) -> Result<(), ProgramError>
+    where
+        B: Backend,
+        ProgramError: From<B::Error>,
+    {
+        terminal.draw(|f| {
+            let area = f.area();
+            self.model.view(f, area, self.config.theme.theme());
+        })?;
+        Ok(())
+    }
 
-            if should_exit_key(&key, &self.config.exit_keys) {
-                break;
+    /// Route a terminal event into the model; returns the reason when it was an exit key.
+    pub(crate) fn handle_event(&mut self, event: Event, now: Instant) -> Option<ExitReason> {
+        if let Event::Key(key) = event {
+            let keymap = self.config.keymap.clone().unwrap_or_else(keymap::active);
+            if should_exit_key(&key, &self.config.exit_keys, &keymap) {
+                return Some(ExitReason::ExitKey(key));
             }
+        }
+        if let Some(msg) = event_to_msg(&mut self.model, event) {
+            self.dispatch(msg, now);
+        }
+        None
+    }
 
-            if let Some(msg) = self.model.on_key(key) {
-                pending.push_back(msg);
-                drain_pending(&mut self.model, &mut pending);
+    /// Earliest instant at which a timer subscription or tick wants to fire.
+    pub(crate) fn next_deadline(&self) -> Option<Instant> {
+        let delayed = self.delayed.iter().map(|delayed| delayed.due).min();
+        match (self.subscriptions.next_deadline(), delayed) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// How long the loop may block on terminal input before it has other work.
+    pub(crate) fn poll_timeout(&self, now: Instant) -> Duration {
+        let mut timeout = if self.executor.is_idle() && !self.subscriptions.has_streams() {
+            self.config.tick_rate
+        } else {
+            self.config.tick_rate.min(COMMAND_POLL_INTERVAL)
+        };
+        if let Some(deadline) = self.next_deadline() {
+            timeout = timeout.min(deadline.saturating_duration_since(now));
+        }
+        timeout
+    }
+
+    fn enqueue(&mut self, command: Command<M::Msg>, now: Instant) {
+        match command {
+            Command::None => {}
+            Command::One(op) => self.executor.spawn(Box::new(move || Outcome::Msg(op()))),
+            Command::Batch(ops) => {
+                for op in ops {
+                    self.executor.spawn(Box::new(move || Outcome::Msg(op())));
+                }
+            }
+            Command::InsertBefore(lines) => self
+                .executor
+                .emit(Outcome::Effect(Effect::InsertBefore(lines))),
+            Command::Quit => self.executor.emit(Outcome::Quit),
+            Command::Sequence(commands) => self.enqueue_sequence(commands, now),
+            Command::Tick(after, to_msg) => self.delayed.push(Delayed {
+                due: now + after,
+                to_msg,
+                rest: Vec::new(),
+            }),
+            Command::EditText(seed, to_msg) => {
+                self.executor
+                    .emit(Outcome::Effect(Effect::EditText(EditRequest {
+                        seed,
+                        to_msg,
+                        rest: Vec::new(),
+                    })))
             }
         }
+    }
 
-        Ok(())
+    /// Deliver the outcome of an external edit and resume its sequence.
+    pub(crate) fn complete_edit(
+        &mut self,
+        request: EditRequest<M::Msg>,
+        edited: io::Result<String>,
+        now: Instant,
+    ) {
+        self.dispatch((request.to_msg)(edited), now);
+        self.enqueue_sequence(request.rest, now);
     }
-}
 
-fn enqueue_command<Msg>(command: Command<Msg>, pending: &mut VecDeque<Msg>) {
-    match command {
-        Command::None => {}
-        Command::One(op) => pending.push_back(op()),
-        Command::Batch(commands) => {
-            for command in commands {
-                pending.push_back(command());
+    /// Run a sequence up to its first command that produces a message; the
+    /// rest resumes once that message has been applied.
+    fn enqueue_sequence(&mut self, commands: Vec<Command<M::Msg>>, now: Instant) {
+        let mut commands = commands.into_iter();
+        while let Some(command) = commands.next() {
+            match command {
+                Command::None => {}
+                Command::InsertBefore(lines) => self
+                    .executor
+                    .emit(Outcome::Effect(Effect::InsertBefore(lines))),
+                Command::Quit => {
+                    self.executor.emit(Outcome::Quit);
+                    return;
+                }
+                Command::Sequence(inner) => {
+                    let rest = inner.into_iter().chain(commands).collect();
+                    self.enqueue_sequence(rest, now);
+                    return;
+                }
+                Command::One(op) => {
+                    let rest = commands.collect();
+                    self.executor.spawn(Box::new(move || Outcome::Step {
+                        msgs: vec![op()],
+                        rest,
+                    }));
+                    return;
+                }
+                Command::Batch(ops) => {
+                    let rest = commands.collect();
+                    self.executor.spawn(Box::new(move || Outcome::Step {
+                        msgs: ops.into_iter().map(|op| op()).collect(),
+                        rest,
+                    }));
+                    return;
+                }
+                Command::Tick(after, to_msg) => {
+                    self.delayed.push(Delayed {
+                        due: now + after,
+                        to_msg,
+                        rest: commands.collect(),
+                    });
+                    return;
+                }
+                Command::EditText(seed, to_msg) => {
+                    self.executor
+                        .emit(Outcome::Effect(Effect::EditText(EditRequest {
+                            seed,
+                            to_msg,
+                            rest: commands.collect(),
+                        })));
+                    return;
+                }
             }
         }
     }
+
+    /// Apply every tick whose deadline has passed, earliest first.
+    fn fire_delayed(&mut self, now: Instant) {
+        let (mut due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.delayed)
+            .into_iter()
+            .partition(|delayed| delayed.due <= now);
+        self.delayed = pending;
+        due.sort_by_key(|delayed| delayed.due);
+        for delayed in due {
+            self.dispatch((delayed.to_msg)(now), now);
+            self.enqueue_sequence(delayed.rest, now);
+        }
+    }
+}
+
+/// Print lines into the scrollback above an inline viewport, one row each.
+pub(crate) fn insert_lines<B>(
+    terminal: &mut Terminal<B>,
+    lines: Vec<Line<'static>>,
+) -> Result<(), ProgramError>
+where
+    B: Backend,
+    ProgramError: From<B::Error>,
+{
+    if lines.is_empty() {
+        return Ok(());
+    }
+    let height = u16::try_from(lines.len()).unwrap_or(u16::MAX);
+    terminal.insert_before(height, |buf| {
+        Paragraph::new(lines).render(buf.area, buf);
+    })?;
+    Ok(())
 }
 
-fn drain_pending<M: Model>(model: &mut M, pending: &mut VecDeque<M::Msg>) {
-    while let Some(msg) = pending.pop_front() {
-        enqueue_command(model.update(msg), pending);
+/// Route a terminal event to the matching `Model` hook.
+fn event_to_msg<M: Model>(model: &mut M, event: Event) -> Option<M::Msg> {
+    match event {
+        Event::Key(key) => model.on_key(key),
+        Event::Mouse(mouse) => model.on_mouse(mouse),
+        Event::Resize(width, height) => model.on_resize(width, height),
+        Event::Paste(text) => model.on_paste(text),
+        Event::FocusGained => model.on_focus(true),
+        Event::FocusLost => model.on_focus(false),
     }
 }
 
-fn should_exit_key(key: &KeyEvent, exit_keys: &ExitKeys) -> bool {
-    match key.code {
-        KeyCode::Esc => exit_keys.esc,
-        KeyCode::Char('q') if key.modifiers.is_empty() => exit_keys.q,
-        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => exit_keys.ctrl_c,
-        _ => false,
+/// Keys bound to `quit` in the keymap's `program` context exit, except stock
+/// keys switched off in `ExitKeys`.
+fn should_exit_key(key: &KeyEvent, exit_keys: &ExitKeys, keymap: &Keymap) -> bool {
+    if keymap.action_for(context::PROGRAM, key) != Some(action::QUIT) {
+        return false;
+    }
+    let chord = KeyChord::from_event(key);
+    match (chord.code, chord.modifiers) {
+        (KeyCode::Esc, KeyModifiers::NONE) => exit_keys.esc,
+        (KeyCode::Char('q'), KeyModifiers::NONE) => exit_keys.q,
+        (KeyCode::Char('c'), KeyModifiers::CONTROL) => exit_keys.ctrl_c,
+        _ => true,
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::VecDeque;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
 
-    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use crossterm::event::{
+        Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    };
+    use nodecode_terminal_kit::components::text_input::TextInput;
+    use nodecode_terminal_kit::keymap::{action, context, Keymap};
     use nodecode_terminal_kit::theme::Theme;
+    use ratatui::backend::TestBackend;
     use ratatui::layout::Rect;
-    use ratatui::Frame;
+    use ratatui::widgets::Paragraph;
+    use ratatui::{Frame, Terminal, TerminalOptions, Viewport};
 
-    use crate::{Command, ExitKeys, Model};
+    use crate::command::{Effect, Outcome};
+    use crate::executor::Executor;
+    use crate::subscription::Subscriptions;
+    use crate::{
+        shared_channel, Command, ExitKeys, ExitReason, Model, ProgramConfig, ProgramError,
+        Subscription, TestProgram,
+    };
 
-    use super::{drain_pending, enqueue_command, should_exit_key};
+    use super::{event_to_msg, insert_lines, should_exit_key, Runtime};
 
     #[derive(Default)]
     struct TestModel {
@@ -142,46 +445,111 @@ mod tests {
         fn view(&self, _frame: &mut Frame, _area: Rect, _theme: &Theme) {}
     }
 
+    fn inline_runtime<M: Model>(model: M) -> Runtime<M> {
+        Runtime::new(model, ProgramConfig::default(), Executor::inline())
+    }
+
     #[test]
     fn init_command_runs_once_and_updates_model() {
-        let mut model = TestModel::default();
-        let mut pending = VecDeque::new();
+        let mut runtime = inline_runtime(TestModel::default());
+        let now = Instant::now();
 
-        enqueue_command(model.init(), &mut pending);
-        drain_pending(&mut model, &mut pending);
+        runtime.start(now);
+        runtime.settle(now);
 
-        assert_eq!(model.init_calls, 1);
-        assert_eq!(model.updates, vec![1]);
+        assert_eq!(runtime.model.init_calls, 1);
+        assert_eq!(runtime.model.updates, vec![1]);
     }
 
     #[test]
     fn batch_command_preserves_insertion_order() {
-        let mut pending = VecDeque::new();
-        enqueue_command(Command::batch([|| 1, || 2, || 3]), &mut pending);
+        let mut runtime = inline_runtime(TestModel::default());
+        let now = Instant::now();
+
+        runtime.enqueue(Command::batch([|| 1, || 2, || 3]), now);
+        runtime.settle(now);
+
+        assert_eq!(runtime.model.updates, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn pool_executor_runs_commands_off_the_caller_thread() {
+        let mut runtime = Runtime::new(
+            TestModel::default(),
+            ProgramConfig::default(),
+            Executor::pool(2),
+        );
+
+        let started = Instant::now();
+        runtime.enqueue(
+            Command::one(|| {
+                thread::sleep(Duration::from_millis(50));
+                7
+            }),
+            started,
+        );
+        assert!(started.elapsed() < Duration::from_millis(50));
+        assert!(!runtime.executor.is_idle());
 
-        let drained: Vec<i32> = pending.into_iter().collect();
-        assert_eq!(drained, vec![1, 2, 3]);
+        while !runtime.executor.is_idle() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        runtime.settle(Instant::now());
+        assert_eq!(runtime.model.updates, vec![7]);
+    }
+
+    #[test]
+    fn panicking_commands_stop_the_program_with_their_message() {
+        let mut runtime = Runtime::new(
+            TestModel::default(),
+            ProgramConfig::default(),
+            Executor::pool(1),
+        );
+        runtime.enqueue(Command::one(|| -> i32 { panic!("boom") }), Instant::now());
+        while !runtime.executor.is_idle() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        runtime.settle(Instant::now());
+        assert!(matches!(
+            runtime.take_failure(),
+            Some(ProgramError::CommandPanicked(message)) if message == "boom"
+        ));
+
+        let mut app = TestProgram::new(ScriptModel::default(), 10, 2);
+        app.send(ScriptMsg::Run(|| {
+            Command::one(|| -> ScriptMsg { panic!("inline boom") })
+        }));
+        assert!(matches!(
+            app.exit_reason(),
+            Some(ExitReason::Error(ProgramError::CommandPanicked(message)))
+                if message == "inline boom"
+        ));
     }
 
     #[test]
     fn default_exit_keys_match_expected_shortcuts() {
         let keys = ExitKeys::default();
+        let keymap = Keymap::default();
 
         assert!(should_exit_key(
             &KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
-            &keys
+            &keys,
+            &keymap
         ));
         assert!(should_exit_key(
             &KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE),
-            &keys
+            &keys,
+            &keymap
         ));
         assert!(should_exit_key(
             &KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
-            &keys
+            &keys,
+            &keymap
         ));
         assert!(!should_exit_key(
             &KeyEvent::new(KeyCode::Char('q'), KeyModifiers::SHIFT),
-            &keys
+            &keys,
+            &keymap
         ));
 
         let no_q = ExitKeys {
@@ -191,7 +559,527 @@ mod tests {
         };
         assert!(!should_exit_key(
             &KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE),
-            &no_q
+            &no_q,
+            &keymap
+        ));
+    }
+
+    #[test]
+    fn exit_keys_follow_remapped_quit_binding() {
+        let mut keymap = Keymap::default();
+        keymap.remap(
+            context::PROGRAM,
+            action::QUIT,
+            vec!["ctrl+q".parse().unwrap()],
+        );
+        let keys = ExitKeys::default();
+
+        assert!(should_exit_key(
+            &KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL),
+            &keys,
+            &keymap
+        ));
+        assert!(!should_exit_key(
+            &KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE),
+            &keys,
+            &keymap
+        ));
+        assert!(!should_exit_key(
+            &KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+            &keys,
+            &keymap
+        ));
+    }
+
+    #[test]
+    fn timer_subscription_fires_when_due_and_stops_when_dropped() {
+        let executor = Executor::<Outcome<i32>>::inline();
+        let mut subscriptions = Subscriptions::new();
+        let start = Instant::now();
+        let every = || Subscription::every("tick", Duration::from_millis(100), |_| 5);
+
+        subscriptions.sync(vec![every()], &executor.sender(), start);
+        assert_eq!(
+            subscriptions.next_deadline(),
+            Some(start + Duration::from_millis(100))
+        );
+        assert!(subscriptions
+            .fire_due(start + Duration::from_millis(50))
+            .is_empty());
+        assert_eq!(
+            subscriptions.fire_due(start + Duration::from_millis(100)),
+            vec![5]
+        );
+
+        // Re-subscribing with the same id keeps the existing schedule.
+        subscriptions.sync(
+            vec![every()],
+            &executor.sender(),
+            start + Duration::from_millis(150),
+        );
+        assert_eq!(
+            subscriptions.next_deadline(),
+            Some(start + Duration::from_millis(200))
+        );
+
+        subscriptions.sync(Vec::new(), &executor.sender(), start);
+        assert_eq!(subscriptions.next_deadline(), None);
+        assert!(subscriptions
+            .fire_due(start + Duration::from_secs(1))
+            .is_empty());
+    }
+
+    #[test]
+    fn receiver_subscription_forwards_messages_until_removed() {
+        let mut model = TestModel::default();
+        let mut executor = Executor::inline();
+        let mut subscriptions = Subscriptions::new();
+        let (tx, rx) = shared_channel();
+
+        subscriptions.sync(
+            vec![Subscription::receiver("external", rx)],
+            &executor.sender(),
+            Instant::now(),
+        );
+        assert!(subscriptions.has_streams());
+
+        tx.send(42).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while model.updates.is_empty() && Instant::now() < deadline {
+            if let Some(Outcome::Msg(msg)) = executor.try_next() {
+                let _ = model.update(msg);
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(model.updates, vec![42]);
+
+        subscriptions.sync(Vec::new(), &executor.sender(), Instant::now());
+        assert!(!subscriptions.has_streams());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn finished_streams_no_longer_count_as_streams() {
+        let mut executor = Executor::inline();
+        let mut subscriptions = Subscriptions::new();
+        let (tx, rx) = shared_channel();
+        let mut command = std::process::Command::new("sh");
+        command.args(["-c", "echo out; echo err >&2"]);
+
+        subscriptions.sync(
+            vec![
+                Subscription::receiver("external", rx),
+                Subscription::process("script", command, |output| format!("{output:?}")),
+            ],
+            &executor.sender(),
+            Instant::now(),
+        );
+        drop(tx);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while subscriptions.has_streams() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert!(!subscriptions.has_streams());
+
+        let mut seen = Vec::new();
+        while let Some(Outcome::Msg(msg)) = executor.try_next() {
+            seen.push(msg);
+        }
+        assert_eq!(seen.len(), 3);
+        assert!(seen.contains(&r#"Stderr("err")"#.to_string()));
+        assert_eq!(seen[2], "Exited(Some(0))");
+    }
+
+    #[test]
+    fn process_subscription_that_fails_to_spawn_reports_once() {
+        let mut executor = Executor::inline();
+        let mut subscriptions = Subscriptions::new();
+        let missing = || {
+            Subscription::process(
+                "missing",
+                std::process::Command::new("nodecode-no-such-binary"),
+                |output| format!("{output:?}"),
+            )
+        };
+
+        for _ in 0..3 {
+            subscriptions.sync(vec![missing()], &executor.sender(), Instant::now());
+        }
+        let mut seen = Vec::new();
+        while let Some(Outcome::Msg(msg)) = executor.try_next() {
+            seen.push(msg);
+        }
+        assert_eq!(seen.len(), 2);
+        assert!(seen[0].starts_with("Stderr"));
+        assert_eq!(seen[1], "Exited(None)");
+        assert!(!subscriptions.has_streams());
+
+        // Dropping and re-adding the id tries again.
+        subscriptions.sync(Vec::new(), &executor.sender(), Instant::now());
+        subscriptions.sync(vec![missing()], &executor.sender(), Instant::now());
+        let mut retried = 0;
+        while let Some(Outcome::Msg(_)) = executor.try_next() {
+            retried += 1;
+        }
+        assert_eq!(retried, 2);
+    }
+
+    #[derive(Default)]
+    struct EventModel {
+        seen: Vec<String>,
+    }
+
+    impl Model for EventModel {
+        type Msg = String;
+
+        fn update(&mut self, msg: Self::Msg) -> Command<Self::Msg> {
+            self.seen.push(msg);
+            Command::none()
+        }
+
+        fn view(&self, _frame: &mut Frame, _area: Rect, _theme: &Theme) {}
+
+        fn on_mouse(&mut self, mouse: MouseEvent) -> Option<Self::Msg> {
+            Some(format!("mouse {},{}", mouse.column, mouse.row))
+        }
+
+        fn on_resize(&mut self, width: u16, height: u16) -> Option<Self::Msg> {
+            Some(format!("resize {width}x{height}"))
+        }
+
+        fn on_paste(&mut self, text: String) -> Option<Self::Msg> {
+            Some(format!("paste {text}"))
+        }
+
+        fn on_focus(&mut self, focused: bool) -> Option<Self::Msg> {
+            Some(format!("focus {focused}"))
+        }
+    }
+
+    #[test]
+    fn non_key_events_reach_their_model_hooks() {
+        let mut model = EventModel::default();
+        let events = [
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column: 3,
+                row: 4,
+                modifiers: KeyModifiers::NONE,
+            }),
+            Event::Resize(80, 24),
+            Event::Paste("hello".to_string()),
+            Event::FocusGained,
+            Event::FocusLost,
+            Event::Key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE)),
+        ];
+
+        let msgs: Vec<String> = events
+            .into_iter()
+            .filter_map(|event| event_to_msg(&mut model, event))
+            .collect();
+
+        assert_eq!(
+            msgs,
+            vec![
+                "mouse 3,4",
+                "resize 80x24",
+                "paste hello",
+                "focus true",
+                "focus false",
+            ]
+        );
+    }
+
+    #[test]
+    fn insert_before_prints_above_an_inline_viewport() {
+        let mut runtime = inline_runtime(TestModel::default());
+        let now = Instant::now();
+        let mut terminal = Terminal::with_options(
+            TestBackend::new(12, 4),
+            TerminalOptions {
+                viewport: Viewport::Inline(2),
+            },
+        )
+        .unwrap();
+
+        runtime.enqueue(Command::insert_before(["done: a", "done: b"]), now);
+        let effects = runtime.settle(now);
+        assert!(matches!(effects.as_slice(), [Effect::InsertBefore(lines)] if lines.len() == 2));
+        for effect in effects {
+            if let Effect::InsertBefore(lines) = effect {
+                insert_lines(&mut terminal, lines).unwrap();
+            }
+        }
+
+        let buffer = terminal.backend().buffer();
+        let row = |y: u16| -> String {
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        };
+        assert_eq!(row(0), "done: a");
+        assert_eq!(row(1), "done: b");
+        assert!(runtime.model.updates.is_empty());
+    }
+
+    #[derive(Default)]
+    struct CounterModel {
+        value: i32,
+        ticking: bool,
+        width: u16,
+    }
+
+    enum CounterMsg {
+        Add(i32),
+        Tick,
+        Resized(u16),
+    }
+
+    impl Model for CounterModel {
+        type Msg = CounterMsg;
+
+        fn update(&mut self, msg: Self::Msg) -> Command<Self::Msg> {
+            match msg {
+                CounterMsg::Add(n) => self.value += n,
+                CounterMsg::Tick => self.value -= 1,
+                CounterMsg::Resized(width) => self.width = width,
+            }
+            Command::none()
+        }
+
+        fn view(&self, frame: &mut Frame, area: Rect, _theme: &Theme) {
+            frame.render_widget(Paragraph::new(format!("Value: {}", self.value)), area);
+        }
+
+        fn subscriptions(&self) -> Vec<Subscription<Self::Msg>> {
+            if self.ticking {
+                vec![Subscription::every("tick", Duration::from_secs(1), |_| {
+                    CounterMsg::Tick
+                })]
+            } else {
+                Vec::new()
+            }
+        }
+
+        fn on_key(&mut self, key: KeyEvent) -> Option<Self::Msg> {
+            match key.code {
+                KeyCode::Char('+') => Some(CounterMsg::Add(1)),
+                KeyCode::Char('t') => {
+                    self.ticking = !self.ticking;
+                    None
+                }
+                _ => None,
+            }
+        }
+
+        fn on_resize(&mut self, width: u16, _height: u16) -> Option<Self::Msg> {
+            Some(CounterMsg::Resized(width))
+        }
+    }
+
+    #[test]
+    fn test_program_drives_a_model_through_scripted_events() {
+        let mut app = TestProgram::new(CounterModel::default(), 12, 2);
+        assert_eq!(app.screen(), "Value: 0\n");
+
+        app.type_text("++").press(KeyCode::Char('+'));
+        assert_eq!(app.model().value, 3);
+        assert_eq!(app.screen(), "Value: 3\n");
+
+        app.press(KeyCode::Char('t'))
+            .advance(Duration::from_millis(2500));
+        assert_eq!(app.model().value, 1);
+
+        app.resize(20, 3);
+        assert_eq!(app.model().width, 20);
+        assert_eq!(app.buffer().area.width, 20);
+
+        app.press(KeyCode::Esc).press(KeyCode::Char('+'));
+        assert!(matches!(
+            app.exit_reason(),
+            Some(ExitReason::ExitKey(key)) if key.code == KeyCode::Esc
         ));
+        assert_eq!(app.into_model().value, 1);
+    }
+
+    #[derive(Default)]
+    struct ScriptModel {
+        log: Vec<String>,
+    }
+
+    enum ScriptMsg {
+        Run(fn() -> Command<ScriptMsg>),
+        Log(String),
+    }
+
+    fn log(entry: &str) -> ScriptMsg {
+        ScriptMsg::Log(entry.to_string())
+    }
+
+    impl Model for ScriptModel {
+        type Msg = ScriptMsg;
+
+        fn update(&mut self, msg: Self::Msg) -> Command<Self::Msg> {
+            match msg {
+                ScriptMsg::Run(script) => script(),
+                ScriptMsg::Log(entry) => {
+                    self.log.push(entry);
+                    Command::none()
+                }
+            }
+        }
+
+        fn view(&self, _frame: &mut Frame, _area: Rect, _theme: &Theme) {}
+    }
+
+    #[test]
+    fn sequence_waits_for_each_step_including_ticks() {
+        let mut app = TestProgram::new(ScriptModel::default(), 10, 2);
+        app.send(ScriptMsg::Run(|| {
+            Command::sequence([
+                Command::one(|| log("first")),
+                Command::tick(Duration::from_secs(1), |_| log("after tick")),
+                Command::batch([|| log("batch a"), || log("batch b")]),
+                Command::sequence([Command::none(), Command::one(|| log("nested"))]),
+            ])
+        }));
+        assert_eq!(app.model().log, vec!["first"]);
+
+        app.advance(Duration::from_millis(999));
+        assert_eq!(app.model().log, vec!["first"]);
+
+        app.advance(Duration::from_millis(1));
+        assert_eq!(
+            app.model().log,
+            vec!["first", "after tick", "batch a", "batch b", "nested"]
+        );
+    }
+
+    #[test]
+    fn mapped_commands_reach_the_parent_message_type() {
+        enum ChildMsg {
+            Loaded(u8),
+        }
+
+        let child = Command::sequence([
+            Command::one(|| ChildMsg::Loaded(1)),
+            Command::tick(Duration::from_millis(10), |_| ChildMsg::Loaded(2)),
+        ]);
+        let parent = child.map(|ChildMsg::Loaded(n)| ScriptMsg::Log(format!("child {n}")));
+
+        let mut runtime = inline_runtime(ScriptModel::default());
+        let now = Instant::now();
+        runtime.enqueue(parent, now);
+        runtime.settle(now);
+        assert_eq!(runtime.model.log, vec!["child 1"]);
+        runtime.settle(now + Duration::from_millis(10));
+        assert_eq!(runtime.model.log, vec!["child 1", "child 2"]);
+    }
+
+    #[test]
+    fn quit_command_stops_the_program_after_pending_messages() {
+        let mut app = TestProgram::new(ScriptModel::default(), 10, 2);
+        app.send(ScriptMsg::Run(|| {
+            Command::sequence([Command::one(|| log("saved")), Command::quit()])
+        }));
+
+        assert!(matches!(app.exit_reason(), Some(ExitReason::Quit)));
+        assert_eq!(app.model().log, vec!["saved"]);
+        app.send(log("ignored"));
+        assert_eq!(app.model().log, vec!["saved"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn termination_signals_are_reported_while_a_program_listens() {
+        use crate::signals::{Signal, Signals};
+        use signal_hook::consts::SIGTERM;
+
+        let signals = Signals::register().unwrap();
+        assert_eq!(signals.take(), None);
+
+        signal_hook::low_level::raise(SIGTERM).unwrap();
+        assert_eq!(signals.take(), Some(Signal::Terminate(SIGTERM)));
+        assert_eq!(signals.take(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn editor_drafts_are_private_to_the_user() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (path, file) = crate::editor::create_temp_file().unwrap();
+        let mode = file.metadata().unwrap().permissions().mode();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[derive(Default)]
+    struct InputModel {
+        input: TextInput,
+        keymap: Arc<Keymap>,
+    }
+
+    impl Model for InputModel {
+        type Msg = ();
+
+        fn update(&mut self, _msg: Self::Msg) -> Command<Self::Msg> {
+            Command::none()
+        }
+
+        fn view(&self, _frame: &mut Frame, _area: Rect, _theme: &Theme) {}
+
+        fn on_key(&mut self, key: KeyEvent) -> Option<Self::Msg> {
+            self.input.handle_key_with(&self.keymap, key);
+            None
+        }
+    }
+
+    #[test]
+    fn suspend_keys_come_from_the_program_keymap() {
+        let ctrl_z = KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL);
+        let mut app = TestProgram::new(InputModel::default(), 10, 2);
+        app.type_text("ab").key(ctrl_z);
+        assert_eq!(app.model().input.text(), "ab");
+        app.key(KeyEvent::new(KeyCode::Char('_'), KeyModifiers::CONTROL));
+        assert_eq!(app.model().input.text(), "");
+
+        let mut keymap = Keymap::default();
+        keymap.unbind(context::PROGRAM, action::SUSPEND);
+        keymap.remap(
+            context::TEXT_INPUT,
+            action::UNDO,
+            vec!["ctrl+z".parse().unwrap()],
+        );
+        let keymap = Arc::new(keymap);
+        let model = InputModel {
+            keymap: Arc::clone(&keymap),
+            ..InputModel::default()
+        };
+        let config = ProgramConfig::new("test").keymap(keymap);
+        let mut app = TestProgram::with_config(model, config, 10, 2);
+        app.type_text("ab").key(ctrl_z);
+        assert_eq!(app.model().input.text(), "");
+    }
+
+    #[test]
+    fn edit_text_delivers_the_edited_contents_before_the_rest_of_a_sequence() {
+        let mut app = TestProgram::new(ScriptModel::default(), 10, 2);
+        app.editor(|seed| Ok(format!("{seed} and more")));
+        app.send(ScriptMsg::Run(|| {
+            Command::sequence([
+                Command::edit_text("draft", |edited| ScriptMsg::Log(edited.unwrap())),
+                Command::one(|| log("after edit")),
+            ])
+        }));
+        assert_eq!(app.model().log, vec!["draft and more", "after edit"]);
+
+        app.editor(|_| Err(std::io::Error::other("editor crashed")));
+        app.send(ScriptMsg::Run(|| {
+            Command::edit_text("", |edited| log(&edited.unwrap_err().to_string()))
+        }));
+        assert_eq!(app.model().log.last().unwrap(), "editor crashed");
     }
 }
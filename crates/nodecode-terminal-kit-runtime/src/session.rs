@@ -0,0 +1,209 @@
+use std::io::{self, Write};
+use std::panic::{self, PanicHookInfo};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, ThreadId};
+
+use crossterm::cursor::Show;
+use crossterm::event::{
+    DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+    EnableFocusChange, EnableMouseCapture,
+};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use nodecode_terminal_kit::components::dialog_shell::InlineDialogGuard;
+use ratatui::backend::CrosstermBackend;
+use ratatui::{Terminal, TerminalOptions, Viewport};
+
+use crate::executor::WORKER_PREFIX;
+use crate::{ProgramConfig, ProgramError, ViewportMode};
+
+/// Terminal modes a session switches on, remembered so they can be switched
+/// off from `Drop`, the panic hook and on suspend.
+#[derive(Debug, Clone, Copy)]
+struct TerminalModes {
+    alternate_screen: bool,
+    mouse_capture: bool,
+    bracketed_paste: bool,
+    focus_reporting: bool,
+}
+
+impl TerminalModes {
+    fn from_config(config: &ProgramConfig) -> Self {
+        Self {
+            alternate_screen: config.viewport == ViewportMode::Fullscreen,
+            mouse_capture: config.mouse_capture,
+            bracketed_paste: config.bracketed_paste,
+            focus_reporting: config.focus_reporting,
+        }
+    }
+
+    fn enter(self, out: &mut impl Write) -> io::Result<()> {
+        enable_raw_mode()?;
+        if self.alternate_screen {
+            execute!(out, EnterAlternateScreen)?;
+        }
+        if self.mouse_capture {
+            execute!(out, EnableMouseCapture)?;
+        }
+        if self.bracketed_paste {
+            execute!(out, EnableBracketedPaste)?;
+        }
+        if self.focus_reporting {
+            execute!(out, EnableFocusChange)?;
+        }
+        Ok(())
+    }
+
+    /// Best effort: keeps going past failures so as much as possible is restored.
+    fn leave(self, out: &mut impl Write) {
+        if self.focus_reporting {
+            let _ = execute!(out, DisableFocusChange);
+        }
+        if self.bracketed_paste {
+            let _ = execute!(out, DisableBracketedPaste);
+        }
+        if self.mouse_capture {
+            let _ = execute!(out, DisableMouseCapture);
+        }
+        let _ = disable_raw_mode();
+        if self.alternate_screen {
+            let _ = execute!(out, LeaveAlternateScreen);
+        }
+        let _ = execute!(out, Show);
+    }
+}
+
+type PanicHook = dyn Fn(&PanicHookInfo<'_>) + Send + Sync + 'static;
+
+/// Panic hook that restores the terminal before the panic message is printed.
+///
+/// Only panics on the event-loop thread restore the terminal, since command
+/// panics on workers are caught and stop the program through the event loop
+/// with `ExitReason::Error`, which carries their message; it is not printed
+/// while the session holds the screen. With `panic = "abort"` every panic ends the process, so
+/// all of them restore it.
+struct PanicHookGuard {
+    previous: Arc<PanicHook>,
+}
+
+impl PanicHookGuard {
+    fn install(modes: TerminalModes, restored: Arc<AtomicBool>) -> Self {
+        let previous: Arc<PanicHook> = Arc::from(panic::take_hook());
+        let chained = Arc::clone(&previous);
+        let owner: ThreadId = thread::current().id();
+        panic::set_hook(Box::new(move |info| {
+            let fatal = cfg!(panic = "abort") || thread::current().id() == owner;
+            if fatal {
+                if !restored.swap(true, Ordering::SeqCst) {
+                    modes.leave(&mut io::stdout());
+                }
+            } else if is_worker() && !restored.load(Ordering::SeqCst) {
+                return;
+            }
+            chained(info);
+        }));
+        Self { previous }
+    }
+}
+
+fn is_worker() -> bool {
+    thread::current()
+        .name()
+        .is_some_and(|name| name.starts_with(WORKER_PREFIX))
+}
+
+impl Drop for PanicHookGuard {
+    fn drop(&mut self) {
+        // Hooks cannot be swapped while unwinding; ours is harmless by then.
+        if thread::panicking() {
+            return;
+        }
+        let previous = Arc::clone(&self.previous);
+        panic::set_hook(Box::new(move |info| previous(info)));
+    }
+}
+
+pub(crate) struct TerminalSession {
+    pub(crate) terminal: Terminal<CrosstermBackend<io::Stdout>>,
+    modes: TerminalModes,
+    /// Set once the terminal has been handed back, e.g. by the panic hook.
+    restored: Arc<AtomicBool>,
+    /// Present in inline mode so dialogs stretch to the viewport width.
+    inline_dialogs: Option<InlineDialogGuard>,
+    _panic_hook: PanicHookGuard,
+}
+
+impl TerminalSession {
+    pub(crate) fn new(config: &ProgramConfig) -> Result<Self, ProgramError> {
+        let modes = TerminalModes::from_config(config);
+        let mut stdout = io::stdout();
+        if let Err(err) = modes.enter(&mut stdout) {
+            modes.leave(&mut stdout);
+            return Err(err.into());
+        }
+        let restored = Arc::new(AtomicBool::new(false));
+        let panic_hook = PanicHookGuard::install(modes, Arc::clone(&restored));
+
+        let viewport = match config.viewport {
+            ViewportMode::Fullscreen => Viewport::Fullscreen,
+            ViewportMode::Inline { height } => Viewport::Inline(height),
+        };
+        let terminal = match Terminal::with_options(
+            CrosstermBackend::new(stdout),
+            TerminalOptions { viewport },
+        ) {
+            Ok(terminal) => terminal,
+            Err(err) => {
+                modes.leave(&mut io::stdout());
+                return Err(err.into());
+            }
+        };
+        let inline_dialogs =
+            matches!(config.viewport, ViewportMode::Inline { .. }).then(InlineDialogGuard::enable);
+        Ok(Self {
+            terminal,
+            modes,
+            restored,
+            inline_dialogs,
+            _panic_hook: panic_hook,
+        })
+    }
+
+    /// Hand the terminal back to the shell while `f` runs, e.g. an external
+    /// editor, then take it over again and repaint.
+    pub(crate) fn release<T>(&mut self, f: impl FnOnce() -> T) -> io::Result<T> {
+        self.modes.leave(self.terminal.backend_mut());
+        let out = f();
+        self.resume()?;
+        Ok(out)
+    }
+
+    /// Stop the process until the shell continues it.
+    pub(crate) fn suspend(&mut self, stop: impl FnOnce() -> io::Result<()>) -> io::Result<()> {
+        self.release(stop)?
+    }
+
+    /// Re-enter every mode and repaint from scratch, e.g. after `SIGCONT`.
+    pub(crate) fn resume(&mut self) -> io::Result<()> {
+        self.modes.enter(self.terminal.backend_mut())?;
+        self.terminal.clear()
+    }
+}
+
+impl Drop for TerminalSession {
+    fn drop(&mut self) {
+        if self.restored.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let inline = self.inline_dialogs.take().is_some();
+        self.modes.leave(self.terminal.backend_mut());
+        if inline {
+            // Leave the shell prompt where the viewport started.
+            let _ = self.terminal.clear();
+        }
+        let _ = self.terminal.show_cursor();
+    }
+}
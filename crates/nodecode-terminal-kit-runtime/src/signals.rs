@@ -0,0 +1,134 @@
+//! Process signals the event loop reacts to.
+//!
+//! Raw mode disables the terminal's own Ctrl-Z/Ctrl-C handling, so these only
+//! fire when a signal is sent from outside (`kill -TSTP`, a closing terminal,
+//! a service manager). On non-Unix platforms nothing is registered.
+
+/// Signal observed since the last poll.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Signal {
+    /// SIGTSTP: restore the terminal and stop until continued.
+    Suspend,
+    /// SIGCONT after being stopped by someone else: redraw from scratch.
+    Resumed,
+    /// SIGTERM, SIGHUP or SIGINT: leave the loop cleanly.
+    Terminate(i32),
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::io;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{Arc, OnceLock};
+
+    use signal_hook::consts::{SIGCONT, SIGHUP, SIGINT, SIGTERM, SIGTSTP};
+    use signal_hook::{flag, low_level};
+
+    use super::Signal;
+
+    /// Handlers are installed once per process and never removed: signal-hook
+    /// cannot restore default dispositions on unregister. While no program is
+    /// running, `idle` makes every handled signal fall back to its default.
+    struct Flags {
+        idle: Arc<AtomicBool>,
+        suspend: Arc<AtomicBool>,
+        resumed: Arc<AtomicBool>,
+        terminate: Arc<AtomicUsize>,
+    }
+
+    static FLAGS: OnceLock<Result<Flags, String>> = OnceLock::new();
+
+    fn install() -> io::Result<Flags> {
+        let flags = Flags {
+            idle: Arc::new(AtomicBool::new(true)),
+            suspend: Arc::new(AtomicBool::new(false)),
+            resumed: Arc::new(AtomicBool::new(false)),
+            terminate: Arc::new(AtomicUsize::new(0)),
+        };
+        flag::register_conditional_default(SIGTSTP, Arc::clone(&flags.idle))?;
+        flag::register(SIGTSTP, Arc::clone(&flags.suspend))?;
+        flag::register(SIGCONT, Arc::clone(&flags.resumed))?;
+        for signal in [SIGTERM, SIGHUP, SIGINT] {
+            flag::register_conditional_default(signal, Arc::clone(&flags.idle))?;
+            flag::register_usize(signal, Arc::clone(&flags.terminate), signal as usize)?;
+        }
+        Ok(flags)
+    }
+
+    /// Listens for signals while alive; dropping it restores default behaviour.
+    pub(crate) struct Signals {
+        flags: &'static Flags,
+    }
+
+    impl Signals {
+        pub(crate) fn register() -> io::Result<Self> {
+            let flags = FLAGS
+                .get_or_init(|| install().map_err(|err| err.to_string()))
+                .as_ref()
+                .map_err(|err| io::Error::other(err.clone()))?;
+            flags.suspend.store(false, Ordering::SeqCst);
+            flags.resumed.store(false, Ordering::SeqCst);
+            flags.terminate.store(0, Ordering::SeqCst);
+            flags.idle.store(false, Ordering::SeqCst);
+            Ok(Self { flags })
+        }
+
+        pub(crate) fn take(&self) -> Option<Signal> {
+            let terminate = self.flags.terminate.swap(0, Ordering::SeqCst);
+            if terminate != 0 {
+                return Some(Signal::Terminate(terminate as i32));
+            }
+            if self.flags.suspend.swap(false, Ordering::SeqCst) {
+                return Some(Signal::Suspend);
+            }
+            self.flags
+                .resumed
+                .swap(false, Ordering::SeqCst)
+                .then_some(Signal::Resumed)
+        }
+
+        /// Stop the whole process like the default SIGTSTP action; returns once
+        /// the shell continues it.
+        pub(crate) fn stop_process(&self) -> io::Result<()> {
+            low_level::emulate_default_handler(SIGTSTP)?;
+            // The caller redraws after our own stop; don't do it twice.
+            self.flags.resumed.store(false, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    impl Drop for Signals {
+        fn drop(&mut self) {
+            self.flags.idle.store(true, Ordering::SeqCst);
+        }
+    }
+
+    pub(crate) const CAN_SUSPEND: bool = true;
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use std::io;
+
+    use super::Signal;
+
+    pub(crate) struct Signals;
+
+    impl Signals {
+        pub(crate) fn register() -> io::Result<Self> {
+            Ok(Self)
+        }
+
+        pub(crate) fn take(&self) -> Option<Signal> {
+            None
+        }
+
+        pub(crate) fn stop_process(&self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    pub(crate) const CAN_SUSPEND: bool = false;
+}
+
+pub(crate) use imp::{Signals, CAN_SUSPEND};
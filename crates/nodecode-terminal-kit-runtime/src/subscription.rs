@@ -0,0 +1,387 @@
+use std::io::{BufRead, BufReader, Read};
+use std::process::{self, Child, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::command::Outcome;
+
+/// Receiver that can be handed to `Subscription::receiver` on every update.
+pub type SharedReceiver<Msg> = Arc<Mutex<Receiver<Msg>>>;
+
+/// How long forwarding threads wait on a channel before re-checking for cancellation.
+const STREAM_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Create a channel whose receiving half can be shared with `Subscription::receiver`.
+#[must_use]
+pub fn shared_channel<Msg>() -> (Sender<Msg>, SharedReceiver<Msg>) {
+    let (tx, rx) = mpsc::channel();
+    (tx, Arc::new(Mutex::new(rx)))
+}
+
+/// Output produced by a `Subscription::process` child.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProcessOutput {
+    Stdout(String),
+    Stderr(String),
+    /// The child exited on its own; carries the exit code when available.
+    Exited(Option<i32>),
+}
+
+/// Declarative message source returned by `Model::subscriptions`.
+///
+/// Subscriptions are keyed by id: the program starts sources whose id is new,
+/// keeps running the ones it already knows, and stops any id that is no longer
+/// returned.
+pub struct Subscription<Msg> {
+    id: String,
+    kind: SubscriptionKind<Msg>,
+}
+
+enum SubscriptionKind<Msg> {
+    Every {
+        interval: Duration,
+        to_msg: Box<dyn Fn(Instant) -> Msg + Send>,
+    },
+    Receiver(SharedReceiver<Msg>),
+    Process {
+        command: process::Command,
+        to_msg: Arc<dyn Fn(ProcessOutput) -> Msg + Send + Sync>,
+    },
+}
+
+impl<Msg: Send + 'static> Subscription<Msg> {
+    /// Emit a message every `interval`, starting one interval after activation.
+    #[must_use]
+    pub fn every<F>(id: impl Into<String>, interval: Duration, to_msg: F) -> Self
+    where
+        F: Fn(Instant) -> Msg + Send + 'static,
+    {
+        Self {
+            id: id.into(),
+            kind: SubscriptionKind::Every {
+                interval: interval.max(Duration::from_millis(1)),
+                to_msg: Box::new(to_msg),
+            },
+        }
+    }
+
+    /// Forward every message sent on a shared channel (e.g. from a watcher thread).
+    #[must_use]
+    pub fn receiver(id: impl Into<String>, receiver: SharedReceiver<Msg>) -> Self {
+        Self {
+            id: id.into(),
+            kind: SubscriptionKind::Receiver(receiver),
+        }
+    }
+
+    /// Spawn a child process and stream its output line by line.
+    ///
+    /// The child is killed when the subscription is dropped from the model.
+    #[must_use]
+    pub fn process<F>(id: impl Into<String>, command: process::Command, to_msg: F) -> Self
+    where
+        F: Fn(ProcessOutput) -> Msg + Send + Sync + 'static,
+    {
+        Self {
+            id: id.into(),
+            kind: SubscriptionKind::Process {
+                command,
+                to_msg: Arc::new(to_msg),
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Subscriptions currently wired into a program's event loop.
+pub(crate) struct Subscriptions<Msg> {
+    active: Vec<ActiveSubscription<Msg>>,
+}
+
+struct ActiveSubscription<Msg> {
+    id: String,
+    source: ActiveSource<Msg>,
+}
+
+enum ActiveSource<Msg> {
+    Timer {
+        interval: Duration,
+        next: Instant,
+        to_msg: Box<dyn Fn(Instant) -> Msg + Send>,
+    },
+    Stream {
+        stop: Arc<AtomicBool>,
+        /// Set by the forwarding thread once nothing more can arrive.
+        done: Arc<AtomicBool>,
+        child: Option<Arc<Mutex<Child>>>,
+    },
+    /// A process that failed to spawn. It stays in place so the failure is
+    /// reported once, not again on every sync.
+    Finished,
+}
+
+impl<Msg> ActiveSource<Msg> {
+    fn stop(&self) {
+        if let Self::Stream { stop, child, .. } = self {
+            stop.store(true, Ordering::SeqCst);
+            if let Some(child) = child {
+                if let Ok(mut child) = child.lock() {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                }
+            }
+        }
+    }
+}
+
+impl<Msg: Send + 'static> Subscriptions<Msg> {
+    pub(crate) fn new() -> Self {
+        Self { active: Vec::new() }
+    }
+
+    /// Diff `wanted` against the running set: start new ids, stop missing ones.
+    ///
+    /// Timers that stay subscribed keep their schedule but pick up the latest
+    /// interval and message constructor.
+    pub(crate) fn sync(
+        &mut self,
+        wanted: Vec<Subscription<Msg>>,
+        sink: &Sender<Outcome<Msg>>,
+        now: Instant,
+    ) {
+        let mut next_active = Vec::with_capacity(wanted.len());
+        for sub in wanted {
+            if next_active
+                .iter()
+                .any(|active: &ActiveSubscription<Msg>| active.id == sub.id)
+            {
+                continue;
+            }
+            let existing = self.active.iter().position(|active| active.id == sub.id);
+            let Some(idx) = existing else {
+                let source = start(sub.kind, sink, now);
+                next_active.push(ActiveSubscription { id: sub.id, source });
+                continue;
+            };
+            let mut active = self.active.swap_remove(idx);
+            if let (
+                ActiveSource::Timer {
+                    interval, to_msg, ..
+                },
+                SubscriptionKind::Every {
+                    interval: new_interval,
+                    to_msg: new_to_msg,
+                },
+            ) = (&mut active.source, sub.kind)
+            {
+                *interval = new_interval;
+                *to_msg = new_to_msg;
+            }
+            next_active.push(active);
+        }
+
+        for removed in self.active.drain(..) {
+            removed.source.stop();
+        }
+        self.active = next_active;
+    }
+
+    /// Messages for every timer that is due at `now`.
+    pub(crate) fn fire_due(&mut self, now: Instant) -> Vec<Msg> {
+        let mut fired = Vec::new();
+        for active in &mut self.active {
+            if let ActiveSource::Timer {
+                interval,
+                next,
+                to_msg,
+            } = &mut active.source
+            {
+                if *next <= now {
+                    fired.push(to_msg(now));
+                    // Skip missed ticks instead of bursting to catch up.
+                    *next = now + *interval;
+                }
+            }
+        }
+        fired
+    }
+
+    /// Earliest instant at which a timer wants to fire.
+    pub(crate) fn next_deadline(&self) -> Option<Instant> {
+        self.active
+            .iter()
+            .filter_map(|active| match &active.source {
+                ActiveSource::Timer { next, .. } => Some(*next),
+                ActiveSource::Stream { .. } | ActiveSource::Finished => None,
+            })
+            .min()
+    }
+
+    /// True while a channel or process may push messages at any time.
+    pub(crate) fn has_streams(&self) -> bool {
+        self.active.iter().any(|active| {
+            matches!(&active.source, ActiveSource::Stream { done, .. } if !done.load(Ordering::SeqCst))
+        })
+    }
+}
+
+impl<Msg> Drop for Subscriptions<Msg> {
+    fn drop(&mut self) {
+        for active in &self.active {
+            active.source.stop();
+        }
+    }
+}
+
+fn start<Msg: Send + 'static>(
+    kind: SubscriptionKind<Msg>,
+    sink: &Sender<Outcome<Msg>>,
+    now: Instant,
+) -> ActiveSource<Msg> {
+    match kind {
+        SubscriptionKind::Every { interval, to_msg } => ActiveSource::Timer {
+            interval,
+            next: now + interval,
+            to_msg,
+        },
+        SubscriptionKind::Receiver(receiver) => {
+            let stop = Arc::new(AtomicBool::new(false));
+            let done = Arc::new(AtomicBool::new(false));
+            let thread_stop = Arc::clone(&stop);
+            let thread_done = DoneOnDrop(Arc::clone(&done));
+            let sink = sink.clone();
+            thread::spawn(move || {
+                let _done = thread_done;
+                forward_receiver(&receiver, &sink, &thread_stop);
+            });
+            ActiveSource::Stream {
+                stop,
+                done,
+                child: None,
+            }
+        }
+        SubscriptionKind::Process {
+            mut command,
+            to_msg,
+        } => {
+            let spawned = command
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn();
+            let mut child = match spawned {
+                Ok(child) => child,
+                Err(err) => {
+                    let _ = sink.send(Outcome::Msg(to_msg(ProcessOutput::Stderr(err.to_string()))));
+                    let _ = sink.send(Outcome::Msg(to_msg(ProcessOutput::Exited(None))));
+                    return ActiveSource::Finished;
+                }
+            };
+            let stdout = child.stdout.take();
+            let stderr = child.stderr.take();
+            let child = Arc::new(Mutex::new(child));
+            let stop = Arc::new(AtomicBool::new(false));
+            let done = Arc::new(AtomicBool::new(false));
+
+            let stderr_thread = stderr.map(|stderr| {
+                let sink = sink.clone();
+                let to_msg = Arc::clone(&to_msg);
+                let stop = Arc::clone(&stop);
+                thread::spawn(move || {
+                    forward_lines(stderr, &stop, |line| {
+                        sink.send(Outcome::Msg(to_msg(ProcessOutput::Stderr(line))))
+                            .is_ok()
+                    });
+                })
+            });
+
+            let sink = sink.clone();
+            let thread_child = Arc::clone(&child);
+            let thread_stop = Arc::clone(&stop);
+            let thread_done = DoneOnDrop(Arc::clone(&done));
+            thread::spawn(move || {
+                let _done = thread_done;
+                if let Some(stdout) = stdout {
+                    forward_lines(stdout, &thread_stop, |line| {
+                        sink.send(Outcome::Msg(to_msg(ProcessOutput::Stdout(line))))
+                            .is_ok()
+                    });
+                }
+                // Poll instead of blocking in `wait` so `stop` can always take the lock.
+                let code = loop {
+                    if thread_stop.load(Ordering::SeqCst) {
+                        return;
+                    }
+                    let status = match thread_child.lock() {
+                        Ok(mut child) => child.try_wait(),
+                        Err(_) => return,
+                    };
+                    match status {
+                        Ok(Some(status)) => break status.code(),
+                        Ok(None) => thread::sleep(STREAM_POLL_INTERVAL),
+                        Err(_) => break None,
+                    }
+                };
+                // Every stderr line arrives before `Exited`.
+                if let Some(stderr_thread) = stderr_thread {
+                    let _ = stderr_thread.join();
+                }
+                let _ = sink.send(Outcome::Msg(to_msg(ProcessOutput::Exited(code))));
+            });
+
+            ActiveSource::Stream {
+                stop,
+                done,
+                child: Some(child),
+            }
+        }
+    }
+}
+
+/// Marks a stream done when its forwarding thread returns, however it returns.
+struct DoneOnDrop(Arc<AtomicBool>);
+
+impl Drop for DoneOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+fn forward_receiver<Msg>(
+    receiver: &SharedReceiver<Msg>,
+    sink: &Sender<Outcome<Msg>>,
+    stop: &AtomicBool,
+) {
+    while !stop.load(Ordering::SeqCst) {
+        let Ok(receiver) = receiver.lock() else {
+            return;
+        };
+        match receiver.recv_timeout(STREAM_POLL_INTERVAL) {
+            Ok(msg) => {
+                if stop.load(Ordering::SeqCst) || sink.send(Outcome::Msg(msg)).is_err() {
+                    return;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+    }
+}
+
+fn forward_lines(source: impl Read, stop: &AtomicBool, mut emit: impl FnMut(String) -> bool) {
+    for line in BufReader::new(source).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        if stop.load(Ordering::SeqCst) || !emit(line) {
+            return;
+        }
+    }
+}
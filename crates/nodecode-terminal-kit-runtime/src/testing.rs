@@ -0,0 +1,272 @@
+//! Headless driver for exercising a `Model` end-to-end without a TTY.
+
+use std::io;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::{Terminal, TerminalOptions, Viewport};
+
+use crate::command::Effect;
+use crate::executor::Executor;
+use crate::program::{insert_lines, Runtime};
+use crate::{ExitReason, Model, ProgramConfig, ViewportMode};
+
+type EditorStub = dyn FnMut(&str) -> io::Result<String>;
+
+/// Drive a `Model` through scripted events and inspect what it renders.
+///
+/// Commands run inline on the calling thread, so every step is fully applied
+/// before it returns. Timer subscriptions follow a virtual clock that only
+/// moves through `advance`. The screen is re-rendered after every step.
+///
+/// ```ignore
+/// let mut app = TestProgram::new(Counter::default(), 20, 3);
+/// app.press(KeyCode::Char('+')).press(KeyCode::Char('+'));
+/// assert_eq!(app.model().value, 2);
+/// assert!(app.screen().contains("Value: 2"));
+/// ```
+pub struct TestProgram<M: Model> {
+    runtime: Runtime<M>,
+    terminal: Terminal<TestBackend>,
+    clock: Instant,
+    exit: Option<ExitReason>,
+    editor: Box<EditorStub>,
+}
+
+impl<M: Model> TestProgram<M> {
+    /// Start `model` on a `width` x `height` screen with the default config.
+    #[must_use]
+    pub fn new(model: M, width: u16, height: u16) -> Self {
+        Self::with_config(model, ProgramConfig::default(), width, height)
+    }
+
+    /// Start `model` with an explicit config; inline viewports are honoured.
+    #[must_use]
+    pub fn with_config(model: M, config: ProgramConfig, width: u16, height: u16) -> Self {
+        let viewport = match config.viewport {
+            ViewportMode::Fullscreen => Viewport::Fullscreen,
+            ViewportMode::Inline { height } => Viewport::Inline(height),
+        };
+        let terminal = match Terminal::with_options(
+            TestBackend::new(width, height),
+            TerminalOptions { viewport },
+        ) {
+            Ok(terminal) => terminal,
+            Err(never) => match never {},
+        };
+        let mut this = Self {
+            runtime: Runtime::new(model, config, Executor::inline()),
+            terminal,
+            clock: Instant::now(),
+            exit: None,
+            editor: Box::new(|seed| Ok(seed.to_string())),
+        };
+        let clock = this.clock;
+        this.runtime.start(clock);
+        this.step();
+        this
+    }
+
+    #[must_use]
+    pub fn model(&self) -> &M {
+        &self.runtime.model
+    }
+
+    /// Mutable access for arranging state; call `step` to re-render afterwards.
+    pub fn model_mut(&mut self) -> &mut M {
+        &mut self.runtime.model
+    }
+
+    #[must_use]
+    pub fn into_model(self) -> M {
+        self.runtime.model
+    }
+
+    /// Buffer produced by the most recent render.
+    ///
+    /// In inline mode this includes rows printed above the viewport.
+    #[must_use]
+    pub fn buffer(&self) -> &Buffer {
+        self.terminal.backend().buffer()
+    }
+
+    /// Rendered screen as text, one line per row with trailing spaces trimmed.
+    #[must_use]
+    pub fn screen(&self) -> String {
+        let buffer = self.buffer();
+        let mut rows = Vec::with_capacity(buffer.area.height as usize);
+        for y in buffer.area.top()..buffer.area.bottom() {
+            let mut row = String::new();
+            for x in buffer.area.left()..buffer.area.right() {
+                row.push_str(buffer[(x, y)].symbol());
+            }
+            rows.push(row.trim_end().to_string());
+        }
+        rows.join("\n")
+    }
+
+    /// Stand in for the external editor used by `Command::edit_text`.
+    ///
+    /// By default the seed comes back unchanged, as if saved without edits.
+    pub fn editor<F>(&mut self, editor: F) -> &mut Self
+    where
+        F: FnMut(&str) -> io::Result<String> + 'static,
+    {
+        self.editor = Box::new(editor);
+        self
+    }
+
+    /// True once an exit key was pressed, the model returned `Command::quit` or
+    /// a command panicked; later events are ignored.
+    #[must_use]
+    pub fn has_exited(&self) -> bool {
+        self.exit.is_some()
+    }
+
+    /// Why the program stopped, once it has.
+    #[must_use]
+    pub fn exit_reason(&self) -> Option<&ExitReason> {
+        self.exit.as_ref()
+    }
+
+    /// Feed a message straight into `Model::update`.
+    pub fn send(&mut self, msg: M::Msg) -> &mut Self {
+        if !self.has_exited() {
+            self.runtime.dispatch(msg, self.clock);
+            self.step();
+        }
+        self
+    }
+
+    /// Feed any terminal event, exactly as `Program::run` would.
+    pub fn event(&mut self, event: Event) -> &mut Self {
+        if self.has_exited() {
+            return self;
+        }
+        // There is no shell to suspend to; the key is just not delivered.
+        if self.runtime.suspends(&event) {
+            return self;
+        }
+        if let Event::Resize(width, height) = event {
+            self.terminal.backend_mut().resize(width, height);
+        }
+        self.exit = self.runtime.handle_event(event, self.clock);
+        if !self.has_exited() {
+            self.step();
+        }
+        self
+    }
+
+    pub fn key(&mut self, key: KeyEvent) -> &mut Self {
+        self.event(Event::Key(key))
+    }
+
+    /// Press a key without modifiers.
+    pub fn press(&mut self, code: KeyCode) -> &mut Self {
+        self.key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    /// Press a key while holding `modifiers`.
+    pub fn press_with(&mut self, code: KeyCode, modifiers: KeyModifiers) -> &mut Self {
+        self.key(KeyEvent::new(code, modifiers))
+    }
+
+    /// Type each character of `text` as an individual key press.
+    pub fn type_text(&mut self, text: &str) -> &mut Self {
+        for ch in text.chars() {
+            self.press(KeyCode::Char(ch));
+        }
+        self
+    }
+
+    pub fn mouse(&mut self, mouse: MouseEvent) -> &mut Self {
+        self.event(Event::Mouse(mouse))
+    }
+
+    /// Left-click (press and release) at a cell.
+    pub fn click(&mut self, column: u16, row: u16) -> &mut Self {
+        for kind in [
+            MouseEventKind::Down(MouseButton::Left),
+            MouseEventKind::Up(MouseButton::Left),
+        ] {
+            self.mouse(MouseEvent {
+                kind,
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            });
+        }
+        self
+    }
+
+    /// Resize the virtual terminal and deliver the resize event.
+    pub fn resize(&mut self, width: u16, height: u16) -> &mut Self {
+        self.event(Event::Resize(width, height))
+    }
+
+    pub fn paste(&mut self, text: impl Into<String>) -> &mut Self {
+        self.event(Event::Paste(text.into()))
+    }
+
+    /// Move the virtual clock forward, firing timer subscriptions and
+    /// `Command::tick` delays that come due.
+    ///
+    /// Time advances in hops between timer deadlines so a long jump fires every
+    /// interval it crosses, in order.
+    pub fn advance(&mut self, by: Duration) -> &mut Self {
+        let target = self.clock + by;
+        while let Some(deadline) = self.runtime.next_deadline().filter(|d| *d <= target) {
+            if self.has_exited() {
+                return self;
+            }
+            self.clock = self.clock.max(deadline);
+            self.step();
+        }
+        self.clock = target;
+        self.step()
+    }
+
+    /// Apply everything that has completed and re-render.
+    ///
+    /// Useful after messages arrive from receiver or process subscriptions,
+    /// which run on their own threads.
+    pub fn step(&mut self) -> &mut Self {
+        if self.has_exited() {
+            return self;
+        }
+        let mut settling = true;
+        while settling {
+            settling = false;
+            for effect in self.runtime.settle(self.clock) {
+                match effect {
+                    Effect::InsertBefore(lines) => {
+                        if let Err(err) = insert_lines(&mut self.terminal, lines) {
+                            panic!("TestProgram insert_before failed: {err}");
+                        }
+                    }
+                    Effect::EditText(request) => {
+                        let edited = (self.editor)(&request.seed);
+                        self.runtime.complete_edit(request, edited, self.clock);
+                        settling = true;
+                    }
+                }
+            }
+        }
+        if let Some(err) = self.runtime.take_failure() {
+            self.exit = Some(ExitReason::Error(err));
+            return self;
+        }
+        if self.runtime.quit_requested() {
+            self.exit = Some(ExitReason::Quit);
+            return self;
+        }
+        if let Err(err) = self.runtime.render(&mut self.terminal) {
+            panic!("TestProgram render failed: {err}");
+        }
+        self
+    }
+}
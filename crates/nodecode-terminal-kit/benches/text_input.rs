@@ -0,0 +1,97 @@
+//! Keystroke latency of `TextInput` and `InputBox` as the buffer grows.
+//!
+//! Run with `cargo bench -p nodecode-terminal-kit`. Each group measures the
+//! same operation at several buffer sizes. Edits within a line cost
+//! O(log n) in the line count plus the line's length, and a frame only
+//! builds the rows on screen, so these should stay roughly flat as the
+//! buffer grows. Splitting or joining lines still rebuilds the per-line
+//! totals in O(n), and a highlighter, when set, sees the whole text.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use nodecode_terminal_kit::components::input_box::InputBox;
+use nodecode_terminal_kit::components::text_input::TextInput;
+use nodecode_terminal_kit::theme::Theme;
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::Terminal;
+
+const LINE_COUNTS: [usize; 3] = [100, 1_000, 5_000];
+const WIDTH: u16 = 80;
+const HEIGHT: u16 = 20;
+
+/// A buffer of `lines` lines, some long enough to wrap, with the cursor in
+/// the middle.
+fn buffer(lines: usize) -> TextInput {
+    let text = (0..lines)
+        .map(|idx| {
+            let words = if idx % 3 == 0 { 30 } else { 8 };
+            format!("{idx}: {}", "lorem ipsum ".repeat(words))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut input = TextInput::with_text(text);
+    input.set_cursor_byte_offset(input.text().len() / 2);
+    input
+}
+
+fn draw(terminal: &mut Terminal<TestBackend>, input: &TextInput, theme: &Theme) {
+    terminal
+        .draw(|frame| {
+            InputBox::new(input, theme)
+                .follow_cursor(true)
+                .render(frame, frame.area());
+        })
+        .unwrap();
+}
+
+/// Type one character and take it back, so the buffer stays the same size.
+fn type_and_erase(input: &mut TextInput) {
+    input.handle_key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE));
+    input.handle_key(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+}
+
+fn keystroke(c: &mut Criterion) {
+    let mut group = c.benchmark_group("keystroke");
+    for lines in LINE_COUNTS {
+        let mut input = buffer(lines);
+        group.bench_with_input(BenchmarkId::from_parameter(lines), &lines, |b, _| {
+            b.iter(|| type_and_erase(&mut input));
+        });
+    }
+    group.finish();
+}
+
+fn keystroke_and_render(c: &mut Criterion) {
+    let theme = Theme::default();
+    let mut group = c.benchmark_group("keystroke_and_render");
+    for lines in LINE_COUNTS {
+        let mut input = buffer(lines);
+        let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+        draw(&mut terminal, &input, &theme);
+        group.bench_with_input(BenchmarkId::from_parameter(lines), &lines, |b, _| {
+            b.iter(|| {
+                type_and_erase(&mut input);
+                draw(&mut terminal, &input, &theme);
+            });
+        });
+    }
+    group.finish();
+}
+
+fn undo(c: &mut Criterion) {
+    let mut group = c.benchmark_group("undo_redo");
+    for lines in LINE_COUNTS {
+        let mut input = buffer(lines);
+        input.insert_str("word");
+        group.bench_with_input(BenchmarkId::from_parameter(lines), &lines, |b, _| {
+            b.iter(|| {
+                input.undo();
+                input.redo();
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, keystroke, keystroke_and_render, undo);
+criterion_main!(benches);
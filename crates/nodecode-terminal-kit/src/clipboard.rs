@@ -0,0 +1,264 @@
+//! Clipboard backends for copy/cut/paste in text inputs.
+//!
+//! The default backend copies with OSC 52, which the terminal forwards to the
+//! local system clipboard, so it also works over SSH. Terminals rarely allow
+//! reading the clipboard back, so OSC 52 paste returns the last text copied
+//! from this process; pastes from outside arrive as bracketed-paste events
+//! and go through `TextInput::paste`. [`CommandClipboard`] talks to the
+//! system clipboard through `pbcopy`, `wl-copy`, `xclip` and friends when the
+//! app runs locally.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ClipboardError {
+    #[error("clipboard i/o failed: {0}")]
+    Io(String),
+    #[error("no clipboard tool found")]
+    Unavailable,
+}
+
+impl From<io::Error> for ClipboardError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err.to_string())
+    }
+}
+
+/// Somewhere copied text can be stored and read back.
+pub trait Clipboard: Send {
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError>;
+
+    /// Current clipboard text, or `None` when empty or unreadable.
+    fn get_text(&mut self) -> Result<Option<String>, ClipboardError>;
+}
+
+/// The OSC 52 "set clipboard" sequence for `text`.
+pub fn osc52_sequence(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", STANDARD.encode(text))
+}
+
+/// Wrap an escape sequence so tmux passes it through to the outer terminal.
+pub fn tmux_passthrough(sequence: &str) -> String {
+    format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+}
+
+/// Copies by writing OSC 52 to a terminal.
+#[derive(Debug)]
+pub struct Osc52Clipboard<W> {
+    writer: W,
+    tmux: bool,
+    last: Option<String>,
+}
+
+impl Osc52Clipboard<io::Stdout> {
+    /// Write to stdout, wrapping for tmux when `$TMUX` is set.
+    pub fn stdout() -> Self {
+        Self::new(io::stdout()).tmux(std::env::var_os("TMUX").is_some())
+    }
+}
+
+impl<W: Write + Send> Osc52Clipboard<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            tmux: false,
+            last: None,
+        }
+    }
+
+    #[must_use]
+    pub fn tmux(mut self, tmux: bool) -> Self {
+        self.tmux = tmux;
+        self
+    }
+
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+}
+
+impl<W: Write + Send> Clipboard for Osc52Clipboard<W> {
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+        let mut sequence = osc52_sequence(text);
+        if self.tmux {
+            sequence = tmux_passthrough(&sequence);
+        }
+        self.writer.write_all(sequence.as_bytes())?;
+        self.writer.flush()?;
+        self.last = Some(text.to_string());
+        Ok(())
+    }
+
+    fn get_text(&mut self) -> Result<Option<String>, ClipboardError> {
+        Ok(self.last.clone())
+    }
+}
+
+/// In-process clipboard, for tests and headless use.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryClipboard {
+    text: Option<String>,
+}
+
+impl MemoryClipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Clipboard for MemoryClipboard {
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+        self.text = Some(text.to_string());
+        Ok(())
+    }
+
+    fn get_text(&mut self) -> Result<Option<String>, ClipboardError> {
+        Ok(self.text.clone())
+    }
+}
+
+/// System clipboard through external copy/paste programs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandClipboard {
+    copy: Vec<String>,
+    paste: Vec<String>,
+}
+
+impl CommandClipboard {
+    /// Explicit programs, e.g. `(["xsel", "-ib"], ["xsel", "-ob"])`.
+    pub fn new<I, S>(copy: I, paste: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            copy: copy.into_iter().map(Into::into).collect(),
+            paste: paste.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Pick the platform's usual tools: `pbcopy` on macOS, `clip.exe` and
+    /// PowerShell on Windows, `wl-copy` under Wayland, otherwise `xclip`.
+    pub fn detect() -> Result<Self, ClipboardError> {
+        let (copy, paste): (&[&str], &[&str]) = if cfg!(target_os = "macos") {
+            (&["pbcopy"], &["pbpaste"])
+        } else if cfg!(windows) {
+            (
+                &["clip.exe"],
+                &["powershell.exe", "-NoProfile", "-Command", "Get-Clipboard"],
+            )
+        } else if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            (&["wl-copy"], &["wl-paste", "--no-newline"])
+        } else if std::env::var_os("DISPLAY").is_some() {
+            (
+                &["xclip", "-selection", "clipboard"],
+                &["xclip", "-selection", "clipboard", "-o"],
+            )
+        } else {
+            return Err(ClipboardError::Unavailable);
+        };
+        Ok(Self::new(copy.iter().copied(), paste.iter().copied()))
+    }
+
+    fn command(argv: &[String]) -> Result<Command, ClipboardError> {
+        let (program, args) = argv.split_first().ok_or(ClipboardError::Unavailable)?;
+        let mut command = Command::new(program);
+        command.args(args).stderr(Stdio::null());
+        Ok(command)
+    }
+}
+
+impl Clipboard for CommandClipboard {
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+        let mut child = Self::command(&self.copy)?
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(ClipboardError::Io(format!(
+                "{} exited with {status}",
+                self.copy[0]
+            )));
+        }
+        Ok(())
+    }
+
+    fn get_text(&mut self) -> Result<Option<String>, ClipboardError> {
+        let output = Self::command(&self.paste)?.stdin(Stdio::null()).output()?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let text = String::from_utf8_lossy(&output.stdout).into_owned();
+        Ok((!text.is_empty()).then_some(text))
+    }
+}
+
+/// A clipboard an input holds instead of the process-wide one; clones share
+/// the same backend.
+#[derive(Clone)]
+pub struct SharedClipboard(Arc<Mutex<dyn Clipboard>>);
+
+impl SharedClipboard {
+    pub fn new(clipboard: impl Clipboard + 'static) -> Self {
+        Self(Arc::new(Mutex::new(clipboard)))
+    }
+
+    pub fn copy(&self, text: &str) -> Result<(), ClipboardError> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .set_text(text)
+    }
+
+    pub fn paste(&self) -> Result<Option<String>, ClipboardError> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_text()
+    }
+}
+
+impl fmt::Debug for SharedClipboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedClipboard").finish_non_exhaustive()
+    }
+}
+
+static ACTIVE: OnceLock<Mutex<Box<dyn Clipboard>>> = OnceLock::new();
+
+fn active_slot() -> &'static Mutex<Box<dyn Clipboard>> {
+    ACTIVE.get_or_init(|| Mutex::new(Box::new(Osc52Clipboard::stdout())))
+}
+
+/// Replace the process-wide clipboard used by `TextInput` copy/cut/paste
+/// when the input holds none of its own.
+pub fn set_active(clipboard: impl Clipboard + 'static) {
+    *active_slot().lock().unwrap_or_else(PoisonError::into_inner) = Box::new(clipboard);
+}
+
+/// Copy `text` with the active clipboard.
+pub fn copy(text: &str) -> Result<(), ClipboardError> {
+    active_slot()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .set_text(text)
+}
+
+/// Read the active clipboard.
+pub fn paste() -> Result<Option<String>, ClipboardError> {
+    active_slot()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get_text()
+}
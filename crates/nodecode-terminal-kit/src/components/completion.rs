@@ -0,0 +1,572 @@
+//! Inline completion for prompts built on `TextInput` and `InputBox`.
+//!
+//! A [`Completer`] asks its providers for candidates for the whitespace
+//! delimited token at the cursor. The selected candidate is shown as ghost
+//! text through `InputBox::suggestion` while the cursor is at the end of the
+//! input, and the rest are listed in a `FuzzyDropdown` anchored to the input
+//! with `resolve_dropdown_area`. Tab accepts the selected candidate, Right
+//! accepts the ghost text.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use ratatui::crossterm::event::KeyEvent;
+use ratatui::layout::Rect;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::ListItem;
+use ratatui::Frame;
+
+use super::dropdown::{dropdown_item_base_style, render_dropdown, FuzzyDropdown};
+use super::text_input::TextInput;
+use crate::keymap::{self, action, context, Keymap};
+use crate::primitives::path::PathDisplayConfig;
+use crate::theme::{to_ratatui, Theme};
+
+/// Default number of candidates kept after filtering.
+pub const DEFAULT_MAX_CANDIDATES: usize = 50;
+
+/// Entries matching the typed name listed per directory by
+/// [`PathProvider`], first in sorted order.
+const MAX_PATH_ENTRIES: usize = 500;
+
+/// How long [`PathProvider`] reuses a directory listing before reading the
+/// directory again.
+const PATH_LISTING_TTL: Duration = Duration::from_secs(2);
+
+/// A possible replacement for the token at the cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    /// Text that replaces the token, including any trigger such as `/`.
+    pub replacement: String,
+    /// Shown in the dropdown; defaults to the replacement.
+    pub label: String,
+    /// Dimmed text after the label, e.g. a command description.
+    pub detail: Option<String>,
+    /// Whether accepting inserts a space after the replacement.
+    pub trailing_space: bool,
+}
+
+impl Candidate {
+    pub fn new(replacement: impl Into<String>) -> Self {
+        let replacement = replacement.into();
+        Self {
+            label: replacement.clone(),
+            replacement,
+            detail: None,
+            trailing_space: true,
+        }
+    }
+
+    #[must_use]
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    #[must_use]
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    #[must_use]
+    pub fn trailing_space(mut self, trailing_space: bool) -> Self {
+        self.trailing_space = trailing_space;
+        self
+    }
+}
+
+/// The token being completed.
+#[derive(Debug, Clone)]
+pub struct CompletionRequest<'a> {
+    /// The whole input.
+    pub text: &'a str,
+    /// Byte range the accepted candidate replaces: the token around the
+    /// cursor.
+    pub token: Range<usize>,
+    /// The token up to the cursor; candidates are filtered against it.
+    pub query: &'a str,
+}
+
+impl CompletionRequest<'_> {
+    /// Whether the token is the first thing in the input.
+    pub fn is_first_token(&self) -> bool {
+        self.text[..self.token.start].trim().is_empty()
+    }
+}
+
+/// A source of candidates. Providers return everything relevant for the
+/// token; the completer filters and ranks them against the query.
+pub trait CompletionProvider: Send {
+    fn complete(&self, request: &CompletionRequest<'_>) -> Vec<Candidate>;
+}
+
+/// Completes plain words from a fixed list.
+#[derive(Debug, Clone, Default)]
+pub struct WordListProvider {
+    words: Vec<String>,
+}
+
+impl WordListProvider {
+    pub fn new<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            words: words.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl CompletionProvider for WordListProvider {
+    fn complete(&self, request: &CompletionRequest<'_>) -> Vec<Candidate> {
+        if request.query.is_empty() {
+            return Vec::new();
+        }
+        self.words
+            .iter()
+            .filter(|word| *word != request.query)
+            .map(Candidate::new)
+            .collect()
+    }
+}
+
+/// A command offered by [`SlashCommandProvider`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlashCommand {
+    /// Name without the leading `/`.
+    pub name: String,
+    pub description: Option<String>,
+}
+
+impl SlashCommand {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+        }
+    }
+
+    #[must_use]
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+/// Completes `/command` when it is the first token of the input.
+#[derive(Debug, Clone, Default)]
+pub struct SlashCommandProvider {
+    commands: Vec<SlashCommand>,
+}
+
+impl SlashCommandProvider {
+    pub fn new(commands: Vec<SlashCommand>) -> Self {
+        Self { commands }
+    }
+}
+
+impl CompletionProvider for SlashCommandProvider {
+    fn complete(&self, request: &CompletionRequest<'_>) -> Vec<Candidate> {
+        if !request.query.starts_with('/') || !request.is_first_token() {
+            return Vec::new();
+        }
+        self.commands
+            .iter()
+            .map(|command| {
+                let candidate = Candidate::new(format!("/{}", command.name));
+                match &command.description {
+                    Some(description) => candidate.detail(description.clone()),
+                    None => candidate,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Completes `@name` mentions from a fixed list.
+#[derive(Debug, Clone, Default)]
+pub struct MentionProvider {
+    names: Vec<String>,
+}
+
+impl MentionProvider {
+    pub fn new<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            names: names.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl CompletionProvider for MentionProvider {
+    fn complete(&self, request: &CompletionRequest<'_>) -> Vec<Candidate> {
+        if !request.query.starts_with('@') {
+            return Vec::new();
+        }
+        self.names
+            .iter()
+            .map(|name| Candidate::new(format!("@{name}")).label(name.clone()))
+            .collect()
+    }
+}
+
+/// Completes file paths relative to `PathDisplayConfig::base_dir` (or the
+/// current directory) after the config's prefix, `@` by default, one
+/// directory level at a time. Hidden entries are listed once the name being
+/// typed starts with a dot. Absolute paths and `..` are not completed.
+#[derive(Debug, Clone, Default)]
+pub struct PathProvider {
+    config: PathDisplayConfig,
+    /// Recent listings by directory, so typing a name does not read the
+    /// directory again on every keystroke.
+    listings: RefCell<HashMap<PathBuf, Listing>>,
+}
+
+/// A directory's entries as `(name, is_dir)`, sorted by name.
+#[derive(Debug, Clone)]
+struct Listing {
+    read_at: Instant,
+    entries: Vec<(String, bool)>,
+}
+
+fn read_listing(dir: &Path, now: Instant) -> Listing {
+    let mut entries: Vec<(String, bool)> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+            Some((name, is_dir))
+        })
+        .collect();
+    entries.sort();
+    Listing {
+        read_at: now,
+        entries,
+    }
+}
+
+impl PathProvider {
+    pub fn new(config: PathDisplayConfig) -> Self {
+        Self {
+            config,
+            listings: RefCell::default(),
+        }
+    }
+
+    fn base_dir(&self) -> PathBuf {
+        self.config
+            .base_dir
+            .clone()
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+}
+
+impl CompletionProvider for PathProvider {
+    fn complete(&self, request: &CompletionRequest<'_>) -> Vec<Candidate> {
+        let Some(partial) = request.query.strip_prefix(self.config.prefix.as_str()) else {
+            return Vec::new();
+        };
+        let (dir_part, name_part) = match partial.rfind('/') {
+            Some(idx) => partial.split_at(idx + 1),
+            None => ("", partial),
+        };
+        let escapes = Path::new(dir_part)
+            .components()
+            .any(|part| !matches!(part, Component::Normal(_) | Component::CurDir));
+        if escapes {
+            return Vec::new();
+        }
+        let now = Instant::now();
+        let mut listings = self.listings.borrow_mut();
+        listings.retain(|_, listing| now.duration_since(listing.read_at) < PATH_LISTING_TTL);
+        let dir = self.base_dir().join(dir_part);
+        let listing = listings
+            .entry(dir)
+            .or_insert_with_key(|dir| read_listing(dir, now));
+        let show_hidden = name_part.starts_with('.');
+        listing
+            .entries
+            .iter()
+            .filter(|(name, _)| {
+                name.starts_with(name_part) && (show_hidden || !name.starts_with('.'))
+            })
+            .take(MAX_PATH_ENTRIES)
+            .map(|(name, is_dir)| {
+                let slash = if *is_dir { "/" } else { "" };
+                let path = format!("{dir_part}{name}{slash}");
+                Candidate::new(format!("{}{path}", self.config.prefix))
+                    .label(path)
+                    .trailing_space(!*is_dir)
+            })
+            .collect()
+    }
+}
+
+/// Byte range of the whitespace delimited token around `cursor`.
+pub fn token_at(text: &str, cursor: usize) -> Range<usize> {
+    let cursor = cursor.min(text.len());
+    let start = text[..cursor]
+        .char_indices()
+        .rev()
+        .find(|(_, ch)| ch.is_whitespace())
+        .map_or(0, |(idx, ch)| idx + ch.len_utf8());
+    let end = text[cursor..]
+        .find(char::is_whitespace)
+        .map_or(text.len(), |idx| cursor + idx);
+    start..end
+}
+
+/// Completion state for one input: providers, the current token and the
+/// filtered candidates.
+pub struct Completer {
+    providers: Vec<Box<dyn CompletionProvider>>,
+    dropdown: FuzzyDropdown<Candidate>,
+    token: Range<usize>,
+    query: String,
+    /// Text and cursor the candidates were computed for.
+    seen: Option<(String, usize)>,
+    dismissed: bool,
+    max_candidates: usize,
+}
+
+impl Default for Completer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Completer {
+    pub fn new() -> Self {
+        Self {
+            providers: Vec::new(),
+            dropdown: FuzzyDropdown::new(Vec::new()),
+            token: 0..0,
+            query: String::new(),
+            seen: None,
+            dismissed: false,
+            max_candidates: DEFAULT_MAX_CANDIDATES,
+        }
+    }
+
+    /// Add a provider; candidates from earlier providers rank first among
+    /// equal matches.
+    #[must_use]
+    pub fn with_provider(mut self, provider: impl CompletionProvider + 'static) -> Self {
+        self.push_provider(provider);
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_candidates(mut self, max_candidates: usize) -> Self {
+        self.max_candidates = max_candidates;
+        self
+    }
+
+    pub fn push_provider(&mut self, provider: impl CompletionProvider + 'static) {
+        self.providers.push(Box::new(provider));
+        self.seen = None;
+    }
+
+    /// Recompute candidates for `input`; cheap when neither its text nor its
+    /// cursor changed. Call after every key the input handles.
+    pub fn update(&mut self, input: &TextInput) {
+        let text = input.text();
+        let cursor = input.cursor();
+        if self
+            .seen
+            .as_ref()
+            .is_some_and(|(seen_text, seen_cursor)| seen_text == text && *seen_cursor == cursor)
+        {
+            return;
+        }
+        self.seen = Some((text.to_string(), cursor));
+        self.dismissed = false;
+
+        let mut candidates: Vec<Candidate> = Vec::new();
+        if input.selection().is_none() {
+            self.token = token_at(text, cursor);
+            self.query = text[self.token.start..cursor].to_string();
+            let request = CompletionRequest {
+                text,
+                token: self.token.clone(),
+                query: &self.query,
+            };
+            for provider in &self.providers {
+                for candidate in provider.complete(&request) {
+                    if !candidates
+                        .iter()
+                        .any(|seen| seen.replacement == candidate.replacement)
+                    {
+                        candidates.push(candidate);
+                    }
+                }
+            }
+        }
+        self.dropdown.update_items(candidates);
+        self.dropdown.update_filter(
+            &self.query,
+            self.max_candidates,
+            self.max_candidates,
+            |candidate| candidate.replacement.as_str(),
+        );
+    }
+
+    /// Whether candidates are showing.
+    pub fn is_open(&self) -> bool {
+        !self.dismissed && self.dropdown.visible_count() > 0
+    }
+
+    /// Hide the candidates until the input changes.
+    pub fn dismiss(&mut self) {
+        self.dismissed = true;
+    }
+
+    /// Filtered candidates, best match first.
+    pub fn candidates(&self) -> impl Iterator<Item = &Candidate> + '_ {
+        let items = self.dropdown.items();
+        self.dropdown
+            .list()
+            .items()
+            .iter()
+            .map(move |&idx| &items[idx])
+    }
+
+    pub fn selected(&self) -> Option<&Candidate> {
+        if !self.is_open() {
+            return None;
+        }
+        let idx = *self.dropdown.list().selected()?;
+        self.dropdown.items().get(idx)
+    }
+
+    pub fn select_previous(&mut self) {
+        self.dropdown.select_previous();
+    }
+
+    pub fn select_next(&mut self) {
+        self.dropdown.select_next();
+    }
+
+    /// Remainder of the selected candidate, for `InputBox::suggestion`. Only
+    /// offered while the cursor is at the end of the input and the candidate
+    /// extends what was typed.
+    pub fn ghost_text(&self, input: &TextInput) -> Option<&str> {
+        let candidate = self.selected()?;
+        let text = input.text();
+        if input.cursor() != text.len() || self.token.end != text.len() {
+            return None;
+        }
+        candidate
+            .replacement
+            .strip_prefix(self.query.as_str())
+            .filter(|rest| !rest.is_empty())
+    }
+
+    /// Replace the token with the selected candidate. Returns whether
+    /// anything was inserted.
+    pub fn accept(&mut self, input: &mut TextInput) -> bool {
+        let Some(candidate) = self.selected().cloned() else {
+            return false;
+        };
+        let token = self.token.clone();
+        if token.is_empty() {
+            input.set_cursor_byte_offset(token.start);
+        } else {
+            input.set_selection(token.clone());
+        }
+        let mut replacement = candidate.replacement;
+        let followed_by_space = input.text()[token.end..].starts_with(char::is_whitespace);
+        if candidate.trailing_space && !followed_by_space {
+            replacement.push(' ');
+        }
+        input.insert_str(&replacement);
+        self.update(input);
+        true
+    }
+
+    /// Handle completion keys with the active keymap; returns whether the
+    /// key was consumed. See [`Completer::handle_key_with`].
+    pub fn handle_key(&mut self, input: &mut TextInput, key: KeyEvent) -> bool {
+        self.handle_key_with(&keymap::active(), input, key)
+    }
+
+    /// While candidates are showing, move the selection, accept with
+    /// `complete` (tab), accept the ghost text with `accept_suggestion`
+    /// (right) or dismiss with `cancel`. Call before `TextInput::handle_key`
+    /// and [`Completer::update`] after it.
+    pub fn handle_key_with(
+        &mut self,
+        keymap: &Keymap,
+        input: &mut TextInput,
+        key: KeyEvent,
+    ) -> bool {
+        if !self.is_open() {
+            return false;
+        }
+        match keymap.action_for(context::COMPLETION, &key) {
+            Some(action::SELECT_PREV) => {
+                self.select_previous();
+                true
+            }
+            Some(action::SELECT_NEXT) => {
+                self.select_next();
+                true
+            }
+            Some(action::COMPLETE) => self.accept(input),
+            Some(action::ACCEPT_SUGGESTION) => {
+                self.ghost_text(input).is_some() && self.accept(input)
+            }
+            Some(action::CANCEL) => {
+                self.dismiss();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Draw the candidate list below (or above) `input_area`, or in
+    /// `placement_area` when given. Returns whether anything was drawn.
+    pub fn render(
+        &mut self,
+        frame: &mut Frame,
+        input_area: Rect,
+        placement_area: Option<Rect>,
+        theme: &Theme,
+    ) -> bool {
+        if !self.is_open() {
+            return false;
+        }
+        let items = self.dropdown.items_arc();
+        let list = self.dropdown.list_mut();
+        let indices = list.items().to_vec();
+        render_dropdown(
+            frame,
+            input_area,
+            placement_area,
+            theme,
+            list,
+            |idx, is_selected| {
+                let candidate = &items[indices[idx]];
+                let style = dropdown_item_base_style(theme, is_selected);
+                let mut spans = vec![Span::styled(format!(" {}", candidate.label), style)];
+                if let Some(detail) = &candidate.detail {
+                    let detail_style = style.fg(to_ratatui(theme.secondary));
+                    spans.push(Span::styled(format!("  {detail}"), detail_style));
+                }
+                ListItem::new(Line::from(spans)).style(style)
+            },
+        )
+    }
+}
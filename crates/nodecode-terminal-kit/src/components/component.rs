@@ -38,4 +38,13 @@ pub trait UiComponent {
     fn handle_mouse(&mut self, _mouse: MouseEvent, _area: Rect) -> Option<Self::Action> {
         None
     }
+
+    /// Keymap contexts consulted by `handle_key`, most specific first; used to
+    /// derive this component's help.
+    fn key_contexts(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Called by `FocusTree` when the component gains or loses focus.
+    fn set_focused(&mut self, _focused: bool) {}
 }
@@ -0,0 +1,391 @@
+//! Focus tree for composed `UiComponent`s.
+//!
+//! A [`FocusTree`] owns the components of a screen keyed by caller-chosen ids.
+//! Keys go to the focused component and bubble up through its parents until
+//! one returns an action; Tab/Shift-Tab (the keymap's `focus` context) cycle
+//! focus in tree order, and a click focuses the component under the pointer
+//! based on where each one was last rendered.
+
+use ratatui::crossterm::event::{KeyEvent, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+use ratatui::Frame;
+
+use super::component::UiComponent;
+use crate::keymap::{self, action, context, Keymap};
+use crate::theme::{Theme, ThemeState};
+
+struct FocusNode<Id, A> {
+    id: Id,
+    parent: Option<Id>,
+    component: Option<Box<dyn UiComponent<Action = A>>>,
+    focusable: bool,
+    captures_tab: bool,
+    area: Option<Rect>,
+}
+
+/// Components arranged in a tree with a single focused node.
+pub struct FocusTree<Id, A> {
+    nodes: Vec<FocusNode<Id, A>>,
+    focused: Option<Id>,
+}
+
+impl<Id, A> Default for FocusTree<Id, A>
+where
+    Id: Copy + Eq,
+    A: Send + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Id, A> FocusTree<Id, A>
+where
+    Id: Copy + Eq,
+    A: Send + 'static,
+{
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            focused: None,
+        }
+    }
+
+    /// Add a focusable component under `parent` (or as a root), replacing any
+    /// node with the same id. The first focusable node added takes focus.
+    pub fn insert<C>(&mut self, id: Id, parent: Option<Id>, component: C)
+    where
+        C: UiComponent<Action = A> + 'static,
+    {
+        self.insert_node(id, parent, Some(Box::new(component)));
+        if self.focused.is_none() {
+            self.focus(id);
+        }
+    }
+
+    /// Add a node without a component that only groups its children.
+    pub fn insert_group(&mut self, id: Id, parent: Option<Id>) {
+        self.insert_node(id, parent, None);
+    }
+
+    /// Remove `id` and everything below it. If that held focus, the first
+    /// remaining focusable node takes over.
+    pub fn remove(&mut self, id: Id) {
+        let doomed: Vec<Id> = self
+            .nodes
+            .iter()
+            .filter(|node| self.is_within(node.id, id))
+            .map(|node| node.id)
+            .collect();
+        let lost_focus = self.focused.is_some_and(|f| doomed.contains(&f));
+        self.nodes.retain(|node| !doomed.contains(&node.id));
+        if lost_focus {
+            self.focused = None;
+            if let Some(first) = self.focus_order().first().copied() {
+                self.focus(first);
+            }
+        }
+    }
+
+    /// Keep `id` out of (or put it back into) the Tab order and click focus.
+    pub fn set_focusable(&mut self, id: Id, focusable: bool) {
+        if let Some(node) = self.node_mut(id) {
+            node.focusable = focusable && node.component.is_some();
+        }
+        if !focusable && self.focused == Some(id) {
+            self.set_focus(None);
+        }
+    }
+
+    /// Let `id` receive Tab/Shift-Tab itself while focused instead of moving
+    /// focus, e.g. for a tabbed dialog.
+    pub fn set_captures_tab(&mut self, id: Id, captures: bool) {
+        if let Some(node) = self.node_mut(id) {
+            node.captures_tab = captures;
+        }
+    }
+
+    pub fn focused(&self) -> Option<Id> {
+        self.focused
+    }
+
+    /// Focus `id` if it is focusable; returns whether focus is now on it.
+    pub fn focus(&mut self, id: Id) -> bool {
+        if !self.node(id).is_some_and(|node| node.focusable) {
+            return false;
+        }
+        self.set_focus(Some(id));
+        true
+    }
+
+    pub fn focus_next(&mut self) {
+        self.cycle(1);
+    }
+
+    pub fn focus_prev(&mut self) {
+        self.cycle(-1);
+    }
+
+    pub fn is_focused(&self, id: Id) -> bool {
+        self.focused == Some(id)
+    }
+
+    /// True if `id` or one of its descendants is focused.
+    pub fn contains_focus(&self, id: Id) -> bool {
+        self.focused.is_some_and(|f| self.is_within(f, id))
+    }
+
+    /// `ThemeState::Active` for the focused component, `Normal` otherwise.
+    pub fn theme_state(&self, id: Id) -> ThemeState {
+        if self.is_focused(id) {
+            ThemeState::Active
+        } else {
+            ThemeState::Normal
+        }
+    }
+
+    pub fn component(&self, id: Id) -> Option<&(dyn UiComponent<Action = A> + 'static)> {
+        self.node(id)?.component.as_deref()
+    }
+
+    pub fn component_mut(
+        &mut self,
+        id: Id,
+    ) -> Option<&mut (dyn UiComponent<Action = A> + 'static)> {
+        self.node_mut(id)?.component.as_deref_mut()
+    }
+
+    /// Render `id` into `area` and remember the area for click focus.
+    pub fn render(&mut self, id: Id, frame: &mut Frame, area: Rect, theme: &Theme) {
+        if let Some(node) = self.node_mut(id) {
+            node.area = Some(area);
+            if let Some(component) = node.component.as_mut() {
+                component.view(frame, area, theme);
+            }
+        }
+    }
+
+    /// Record where `id` was drawn when it is rendered by other means.
+    pub fn set_area(&mut self, id: Id, area: Rect) {
+        if let Some(node) = self.node_mut(id) {
+            node.area = Some(area);
+        }
+    }
+
+    /// Deepest node whose last rendered area contains the cell.
+    pub fn hit_test(&self, column: u16, row: u16) -> Option<Id> {
+        let position = Position::new(column, row);
+        self.nodes
+            .iter()
+            .filter(|node| node.area.is_some_and(|area| area.contains(position)))
+            .max_by_key(|node| self.depth(node.id))
+            .map(|node| node.id)
+    }
+
+    /// Route a key with the active keymap; see [`FocusTree::handle_key_with`].
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<A> {
+        self.handle_key_with(&keymap::active(), key)
+    }
+
+    /// Route a key to the focused component, bubbling to its parents until
+    /// one returns an action. Unhandled `focus` keys in `keymap` then move
+    /// focus.
+    ///
+    /// Focus keys move focus before the component sees them unless the
+    /// focused node captures Tab (see `set_captures_tab`).
+    pub fn handle_key_with(&mut self, keymap: &Keymap, key: KeyEvent) -> Option<A> {
+        let focus_action = keymap.action_for(context::FOCUS, &key);
+        let captures = self
+            .focused
+            .and_then(|id| self.node(id))
+            .is_some_and(|node| node.captures_tab);
+        if focus_action.is_some() && !captures {
+            self.apply_focus_action(focus_action);
+            return None;
+        }
+
+        let chain = self.focused.map(|id| self.ancestry(id)).unwrap_or_default();
+        for id in chain {
+            let handled = self
+                .component_mut(id)
+                .and_then(|component| component.handle_key(key));
+            if handled.is_some() {
+                return handled;
+            }
+        }
+        self.apply_focus_action(focus_action);
+        None
+    }
+
+    /// Route a mouse event to the component under the pointer (bubbling like
+    /// keys), focusing the nearest focusable one on button press.
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) -> Option<A> {
+        let hit = self.hit_test(mouse.column, mouse.row);
+        if matches!(mouse.kind, MouseEventKind::Down(_)) {
+            if let Some(target) = hit.and_then(|id| {
+                self.ancestry(id)
+                    .into_iter()
+                    .find(|id| self.node(*id).is_some_and(|node| node.focusable))
+            }) {
+                self.focus(target);
+            }
+        }
+
+        let chain = hit
+            .or(self.focused)
+            .map(|id| self.ancestry(id))
+            .unwrap_or_default();
+        for id in chain {
+            let Some(node) = self.node_mut(id) else {
+                continue;
+            };
+            let area = node.area.unwrap_or_default();
+            let handled = node
+                .component
+                .as_mut()
+                .and_then(|component| component.handle_mouse(mouse, area));
+            if handled.is_some() {
+                return handled;
+            }
+        }
+        None
+    }
+
+    /// Keymap contexts relevant right now: those of the focused component and
+    /// its parents, then focus navigation. Feed to `help::help_sections`.
+    pub fn key_contexts(&self) -> Vec<&str> {
+        let mut contexts: Vec<&str> = Vec::new();
+        for id in self.focused.map(|id| self.ancestry(id)).unwrap_or_default() {
+            if let Some(component) = self.component(id) {
+                for context in component.key_contexts() {
+                    if !contexts.contains(context) {
+                        contexts.push(context);
+                    }
+                }
+            }
+        }
+        contexts.push(context::FOCUS);
+        contexts
+    }
+
+    /// Focusable ids in Tab order: depth-first, in insertion order.
+    pub fn focus_order(&self) -> Vec<Id> {
+        let mut order = Vec::new();
+        for root in self.children(None) {
+            self.collect_order(root, &mut order);
+        }
+        order
+    }
+
+    fn collect_order(&self, id: Id, order: &mut Vec<Id>) {
+        if self.node(id).is_some_and(|node| node.focusable) {
+            order.push(id);
+        }
+        for child in self.children(Some(id)) {
+            self.collect_order(child, order);
+        }
+    }
+
+    fn children(&self, parent: Option<Id>) -> Vec<Id> {
+        self.nodes
+            .iter()
+            .filter(|node| match parent {
+                Some(parent) => node.parent == Some(parent),
+                None => node.parent.is_none_or(|p| self.node(p).is_none()),
+            })
+            .map(|node| node.id)
+            .collect()
+    }
+
+    fn apply_focus_action(&mut self, focus_action: Option<&str>) {
+        match focus_action {
+            Some(action::FOCUS_NEXT) => self.focus_next(),
+            Some(action::FOCUS_PREV) => self.focus_prev(),
+            _ => {}
+        }
+    }
+
+    fn cycle(&mut self, step: isize) {
+        let order = self.focus_order();
+        if order.is_empty() {
+            return;
+        }
+        let len = order.len() as isize;
+        let next = match self
+            .focused
+            .and_then(|f| order.iter().position(|id| *id == f))
+        {
+            Some(idx) => (idx as isize + step).rem_euclid(len) as usize,
+            None if step < 0 => order.len() - 1,
+            None => 0,
+        };
+        self.set_focus(Some(order[next]));
+    }
+
+    fn set_focus(&mut self, id: Option<Id>) {
+        if self.focused == id {
+            return;
+        }
+        if let Some(old) = self.focused.and_then(|old| self.component_mut(old)) {
+            old.set_focused(false);
+        }
+        self.focused = id;
+        if let Some(new) = id.and_then(|new| self.component_mut(new)) {
+            new.set_focused(true);
+        }
+    }
+
+    fn insert_node(
+        &mut self,
+        id: Id,
+        parent: Option<Id>,
+        component: Option<Box<dyn UiComponent<Action = A>>>,
+    ) {
+        let node = FocusNode {
+            id,
+            parent,
+            focusable: component.is_some(),
+            component,
+            captures_tab: false,
+            area: None,
+        };
+        match self.nodes.iter_mut().find(|n| n.id == id) {
+            Some(existing) => *existing = node,
+            None => self.nodes.push(node),
+        }
+    }
+
+    /// `id` followed by its parents up to the root.
+    fn ancestry(&self, id: Id) -> Vec<Id> {
+        let mut chain = Vec::new();
+        let mut current = self.node(id).map(|node| node.id);
+        while let Some(id) = current {
+            // Guard against parent cycles introduced by re-inserting nodes.
+            if chain.contains(&id) {
+                break;
+            }
+            chain.push(id);
+            current = self
+                .node(id)
+                .and_then(|node| node.parent)
+                .filter(|parent| self.node(*parent).is_some());
+        }
+        chain
+    }
+
+    fn is_within(&self, id: Id, ancestor: Id) -> bool {
+        self.ancestry(id).contains(&ancestor)
+    }
+
+    fn depth(&self, id: Id) -> usize {
+        self.ancestry(id).len()
+    }
+
+    fn node(&self, id: Id) -> Option<&FocusNode<Id, A>> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    fn node_mut(&mut self, id: Id) -> Option<&mut FocusNode<Id, A>> {
+        self.nodes.iter_mut().find(|node| node.id == id)
+    }
+}
@@ -0,0 +1,389 @@
+//! Help derived from the active keymap.
+//!
+//! Footers and the full "?" overlay are built from the bindings a component
+//! actually consults, so remapped or unbound keys never show stale hints.
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::Rect;
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+use unicode_width::UnicodeWidthStr;
+
+use super::component::UiComponent;
+use super::help_bar::{render_help_bar_entries, HelpEntry};
+use super::overlay_dialog::{layout_overlay, OverlayDialogOptions};
+use super::search_bar::render_search_bar;
+use super::text_input::TextInput;
+use crate::keymap::{action, context, KeySequence, Keymap};
+use crate::theme::{Theme, ThemeElement};
+
+/// Keys bound to one action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpItem {
+    pub action: String,
+    pub description: String,
+    pub keys: Vec<KeySequence>,
+}
+
+impl HelpItem {
+    /// All keys as shown in the overlay, e.g. `↓, j`.
+    pub fn keys_label(&self) -> String {
+        self.keys
+            .iter()
+            .map(key_label)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Compact footer entry using the first key only.
+    pub fn entry(&self) -> HelpEntry<'static> {
+        let key = self.keys.first().map(key_label).unwrap_or_default();
+        HelpEntry::new(key, self.description.clone())
+    }
+
+    fn matches(&self, query: &str) -> bool {
+        self.description.to_lowercase().contains(query)
+            || self.action.contains(query)
+            || self.keys_label().to_lowercase().contains(query)
+    }
+}
+
+/// Every bound action of one keymap context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpSection {
+    pub context: String,
+    pub title: String,
+    pub items: Vec<HelpItem>,
+}
+
+impl HelpSection {
+    /// Collect the bindings of `context`, one item per action in binding order.
+    pub fn from_keymap(keymap: &Keymap, context: &str) -> Self {
+        let mut items: Vec<HelpItem> = Vec::new();
+        for binding in keymap.bindings(context) {
+            match items.iter_mut().find(|item| item.action == binding.action) {
+                Some(item) => item.keys.push(binding.keys.clone()),
+                None => items.push(HelpItem {
+                    action: binding.action.clone(),
+                    description: describe_action(&binding.action),
+                    keys: vec![binding.keys.clone()],
+                }),
+            }
+        }
+        Self {
+            context: context.to_string(),
+            title: describe_context(context),
+            items,
+        }
+    }
+
+    pub fn entries(&self) -> Vec<HelpEntry<'static>> {
+        self.items.iter().map(HelpItem::entry).collect()
+    }
+}
+
+/// Help for several contexts, most specific first.
+pub fn help_sections(keymap: &Keymap, contexts: &[&str]) -> Vec<HelpSection> {
+    contexts
+        .iter()
+        .map(|context| HelpSection::from_keymap(keymap, context))
+        .filter(|section| !section.items.is_empty())
+        .collect()
+}
+
+/// Help for the contexts a component declares via `UiComponent::key_contexts`.
+pub fn component_help<C: UiComponent>(component: &C, keymap: &Keymap) -> Vec<HelpSection> {
+    help_sections(keymap, component.key_contexts())
+}
+
+/// Footer entries for chosen actions with caller-supplied labels, e.g.
+/// `[(action::SUBMIT, "next"), (action::CANCEL, "cancel")]`.
+///
+/// Actions without a binding are left out.
+pub fn hint_entries(
+    keymap: &Keymap,
+    context: &str,
+    labels: &[(&str, &str)],
+) -> Vec<HelpEntry<'static>> {
+    labels
+        .iter()
+        .filter_map(|(action, label)| {
+            let keys = keymap.keys_for(context, action).next()?;
+            Some(HelpEntry::new(key_label(keys), label.to_string()))
+        })
+        .collect()
+}
+
+/// One footer entry for a pair of opposite actions, e.g. `↑↓ navigate`;
+/// `None` unless both are bound.
+pub fn hint_pair(
+    keymap: &Keymap,
+    context: &str,
+    (first, second): (&str, &str),
+    label: &str,
+) -> Option<HelpEntry<'static>> {
+    let first = keymap.keys_for(context, first).next()?;
+    let second = keymap.keys_for(context, second).next()?;
+    Some(HelpEntry::new(
+        format!("{}{}", key_label(first), key_label(second)),
+        label.to_string(),
+    ))
+}
+
+/// Join entries into the double-space separated form read by
+/// `help_bar::render_help_bar` and `key_hints::view`.
+pub fn hint_text(entries: &[HelpEntry<'_>]) -> String {
+    hint_strings(entries).join("  ")
+}
+
+/// One `"key description"` string per entry, as taken by `key_hints::view`.
+pub fn hint_strings(entries: &[HelpEntry<'_>]) -> Vec<String> {
+    entries
+        .iter()
+        .map(|entry| {
+            if entry.description.is_empty() {
+                entry.key.to_string()
+            } else {
+                format!("{} {}", entry.key, entry.description)
+            }
+        })
+        .collect()
+}
+
+/// Render a one-line footer from `sections`, dropping entries that do not fit
+/// and ending with the key that opens the overlay when one is bound.
+pub fn render_compact_help(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    keymap: &Keymap,
+    sections: &[HelpSection],
+) {
+    let help_entry = hint_entries(keymap, context::PROGRAM, &[(action::SHOW_HELP, "help")]);
+    let reserved: usize = help_entry.iter().map(|e| entry_width(e) + 2).sum();
+    let budget = (area.width as usize).saturating_sub(reserved);
+
+    let mut used = 0;
+    let mut entries = Vec::new();
+    for entry in sections.iter().flat_map(HelpSection::entries) {
+        let width = entry_width(&entry) + if entries.is_empty() { 0 } else { 2 };
+        if used + width > budget {
+            break;
+        }
+        used += width;
+        entries.push(entry);
+    }
+    entries.extend(help_entry);
+    render_help_bar_entries(frame, area, theme, entries, "  ");
+}
+
+/// Full-screen, searchable list of every binding grouped by context.
+///
+/// Typing filters by key, action or description; the `help` keymap context
+/// scrolls and closes it.
+#[derive(Debug, Clone, Default)]
+pub struct HelpOverlay {
+    open: bool,
+    search: TextInput,
+    scroll: usize,
+}
+
+impl HelpOverlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+        self.scroll = 0;
+        self.search.clear();
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    pub fn toggle(&mut self) {
+        if self.open {
+            self.close();
+        } else {
+            self.open();
+        }
+    }
+
+    pub fn query(&self) -> &str {
+        self.search.text()
+    }
+
+    /// Handle a key while open; returns false when closed so the caller can
+    /// route the key elsewhere.
+    pub fn handle_key(&mut self, keymap: &Keymap, key: KeyEvent) -> bool {
+        if !self.open {
+            return false;
+        }
+        match keymap.action_for(context::HELP, &key) {
+            Some(action::CLOSE) => self.close(),
+            Some(action::SELECT_PREV) => self.scroll = self.scroll.saturating_sub(1),
+            Some(action::SELECT_NEXT) => self.scroll = self.scroll.saturating_add(1),
+            Some(action::PAGE_UP) => self.scroll = self.scroll.saturating_sub(10),
+            Some(action::PAGE_DOWN) => self.scroll = self.scroll.saturating_add(10),
+            Some(action::JUMP_TOP) => self.scroll = 0,
+            Some(action::JUMP_BOTTOM) => self.scroll = usize::MAX,
+            _ => {
+                if matches!(key.code, KeyCode::Char(_) | KeyCode::Backspace)
+                    && self.search.handle_key_with(keymap, key)
+                {
+                    self.scroll = 0;
+                }
+            }
+        }
+        true
+    }
+
+    /// Render over `area`; sections are usually `help_sections` of the
+    /// focused component plus any global contexts.
+    pub fn render(
+        &mut self,
+        frame: &mut Frame,
+        area: Rect,
+        theme: &Theme,
+        keymap: &Keymap,
+        sections: &[HelpSection],
+    ) {
+        if !self.open {
+            return;
+        }
+        let layout = layout_overlay(frame, area, theme, OverlayDialogOptions::overlay(3, 1));
+        let header = Rect {
+            height: 1,
+            ..layout.header
+        };
+        let search = Rect {
+            y: layout.header.y.saturating_add(1),
+            height: layout.header.height.saturating_sub(1),
+            ..layout.header
+        };
+        frame.render_widget(
+            Paragraph::new("Keyboard shortcuts").style(
+                theme
+                    .style(ThemeElement::Primary)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            header,
+        );
+        render_search_bar(frame, search, theme, &self.search, Some("Filter"));
+
+        let lines = self.body_lines(theme, sections);
+        let visible = layout.body.height as usize;
+        self.scroll = self.scroll.min(lines.len().saturating_sub(visible));
+        let end = (self.scroll + visible).min(lines.len());
+        frame.render_widget(
+            Paragraph::new(lines[self.scroll..end].to_vec()),
+            layout.body,
+        );
+
+        let footer = hint_entries(
+            keymap,
+            context::HELP,
+            &[(action::SELECT_NEXT, "scroll"), (action::CLOSE, "close")],
+        );
+        render_help_bar_entries(frame, layout.footer, theme, footer, "  ");
+    }
+
+    fn body_lines(&self, theme: &Theme, sections: &[HelpSection]) -> Vec<Line<'static>> {
+        let query = self.search.text().trim().to_lowercase();
+        let mut lines = Vec::new();
+        for section in sections {
+            let items: Vec<&HelpItem> = section
+                .items
+                .iter()
+                .filter(|item| query.is_empty() || item.matches(&query))
+                .collect();
+            if items.is_empty() {
+                continue;
+            }
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+            lines.push(Line::from(Span::styled(
+                section.title.clone(),
+                theme
+                    .style(ThemeElement::Secondary)
+                    .add_modifier(Modifier::BOLD),
+            )));
+            let labels: Vec<String> = items.iter().map(|item| item.keys_label()).collect();
+            let key_width = labels.iter().map(|l| l.width()).max().unwrap_or(0);
+            for (item, label) in items.iter().zip(labels) {
+                let pad = key_width - label.width();
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("  {label}{}  ", " ".repeat(pad)),
+                        theme.style(ThemeElement::Primary),
+                    ),
+                    Span::styled(
+                        item.description.clone(),
+                        theme.style(ThemeElement::Tertiary),
+                    ),
+                ]));
+            }
+        }
+        if lines.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "No matching shortcuts",
+                theme.style(ThemeElement::Tertiary),
+            )));
+        }
+        lines
+    }
+}
+
+/// Key sequence as shown in hints; arrows use their glyphs.
+pub fn key_label(keys: &KeySequence) -> String {
+    keys.chords()
+        .iter()
+        .map(|chord| match (chord.code, chord.modifiers.is_empty()) {
+            (KeyCode::Left, true) => "←".to_string(),
+            (KeyCode::Right, true) => "→".to_string(),
+            (KeyCode::Up, true) => "↑".to_string(),
+            (KeyCode::Down, true) => "↓".to_string(),
+            _ => chord.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Human description of an action name: `delete_word_backward` becomes
+/// `delete word backward`.
+pub fn describe_action(action: &str) -> String {
+    match action {
+        action::SELECT_PREV => "previous item".to_string(),
+        action::SELECT_NEXT => "next item".to_string(),
+        action::JUMP_TOP => "first item".to_string(),
+        action::JUMP_BOTTOM => "last item".to_string(),
+        other => other.replace('_', " "),
+    }
+}
+
+fn describe_context(context: &str) -> String {
+    let mut title = context.replace('_', " ");
+    if let Some(first) = title.get(..1) {
+        title.replace_range(..1, &first.to_uppercase());
+    }
+    title
+}
+
+fn entry_width(entry: &HelpEntry<'_>) -> usize {
+    let key = entry.key.width();
+    let desc = entry.description.width();
+    match (key, desc) {
+        (0, d) => d,
+        (k, 0) => k,
+        (k, d) => k + 1 + d,
+    }
+}
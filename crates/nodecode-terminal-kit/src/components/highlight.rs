@@ -0,0 +1,209 @@
+//! Token highlighting for text shown in `InputBox`.
+//!
+//! A [`Highlighter`] returns styled byte ranges for the current text and
+//! `InputBox::highlighter` paints them across the wrapped rows, under any
+//! selection or suggestion. The built-in highlighters color slash commands,
+//! `@path` mentions found by `MentionPathFormatter`, and inline code;
+//! [`Highlighters`] layers several of them.
+
+use std::ops::Range;
+
+use ratatui::style::{Modifier, Style};
+
+use crate::primitives::path::{MentionPathFormatter, PathDisplayConfig};
+use crate::theme::{to_ratatui, Theme, ThemeElement};
+
+/// A style for a byte range of the input's text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlight {
+    pub range: Range<usize>,
+    /// Patched onto the input's text style.
+    pub style: Style,
+}
+
+impl Highlight {
+    pub fn new(range: Range<usize>, style: Style) -> Self {
+        Self { range, style }
+    }
+}
+
+/// Finds the ranges of the text to style.
+pub trait Highlighter: Send {
+    /// Ranges may overlap; later ones are painted over earlier ones. Ranges
+    /// that are empty, out of bounds or not on character boundaries are
+    /// ignored.
+    fn highlight(&self, text: &str, theme: &Theme) -> Vec<Highlight>;
+}
+
+/// Colors `/command` when it is the first token of the input.
+///
+/// With a list of known commands, a command that no known name starts with
+/// is shown in the theme's error color while it is typed.
+#[derive(Debug, Clone, Default)]
+pub struct SlashCommandHighlighter {
+    commands: Option<Vec<String>>,
+}
+
+impl SlashCommandHighlighter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Known command names, without the leading `/`.
+    #[must_use]
+    pub fn commands<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.commands = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    fn is_known_prefix(&self, name: &str) -> bool {
+        self.commands
+            .as_ref()
+            .is_none_or(|commands| commands.iter().any(|command| command.starts_with(name)))
+    }
+}
+
+impl Highlighter for SlashCommandHighlighter {
+    fn highlight(&self, text: &str, theme: &Theme) -> Vec<Highlight> {
+        let start = text.len() - text.trim_start().len();
+        if !text[start..].starts_with('/') {
+            return Vec::new();
+        }
+        let end = text[start..]
+            .find(char::is_whitespace)
+            .map_or(text.len(), |idx| start + idx);
+        let element = if self.is_known_prefix(&text[start + 1..end]) {
+            ThemeElement::Primary
+        } else {
+            ThemeElement::Error
+        };
+        vec![Highlight::new(
+            start..end,
+            theme.style(element).add_modifier(Modifier::BOLD),
+        )]
+    }
+}
+
+/// Colors path mentions with `PathDisplayConfig::color` and `bold`, or the
+/// theme's first accent when no color is set.
+#[derive(Debug, Clone, Default)]
+pub struct MentionHighlighter {
+    config: PathDisplayConfig,
+}
+
+impl MentionHighlighter {
+    pub fn new(config: PathDisplayConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Highlighter for MentionHighlighter {
+    fn highlight(&self, text: &str, theme: &Theme) -> Vec<Highlight> {
+        let mut style = match self.config.color {
+            Some(color) => Style::default().fg(color),
+            None => theme.style(ThemeElement::Accent1),
+        };
+        if self.config.bold {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        MentionPathFormatter::with_config(self.config.clone())
+            .mention_ranges(text)
+            .into_iter()
+            .map(|range| Highlight::new(range, style))
+            .collect()
+    }
+}
+
+/// Colors `` `code` `` spans, including their backticks. A span closes at the
+/// next run of the same number of backticks; unclosed spans are left plain.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InlineCodeHighlighter;
+
+impl Highlighter for InlineCodeHighlighter {
+    fn highlight(&self, text: &str, theme: &Theme) -> Vec<Highlight> {
+        let style = Style::default()
+            .fg(to_ratatui(theme.accent2))
+            .bg(to_ratatui(theme.background_subtle));
+        let mut highlights = Vec::new();
+        let mut pos = 0;
+        while let Some(idx) = text[pos..].find('`') {
+            let start = pos + idx;
+            let ticks = backtick_run(&text[start..]);
+            let body = start + ticks;
+            let mut search = body;
+            let mut close = None;
+            while let Some(idx) = text[search..].find('`') {
+                let candidate = search + idx;
+                let run = backtick_run(&text[candidate..]);
+                if run == ticks {
+                    close = Some(candidate + run);
+                    break;
+                }
+                search = candidate + run;
+            }
+            match close {
+                Some(end) => {
+                    highlights.push(Highlight::new(start..end, style));
+                    pos = end;
+                }
+                None => pos = body,
+            }
+        }
+        highlights
+    }
+}
+
+fn backtick_run(text: &str) -> usize {
+    text.bytes().take_while(|&byte| byte == b'`').count()
+}
+
+/// Several highlighters, painted in the order they were added.
+#[derive(Default)]
+pub struct Highlighters {
+    highlighters: Vec<Box<dyn Highlighter>>,
+}
+
+impl std::fmt::Debug for Highlighters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Highlighters")
+            .field("highlighters", &self.highlighters.len())
+            .finish()
+    }
+}
+
+impl Highlighters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Commands, mentions and inline code with default settings.
+    pub fn prompt(config: PathDisplayConfig) -> Self {
+        Self::new()
+            .with(InlineCodeHighlighter)
+            .with(MentionHighlighter::new(config))
+            .with(SlashCommandHighlighter::new())
+    }
+
+    #[must_use]
+    pub fn with(mut self, highlighter: impl Highlighter + 'static) -> Self {
+        self.push(highlighter);
+        self
+    }
+
+    pub fn push(&mut self, highlighter: impl Highlighter + 'static) {
+        self.highlighters.push(Box::new(highlighter));
+    }
+}
+
+impl Highlighter for Highlighters {
+    fn highlight(&self, text: &str, theme: &Theme) -> Vec<Highlight> {
+        self.highlighters
+            .iter()
+            .flat_map(|highlighter| highlighter.highlight(text, theme))
+            .collect()
+    }
+}
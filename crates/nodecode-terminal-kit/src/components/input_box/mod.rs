@@ -2,15 +2,18 @@ use ratatui::{
     layout::{Position, Rect},
     style::{Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Paragraph},
+    widgets::{Block, Borders, Paragraph},
     Frame,
 };
 use unicode_width::UnicodeWidthStr;
 
+use crate::components::highlight::{Highlight, Highlighter};
 use crate::components::text_input::TextInput;
+use crate::components::validation::ValidationState;
 use crate::layout::text::wrapped_row_ranges;
 use crate::primitives::shimmer::shimmer_spans;
 use crate::theme::{to_ratatui, Color, Theme, ThemeElement};
+use std::ops::Range;
 use std::time::Instant;
 
 /// Geometry info for an input box's internal scrollbar.
@@ -30,6 +33,8 @@ pub struct InputBoxOutcome {
     pub scroll_offset: u16,
     pub scrollbar: Option<ScrollbarGeometry>,
     pub cursor_screen_pos: Position,
+    /// Where the wrapped text was drawn; used for mouse hit-testing.
+    pub content_area: Rect,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -64,6 +69,8 @@ pub struct InputBox<'a> {
     prompt_padding_left: u16,
     padding_top: u16,
     padding_bottom: u16,
+    validation: Option<ValidationState>,
+    highlighter: Option<&'a dyn Highlighter>,
 }
 
 impl<'a> InputBox<'a> {
@@ -93,6 +100,8 @@ impl<'a> InputBox<'a> {
             prompt_padding_left: 0,
             padding_top: 1,
             padding_bottom: 1,
+            validation: None,
+            highlighter: None,
         }
     }
 
@@ -202,6 +211,22 @@ impl<'a> InputBox<'a> {
         self
     }
 
+    /// Draw a border in the theme's error or success color with the state's
+    /// message along its bottom edge, e.g. from `TextInput::validation`.
+    /// `Idle` draws it in the plain border color, so the text does not move
+    /// when the state changes.
+    pub fn validation(mut self, val: ValidationState) -> Self {
+        self.validation = Some(val);
+        self
+    }
+
+    /// Style parts of the text, e.g. with `Highlighters::prompt`. Masked
+    /// secrets and placeholders are never highlighted.
+    pub fn highlighter(mut self, val: &'a dyn Highlighter) -> Self {
+        self.highlighter = Some(val);
+        self
+    }
+
     pub fn render(self, frame: &mut Frame, area: Rect) -> InputBoxOutcome {
         let frame_area = frame.area();
         if frame_area.width == 0 || frame_area.height == 0 || area.width == 0 || area.height == 0 {
@@ -209,6 +234,7 @@ impl<'a> InputBox<'a> {
                 scroll_offset: self.scroll_offset,
                 scrollbar: None,
                 cursor_screen_pos: Position::new(frame_area.x, frame_area.y),
+                content_area: Rect::default(),
             };
         }
 
@@ -222,8 +248,17 @@ impl<'a> InputBox<'a> {
             .map(|style| style.bg(to_ratatui(bg_color)))
             .unwrap_or(base_style);
         frame.render_widget(Block::default().style(base_style), area);
+        let bordered = render_validation(
+            frame,
+            self.theme,
+            area,
+            self.validation.as_ref(),
+            self.title_override,
+            bg_color,
+        );
+        let area = bordered.unwrap_or(area);
 
-        if let Some(title) = self.title_override {
+        if let Some(title) = self.title_override.filter(|_| bordered.is_none()) {
             if area.height >= 3 {
                 let title_style = self
                     .theme
@@ -270,18 +305,26 @@ impl<'a> InputBox<'a> {
         };
 
         let content_width = content_area.width.max(1);
-        let display_text = self.input.text();
-        let use_placeholder = display_text.is_empty() && self.input.placeholder().is_some();
+        let masked = self.input.is_secret() && !self.input.is_revealed();
+        let use_placeholder = self.input.is_empty() && self.input.placeholder().is_some();
+        let selection = self
+            .input
+            .selection()
+            .filter(|_| !use_placeholder)
+            .map(|range| {
+                self.input.visible_offset(range.start)..self.input.visible_offset(range.end)
+            });
+        // The suggestion is hidden while selecting so the highlight is exact,
+        // and for masked secrets so it cannot hint at the value.
         let suggestion = self
             .suggestion
-            .and_then(|value| (!value.is_empty()).then_some(value));
+            .and_then(|value| (!value.is_empty()).then_some(value))
+            .filter(|_| selection.is_none() && !masked);
 
-        let content = if use_placeholder {
-            self.input.placeholder().unwrap_or_default()
-        } else {
-            display_text
-        };
-        let (rows, used_placeholder) = if use_placeholder {
+        // Placeholders are short and drawn whole; the text itself is wrapped
+        // by the input's row cache and only the rows on screen are built.
+        let placeholder_lines = use_placeholder.then(|| {
+            let content = self.input.placeholder().unwrap_or_default();
             let shimmer = self.placeholder_shimmer.map(|spec| {
                 let len = content.len();
                 let start = spec.start.min(len);
@@ -293,38 +336,27 @@ impl<'a> InputBox<'a> {
                 }
             });
             if let Some(shimmer) = shimmer.filter(|spec| spec.start < spec.end) {
-                (
-                    build_placeholder_shimmer_lines(
-                        content,
-                        content_width,
-                        shimmer,
-                        self.theme,
-                        bg_color,
-                        self.placeholder_style,
-                        self.placeholder_color_override,
-                        self.placeholder_bold,
-                    ),
-                    true,
+                build_placeholder_shimmer_lines(
+                    content,
+                    content_width,
+                    shimmer,
+                    self.theme,
+                    bg_color,
+                    self.placeholder_style,
+                    self.placeholder_color_override,
+                    self.placeholder_bold,
                 )
             } else {
-                (build_text_lines(content, content_width), true)
+                build_text_lines(content, content_width)
             }
-        } else if let Some(suggestion) = suggestion {
-            let style = self.suggestion_style.unwrap_or_else(|| {
-                self.theme
-                    .style(ThemeElement::Secondary)
-                    .bg(to_ratatui(bg_color))
-                    .add_modifier(Modifier::DIM)
-            });
-            (
-                build_suggestion_lines(display_text, suggestion, style, content_width),
-                false,
-            )
-        } else {
-            (build_text_lines(content, content_width), false)
-        };
-
-        let total_rows = rows.len().max(1) as u16;
+        });
+        let used_placeholder = placeholder_lines.is_some();
+        let suffix = suggestion.unwrap_or_default();
+        let total_rows = match &placeholder_lines {
+            Some(lines) => lines.len(),
+            None => self.input.visual_rows_with_suffix(content_width, suffix),
+        }
+        .max(1) as u16;
 
         let cursor_pos = self.input.cursor_visual_position(content_width);
         let max_scroll = total_rows.saturating_sub(content_area.height);
@@ -340,11 +372,55 @@ impl<'a> InputBox<'a> {
             scroll = scroll.min(max_scroll);
         }
 
-        let visible_rows: Vec<Line<'static>> = rows
-            .into_iter()
-            .skip(scroll as usize)
-            .take(content_area.height as usize)
-            .collect();
+        let visible_rows: Vec<Line<'static>> = match placeholder_lines {
+            Some(lines) => lines
+                .into_iter()
+                .skip(scroll as usize)
+                .take(content_area.height as usize)
+                .collect(),
+            None => {
+                let window = scroll as usize..scroll as usize + content_area.height as usize;
+                let rows = self.input.visible_rows(content_width, suffix, window);
+                let mut highlights = match self.highlighter {
+                    Some(highlighter) if !masked => {
+                        // Highlighters see the whole text, so they cost a
+                        // pass over it every frame.
+                        let visible_text = self.input.visible_text();
+                        highlighter
+                            .highlight(&visible_text, self.theme)
+                            .into_iter()
+                            .filter(|highlight| is_valid_range(&visible_text, &highlight.range))
+                            .collect()
+                    }
+                    _ => Vec::new(),
+                };
+                // Chips are drawn as badges and wrapped as single units.
+                let chip_style = Style::default()
+                    .fg(to_ratatui(self.theme.primary))
+                    .bg(to_ratatui(self.theme.background_badge));
+                highlights.extend(
+                    rows.chips
+                        .iter()
+                        .map(|range| Highlight::new(range.clone(), chip_style)),
+                );
+                if let Some(selection) = selection {
+                    let style = Style::default()
+                        .fg(to_ratatui(bg_color))
+                        .bg(to_ratatui(self.theme.selection));
+                    highlights.push(Highlight::new(selection, style));
+                } else if !suffix.is_empty() {
+                    let style = self.suggestion_style.unwrap_or_else(|| {
+                        self.theme
+                            .style(ThemeElement::Secondary)
+                            .bg(to_ratatui(bg_color))
+                            .add_modifier(Modifier::DIM)
+                    });
+                    let end = rows.text_len + suffix.len();
+                    highlights.push(Highlight::new(rows.text_len..end, style));
+                }
+                build_styled_lines(&rows.rows, &highlights)
+            }
+        };
 
         let mut text = Text::from(visible_rows);
         if used_placeholder && self.placeholder_shimmer.is_none() {
@@ -411,10 +487,57 @@ impl<'a> InputBox<'a> {
             scroll_offset: scroll,
             scrollbar,
             cursor_screen_pos,
+            content_area,
         }
     }
 }
 
+/// Border and message for a validation state, with the title on the top
+/// edge. Returns the area inside the border, or `None` if none was drawn.
+fn render_validation(
+    frame: &mut Frame,
+    theme: &Theme,
+    area: Rect,
+    validation: Option<&ValidationState>,
+    title: Option<&str>,
+    bg_color: Color,
+) -> Option<Rect> {
+    let validation = validation?;
+    let (color, mark) = match validation {
+        ValidationState::Idle => (theme.border, ""),
+        ValidationState::Valid(_) => (theme.success, "✓"),
+        ValidationState::Invalid(_) => (theme.error, "✗"),
+    };
+    if area.width < 3 || area.height < 3 {
+        return None;
+    }
+    let style = Style::default()
+        .fg(to_ratatui(color))
+        .bg(to_ratatui(bg_color));
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(theme.border_type)
+        .border_style(style)
+        .style(Style::default().bg(to_ratatui(bg_color)));
+    if let Some(title) = title {
+        block = block.title(Line::from(Span::styled(
+            title.to_string(),
+            theme
+                .style(ThemeElement::Secondary)
+                .add_modifier(Modifier::BOLD),
+        )));
+    }
+    if let Some(message) = validation.message() {
+        block = block.title_bottom(Line::from(Span::styled(
+            format!(" {mark} {message} "),
+            style.add_modifier(Modifier::BOLD),
+        )));
+    }
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    Some(inner)
+}
+
 fn build_text_lines(text: &str, content_width: u16) -> Vec<Line<'static>> {
     if text.is_empty() {
         return vec![Line::from("")];
@@ -427,34 +550,45 @@ fn build_text_lines(text: &str, content_width: u16) -> Vec<Line<'static>> {
         .collect()
 }
 
-fn build_suggestion_lines(
-    text: &str,
-    suggestion: &str,
-    suggestion_style: Style,
-    content_width: u16,
-) -> Vec<Line<'static>> {
-    let full_text = format!("{}{}", text, suggestion);
-    if full_text.is_empty() {
-        return vec![Line::from("")];
-    }
-    let row_ranges = wrapped_row_ranges(&full_text, content_width);
-    let text_len = text.len();
+fn is_valid_range(text: &str, range: &Range<usize>) -> bool {
+    range.start < range.end
+        && range.end <= text.len()
+        && text.is_char_boundary(range.start)
+        && text.is_char_boundary(range.end)
+}
 
-    row_ranges
-        .into_iter()
-        .map(|(start, end)| {
-            if end <= text_len {
-                Line::raw(full_text[start..end].to_string())
-            } else if start >= text_len {
-                Line::styled(full_text[start..end].to_string(), suggestion_style)
-            } else {
-                let text_part = &full_text[start..text_len];
-                let sugg_part = &full_text[text_len..end];
-                Line::from(vec![
-                    Span::raw(text_part.to_string()),
-                    Span::styled(sugg_part.to_string(), suggestion_style),
-                ])
+/// Styled lines for `rows`, each its start in the visible text and its
+/// contents.
+fn build_styled_lines(rows: &[(usize, String)], highlights: &[Highlight]) -> Vec<Line<'static>> {
+    rows.iter()
+        .map(|(start, text)| {
+            let (start, end) = (*start, start + text.len());
+            let mut cuts = vec![start, end];
+            for highlight in highlights {
+                for edge in [highlight.range.start, highlight.range.end] {
+                    if start < edge && edge < end {
+                        cuts.push(edge);
+                    }
+                }
             }
+            cuts.sort_unstable();
+            cuts.dedup();
+            let spans: Vec<Span<'static>> = cuts
+                .windows(2)
+                .map(|pair| {
+                    let (from, to) = (pair[0], pair[1]);
+                    let style = highlights
+                        .iter()
+                        .filter(|highlight| {
+                            highlight.range.start <= from && to <= highlight.range.end
+                        })
+                        .fold(Style::default(), |style, highlight| {
+                            style.patch(highlight.style)
+                        });
+                    Span::styled(text[from - start..to - start].to_string(), style)
+                })
+                .collect();
+            Line::from(spans)
         })
         .collect()
 }
@@ -0,0 +1,422 @@
+//! Shell-like history for prompts built on `TextInput`.
+//!
+//! Up/Down walk earlier submissions once the cursor is on the first/last
+//! visual row, limited to entries starting with what was typed before the
+//! walk began. Ctrl-R opens an incremental reverse search. Entries can be
+//! persisted as JSON lines, one `{"text": ...}` object per submission.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+use super::text_input::TextInput;
+use crate::keymap::{self, action, context, Keymap};
+
+/// Default number of entries kept in memory and on disk.
+pub const DEFAULT_MAX_ENTRIES: usize = 1000;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Record {
+    text: String,
+}
+
+#[derive(Debug, Clone)]
+struct Walk {
+    /// Index into `entries` of the entry shown, `entries.len()` for the draft.
+    index: usize,
+    draft: String,
+}
+
+#[derive(Debug, Clone)]
+struct ReverseSearch {
+    query: String,
+    matched: Option<usize>,
+    draft: String,
+}
+
+/// Previous submissions, oldest first, without duplicates.
+#[derive(Debug, Clone)]
+pub struct InputHistory {
+    entries: Vec<String>,
+    max_entries: usize,
+    path: Option<PathBuf>,
+    walk: Option<Walk>,
+    search: Option<ReverseSearch>,
+}
+
+impl Default for InputHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputHistory {
+    /// In-memory history.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            max_entries: DEFAULT_MAX_ENTRIES,
+            path: None,
+            walk: None,
+            search: None,
+        }
+    }
+
+    /// Load `path` (missing files are empty) and append new entries to it.
+    ///
+    /// Unreadable lines are skipped. When the file holds duplicates or more
+    /// than `max_entries` lines it is rewritten compacted.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        Self::open_with_limit(path, DEFAULT_MAX_ENTRIES)
+    }
+
+    pub fn open_with_limit(path: impl Into<PathBuf>, max_entries: usize) -> io::Result<Self> {
+        let path = path.into();
+        let mut history = Self::new();
+        history.max_entries = max_entries;
+        let lines = read_records(&path)?;
+        let line_count = lines.len();
+        for text in lines {
+            history.insert(text);
+        }
+        if history.entries.len() != line_count {
+            write_records(&path, &history.entries)?;
+        }
+        history.path = Some(path);
+        Ok(history)
+    }
+
+    #[must_use]
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self.trim();
+        self
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Record a submission, moving an existing copy to the newest position.
+    /// Blank entries are ignored. Ends any walk or search.
+    pub fn push(&mut self, entry: impl Into<String>) -> io::Result<()> {
+        self.walk = None;
+        self.search = None;
+        let entry = entry.into();
+        if entry.trim().is_empty() {
+            return Ok(());
+        }
+        self.insert(entry.clone());
+        match &self.path {
+            Some(path) => append_record(path, &entry),
+            None => Ok(()),
+        }
+    }
+
+    /// Rewrite the backing file from memory, dropping duplicates and old
+    /// entries appended by this or other sessions.
+    pub fn compact(&self) -> io::Result<()> {
+        match &self.path {
+            Some(path) => write_records(path, &self.entries),
+            None => Ok(()),
+        }
+    }
+
+    /// Older entry starting with the text typed before the walk began.
+    /// `current` is remembered as the draft on the first step.
+    pub fn older(&mut self, current: &str) -> Option<&str> {
+        let walk = self.walk.get_or_insert_with(|| Walk {
+            index: self.entries.len(),
+            draft: current.to_string(),
+        });
+        let found = self.entries[..walk.index]
+            .iter()
+            .rposition(|entry| entry.starts_with(&walk.draft) && entry != current)?;
+        walk.index = found;
+        Some(&self.entries[found])
+    }
+
+    /// Newer matching entry, or the draft once past the newest.
+    pub fn newer(&mut self) -> Option<&str> {
+        let walk = self.walk.as_mut()?;
+        let current = self.entries.get(walk.index)?;
+        let newer = self.entries[walk.index + 1..]
+            .iter()
+            .position(|entry| entry.starts_with(&walk.draft) && entry != current);
+        match newer {
+            Some(offset) => {
+                walk.index += offset + 1;
+                Some(&self.entries[walk.index])
+            }
+            None => {
+                walk.index = self.entries.len();
+                Some(&walk.draft)
+            }
+        }
+    }
+
+    /// Forget the walk position, e.g. after the user edits the recalled text.
+    pub fn reset_navigation(&mut self) {
+        self.walk = None;
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.search.is_some()
+    }
+
+    /// Start a reverse search; `current` is restored if it is cancelled.
+    pub fn start_search(&mut self, current: &str) {
+        self.walk = None;
+        self.search = Some(ReverseSearch {
+            query: String::new(),
+            matched: None,
+            draft: current.to_string(),
+        });
+    }
+
+    pub fn search_query(&self) -> Option<&str> {
+        self.search.as_ref().map(|search| search.query.as_str())
+    }
+
+    /// Entry matching the current search, if any.
+    pub fn search_match(&self) -> Option<&str> {
+        let index = self.search.as_ref()?.matched?;
+        self.entries.get(index).map(String::as_str)
+    }
+
+    /// Prompt label in the style of readline, e.g. ``(reverse-i-search)`git': ``
+    /// or ``(failing reverse-i-search)`xyz': ``.
+    pub fn search_prompt(&self) -> Option<String> {
+        let search = self.search.as_ref()?;
+        let failing = if search.matched.is_none() && !search.query.is_empty() {
+            "failing "
+        } else {
+            ""
+        };
+        Some(format!("({failing}reverse-i-search)`{}': ", search.query))
+    }
+
+    /// Replace the search query and jump to the newest entry containing it.
+    pub fn set_search_query(&mut self, query: impl Into<String>) -> Option<&str> {
+        let search = self.search.as_mut()?;
+        search.query = query.into();
+        search.matched = find_containing(&self.entries, &search.query, self.entries.len());
+        self.search_match()
+    }
+
+    /// Move to the next older entry containing the query.
+    pub fn search_older(&mut self) -> Option<&str> {
+        let search = self.search.as_mut()?;
+        let before = search.matched.unwrap_or(self.entries.len());
+        if let Some(found) = find_containing(&self.entries, &search.query, before) {
+            search.matched = Some(found);
+        }
+        self.search_match()
+    }
+
+    /// End the search, returning the match (or the draft when nothing
+    /// matched).
+    pub fn accept_search(&mut self) -> Option<String> {
+        let search = self.search.take()?;
+        Some(match search.matched {
+            Some(index) => self.entries[index].clone(),
+            None => search.draft,
+        })
+    }
+
+    /// End the search, returning the text from before it started.
+    pub fn cancel_search(&mut self) -> Option<String> {
+        self.search.take().map(|search| search.draft)
+    }
+
+    /// Handle history keys for `input` with the active keymap; returns
+    /// whether the key was consumed. See [`InputHistory::handle_key_with`].
+    pub fn handle_key(&mut self, input: &mut TextInput, key: KeyEvent, content_width: u16) -> bool {
+        self.handle_key_with(&keymap::active(), input, key, content_width)
+    }
+
+    /// Route `key` for a prompt: during a reverse search it edits the query
+    /// (other keys accept the match and are left for the caller); otherwise
+    /// `history_prev`/`history_next` recall entries once the cursor cannot
+    /// move further up/down, and `history_search` starts a search.
+    ///
+    /// Call before `TextInput::handle_key`, and `push` on submit.
+    pub fn handle_key_with(
+        &mut self,
+        keymap: &Keymap,
+        input: &mut TextInput,
+        key: KeyEvent,
+        content_width: u16,
+    ) -> bool {
+        if self.search.is_some() {
+            return self.handle_search_key(keymap, input, key);
+        }
+        match keymap.action_for(context::HISTORY, &key) {
+            Some(action::HISTORY_PREV) => {
+                if input.cursor_move_visual_vertical(true, content_width) {
+                    return true;
+                }
+                let current = input.text().to_string();
+                if let Some(entry) = self.older(&current).map(str::to_string) {
+                    input.restore_text(entry);
+                }
+                true
+            }
+            Some(action::HISTORY_NEXT) => {
+                if input.cursor_move_visual_vertical(false, content_width) {
+                    return true;
+                }
+                if let Some(entry) = self.newer().map(str::to_string) {
+                    input.restore_text(entry);
+                }
+                true
+            }
+            Some(action::HISTORY_SEARCH) => {
+                self.start_search(input.text());
+                true
+            }
+            _ => {
+                if self.walk.is_some() && is_editing_key(&key) {
+                    self.reset_navigation();
+                }
+                false
+            }
+        }
+    }
+
+    fn handle_search_key(&mut self, keymap: &Keymap, input: &mut TextInput, key: KeyEvent) -> bool {
+        let mut query = self.search_query().unwrap_or_default().to_string();
+        match keymap.action_for(context::REVERSE_SEARCH, &key) {
+            Some(action::HISTORY_SEARCH) => {
+                if let Some(entry) = self.search_older().map(str::to_string) {
+                    input.restore_text(entry);
+                }
+                return true;
+            }
+            Some(action::CONFIRM) => {
+                if let Some(text) = self.accept_search() {
+                    input.restore_text(text);
+                }
+                return true;
+            }
+            Some(action::CANCEL) => {
+                if let Some(text) = self.cancel_search() {
+                    input.restore_text(text);
+                }
+                return true;
+            }
+            _ => {}
+        }
+        match key.code {
+            KeyCode::Char(ch) if !has_command_modifier(&key) => query.push(ch),
+            KeyCode::Backspace => {
+                query.pop();
+            }
+            _ => {
+                if let Some(text) = self.accept_search() {
+                    input.restore_text(text);
+                }
+                return false;
+            }
+        }
+        let shown = self
+            .set_search_query(query)
+            .map(str::to_string)
+            .or_else(|| self.search.as_ref().map(|search| search.draft.clone()));
+        if let Some(text) = shown {
+            input.restore_text(text);
+        }
+        true
+    }
+
+    fn insert(&mut self, entry: String) {
+        self.entries.retain(|existing| *existing != entry);
+        self.entries.push(entry);
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        let excess = self.entries.len().saturating_sub(self.max_entries);
+        self.entries.drain(..excess);
+    }
+}
+
+fn find_containing(entries: &[String], query: &str, before: usize) -> Option<usize> {
+    entries[..before.min(entries.len())]
+        .iter()
+        .rposition(|entry| entry.contains(query))
+}
+
+fn has_command_modifier(key: &KeyEvent) -> bool {
+    key.modifiers
+        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+}
+
+fn is_editing_key(key: &KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char(_) => !has_command_modifier(key),
+        KeyCode::Backspace | KeyCode::Delete | KeyCode::Enter => true,
+        _ => false,
+    }
+}
+
+fn read_records(path: &Path) -> io::Result<Vec<String>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        // A torn final write from a crashed session should not lose the rest.
+        if let Ok(record) = serde_json::from_str::<Record>(&line?) {
+            entries.push(record.text);
+        }
+    }
+    Ok(entries)
+}
+
+fn record_line(text: &str) -> io::Result<String> {
+    let mut line = serde_json::to_string(&Record {
+        text: text.to_string(),
+    })?;
+    line.push('\n');
+    Ok(line)
+}
+
+fn append_record(path: &Path, text: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(record_line(text)?.as_bytes())
+}
+
+/// Replace the file atomically so a concurrent reader never sees half of it.
+fn write_records(path: &Path, entries: &[String]) -> io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let mut contents = String::new();
+    for entry in entries {
+        contents.push_str(&record_line(entry)?);
+    }
+    let tmp = path.with_extension("jsonl.tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
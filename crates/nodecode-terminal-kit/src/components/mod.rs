@@ -1,18 +1,26 @@
 //! Reusable terminal UI components.
 
+pub mod completion;
 pub mod component;
 pub mod dialog_shell;
 pub mod dropdown;
+pub mod focus;
+pub mod help;
 pub mod help_bar;
+pub mod highlight;
 pub mod input_box;
+pub mod input_history;
 pub mod key_hints;
 pub mod lines_viewport;
 pub mod list;
 pub mod list_items;
 pub mod overlay_dialog;
+pub mod overlay_stack;
 pub mod picker;
 pub mod picker_dialog;
 pub mod search_bar;
 pub mod tabbed_dialog;
 pub mod tabbed_prompt_dialog;
 pub mod text_input;
+pub mod validation;
+pub mod vim;
@@ -0,0 +1,288 @@
+//! Layered modal dialogs.
+//!
+//! An [`OverlayStack`] owns open modals in z-order. Rendering dims whatever
+//! lies below each layer, input only reaches the top layer, the `overlay`
+//! keymap context's `close` (esc by default) dismisses layers top-down, and a
+//! click outside the top layer can dismiss it.
+
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{KeyEvent, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+use ratatui::style::{Color as RatatuiColor, Modifier};
+use ratatui::Frame;
+
+use super::component::UiComponent;
+use super::dialog_shell::{self, DialogOptions};
+use crate::keymap::{self, action, context, Keymap};
+use crate::theme::{blend_colors, Color, Theme};
+
+/// Where a layer is drawn within the stack's area.
+#[derive(Debug, Clone, Copy)]
+pub enum OverlayPlacement {
+    /// The whole area.
+    Full,
+    /// Centered like `dialog_shell::compute_centered`.
+    Centered(DialogOptions),
+    /// An absolute rect, clipped to the area.
+    Fixed(Rect),
+}
+
+impl OverlayPlacement {
+    pub fn resolve(self, area: Rect) -> Rect {
+        match self {
+            Self::Full => area,
+            Self::Centered(opts) => dialog_shell::compute_centered(area, opts).area,
+            Self::Fixed(rect) => rect.intersection(area),
+        }
+    }
+}
+
+/// A modal pushed onto an [`OverlayStack`].
+pub struct OverlayLayer<Id, A> {
+    id: Id,
+    component: Box<dyn UiComponent<Action = A>>,
+    placement: OverlayPlacement,
+    dismiss_on_esc: bool,
+    dismiss_on_click_outside: bool,
+    area: Option<Rect>,
+}
+
+impl<Id, A> OverlayLayer<Id, A>
+where
+    Id: Copy + Eq,
+    A: Send + 'static,
+{
+    /// Full-area layer dismissed by esc and by clicks outside it.
+    pub fn new<C>(id: Id, component: C) -> Self
+    where
+        C: UiComponent<Action = A> + 'static,
+    {
+        Self {
+            id,
+            component: Box::new(component),
+            placement: OverlayPlacement::Full,
+            dismiss_on_esc: true,
+            dismiss_on_click_outside: true,
+            area: None,
+        }
+    }
+
+    #[must_use]
+    pub fn placement(mut self, placement: OverlayPlacement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    #[must_use]
+    pub fn dismiss_on_esc(mut self, dismiss: bool) -> Self {
+        self.dismiss_on_esc = dismiss;
+        self
+    }
+
+    #[must_use]
+    pub fn dismiss_on_click_outside(mut self, dismiss: bool) -> Self {
+        self.dismiss_on_click_outside = dismiss;
+        self
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Area the layer was last rendered into.
+    pub fn area(&self) -> Option<Rect> {
+        self.area
+    }
+}
+
+/// Outcome of routing an event to a non-empty stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OverlayEvent<Id, A> {
+    /// The top layer emitted an action.
+    Action(A),
+    /// The top layer was popped by esc or an outside click.
+    Dismissed(Id),
+    /// The top layer ignored the event; it must not reach content below.
+    Blocked,
+}
+
+/// Open modals, bottom first.
+pub struct OverlayStack<Id, A> {
+    layers: Vec<OverlayLayer<Id, A>>,
+    dim: f32,
+}
+
+impl<Id, A> Default for OverlayStack<Id, A>
+where
+    Id: Copy + Eq,
+    A: Send + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Id, A> OverlayStack<Id, A>
+where
+    Id: Copy + Eq,
+    A: Send + 'static,
+{
+    pub fn new() -> Self {
+        Self {
+            layers: Vec::new(),
+            dim: 0.5,
+        }
+    }
+
+    /// How strongly content below a layer fades toward the theme background,
+    /// from 0.0 (untouched) to 1.0 (hidden).
+    #[must_use]
+    pub fn with_dim(mut self, dim: f32) -> Self {
+        self.dim = dim.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Open `layer` on top, replacing (and raising) any layer with its id.
+    pub fn push(&mut self, layer: OverlayLayer<Id, A>) {
+        self.close(layer.id);
+        self.layers.push(layer);
+    }
+
+    /// Close the top layer.
+    pub fn pop(&mut self) -> Option<Id> {
+        self.layers.pop().map(|layer| layer.id)
+    }
+
+    /// Close `id` wherever it is in the stack; returns whether it was open.
+    pub fn close(&mut self, id: Id) -> bool {
+        let before = self.layers.len();
+        self.layers.retain(|layer| layer.id != id);
+        self.layers.len() != before
+    }
+
+    pub fn clear(&mut self) {
+        self.layers.clear();
+    }
+
+    pub fn is_open(&self, id: Id) -> bool {
+        self.layers.iter().any(|layer| layer.id == id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    pub fn top(&self) -> Option<Id> {
+        self.layers.last().map(|layer| layer.id)
+    }
+
+    /// Layer ids, bottom first.
+    pub fn ids(&self) -> impl Iterator<Item = Id> + '_ {
+        self.layers.iter().map(|layer| layer.id)
+    }
+
+    pub fn component_mut(
+        &mut self,
+        id: Id,
+    ) -> Option<&mut (dyn UiComponent<Action = A> + 'static)> {
+        self.layers
+            .iter_mut()
+            .find(|layer| layer.id == id)
+            .map(|layer| layer.component.as_mut())
+    }
+
+    /// Render every layer over content already drawn in `area`, dimming what
+    /// lies below each one.
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        for layer in &mut self.layers {
+            dim_area(frame.buffer_mut(), area, theme, self.dim);
+            let layer_area = layer.placement.resolve(area);
+            layer.area = Some(layer_area);
+            layer.component.view(frame, layer_area, theme);
+        }
+    }
+
+    /// Route a key with the active keymap; see
+    /// [`OverlayStack::handle_key_with`].
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<OverlayEvent<Id, A>> {
+        self.handle_key_with(&keymap::active(), key)
+    }
+
+    /// Route a key to the top layer, which `keymap`'s `overlay` close key
+    /// dismisses. Returns `None` when no layer is open so the caller can
+    /// handle the key itself.
+    pub fn handle_key_with(
+        &mut self,
+        keymap: &Keymap,
+        key: KeyEvent,
+    ) -> Option<OverlayEvent<Id, A>> {
+        let top = self.layers.last_mut()?;
+        if let Some(action) = top.component.handle_key(key) {
+            return Some(OverlayEvent::Action(action));
+        }
+        let closes = keymap.action_for(context::OVERLAY, &key) == Some(action::CLOSE);
+        if closes && top.dismiss_on_esc {
+            return self.pop().map(OverlayEvent::Dismissed);
+        }
+        Some(OverlayEvent::Blocked)
+    }
+
+    /// Route a mouse event to the top layer; a press outside it dismisses it
+    /// when allowed. Returns `None` when no layer is open.
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) -> Option<OverlayEvent<Id, A>> {
+        let top = self.layers.last_mut()?;
+        let area = top.area.unwrap_or_default();
+        if area.contains(Position::new(mouse.column, mouse.row)) {
+            return Some(
+                top.component
+                    .handle_mouse(mouse, area)
+                    .map_or(OverlayEvent::Blocked, OverlayEvent::Action),
+            );
+        }
+        if matches!(mouse.kind, MouseEventKind::Down(_)) && top.dismiss_on_click_outside {
+            return self.pop().map(OverlayEvent::Dismissed);
+        }
+        Some(OverlayEvent::Blocked)
+    }
+}
+
+/// Fade `area` toward the theme background. Foregrounds without an RGB color
+/// fall back to the terminal's dim attribute.
+pub fn dim_area(buf: &mut Buffer, area: Rect, theme: &Theme, dim: f32) {
+    if dim <= 0.0 {
+        return;
+    }
+    let area = area.intersection(buf.area);
+    let keep = 1.0 - dim.min(1.0);
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let Some(cell) = buf.cell_mut((x, y)) else {
+                continue;
+            };
+            match fade(cell.fg, theme, keep) {
+                Some(fg) => {
+                    cell.set_fg(fg);
+                }
+                None => cell.modifier.insert(Modifier::DIM),
+            }
+            if let Some(bg) = fade(cell.bg, theme, keep) {
+                cell.set_bg(bg);
+            }
+        }
+    }
+}
+
+fn fade(color: RatatuiColor, theme: &Theme, keep: f32) -> Option<RatatuiColor> {
+    match color {
+        RatatuiColor::Rgb(r, g, b) => Some(blend_colors(
+            &Color::Rgb { r, g, b },
+            &theme.background,
+            keep,
+        )),
+        _ => None,
+    }
+}
@@ -1,4 +1,4 @@
-use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::crossterm::event::KeyEvent;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span, Text};
@@ -8,6 +8,7 @@ use unicode_width::UnicodeWidthStr;
 
 use crate::components::dialog_shell::{layout_centered, DialogLayout, DialogOptions};
 use crate::components::list::{render_list_with_chrome, ListChrome, ListState};
+use crate::keymap::{self, action, context, Keymap, PendingKeys, Resolution};
 use crate::theme::{Theme, ThemeElement};
 
 pub const DEFAULT_TABBED_DIALOG_OPTS: DialogOptions = DialogOptions {
@@ -54,6 +55,9 @@ pub struct ListKeySpec<'a, CmdT> {
     pub len: usize,
     pub on_confirm: Option<Box<dyn FnOnce(Option<usize>) -> CmdT + 'a>>,
     pub on_key: Option<Box<dyn FnMut(KeyEvent, Option<usize>) -> Option<CmdT> + 'a>>,
+    /// Enables multi-key `list` bindings such as `g g`; without it only
+    /// single-key bindings apply.
+    pub pending_keys: Option<&'a mut PendingKeys>,
 }
 
 impl<'a, CmdT> ListKeySpec<'a, CmdT> {
@@ -63,6 +67,7 @@ impl<'a, CmdT> ListKeySpec<'a, CmdT> {
             len,
             on_confirm: None,
             on_key: None,
+            pending_keys: None,
         }
     }
 
@@ -78,6 +83,11 @@ impl<'a, CmdT> ListKeySpec<'a, CmdT> {
         self.on_key = Some(Box::new(handler));
         self
     }
+
+    pub fn pending_keys(mut self, pending: &'a mut PendingKeys) -> Self {
+        self.pending_keys = Some(pending);
+        self
+    }
 }
 
 pub struct PlainKeySpec<'a, CmdT> {
@@ -119,6 +129,13 @@ impl<'a, CmdT> TabKeySpec<'a, CmdT> {
     }
 }
 
+/// Keymap contexts consulted by [`handle_tabbed_key`], for the
+/// `UiComponent::key_contexts` of tabbed dialogs.
+pub const KEY_CONTEXTS: &[&str] = &[context::TABS, context::LIST];
+
+/// Keymap contexts consulted by [`handle_list_navigation`] alone.
+pub const LIST_KEY_CONTEXTS: &[&str] = &[context::LIST];
+
 pub fn next_tab<T: Copy + Eq>(order: &[T], current: T) -> T {
     let idx = order.iter().position(|t| *t == current).unwrap_or(0);
     let next = (idx + 1) % order.len();
@@ -131,47 +148,62 @@ pub fn prev_tab<T: Copy + Eq>(order: &[T], current: T) -> T {
     order[prev]
 }
 
-pub fn apply_tab_navigation<T: Copy + Eq>(order: &[T], current: &mut T, key: &KeyEvent) -> bool {
-    match key.code {
-        KeyCode::BackTab => {
-            *current = prev_tab(order, *current);
+/// Switch tabs for keys bound to `next_tab`/`prev_tab` in `keymap`.
+pub fn apply_tab_navigation<T: Copy + Eq>(
+    keymap: &Keymap,
+    order: &[T],
+    current: &mut T,
+    key: &KeyEvent,
+) -> bool {
+    match keymap.action_for(context::TABS, key) {
+        Some(action::NEXT_TAB) => {
+            *current = next_tab(order, *current);
             true
         }
-        KeyCode::Tab | KeyCode::Char('\t') => {
-            if key.modifiers.contains(KeyModifiers::SHIFT) {
-                *current = prev_tab(order, *current);
-            } else {
-                *current = next_tab(order, *current);
-            }
+        Some(action::PREV_TAB) => {
+            *current = prev_tab(order, *current);
             true
         }
         _ => false,
     }
 }
 
+/// Move the selection for single-key `list` bindings in `keymap`.
 pub fn handle_list_navigation(
+    keymap: &Keymap,
     key: &KeyEvent,
     list: &mut ListState,
     len: usize,
     viewport_estimate: usize,
 ) -> bool {
-    match key.code {
-        KeyCode::Up => {
-            list.select_prev(len);
-            list.update_offset(viewport_estimate);
-            true
-        }
-        KeyCode::Down => {
-            list.select_next(len);
-            list.update_offset(viewport_estimate);
-            true
-        }
-        _ => false,
+    keymap
+        .action_for(context::LIST, key)
+        .is_some_and(|name| apply_list_action(name, list, len, viewport_estimate))
+}
+
+/// Apply a `list` navigation action; false for actions that do not move the
+/// selection (such as `confirm`).
+pub fn apply_list_action(
+    name: &str,
+    list: &mut ListState,
+    len: usize,
+    viewport_estimate: usize,
+) -> bool {
+    match name {
+        action::SELECT_PREV => list.select_prev(len),
+        action::SELECT_NEXT => list.select_next(len),
+        action::PAGE_UP => list.page_up(viewport_estimate, len),
+        action::PAGE_DOWN => list.page_down(viewport_estimate, len),
+        action::JUMP_TOP => list.jump_top(),
+        action::JUMP_BOTTOM => list.jump_bottom(len),
+        _ => return false,
     }
+    list.update_offset(viewport_estimate);
+    true
 }
 
-pub fn is_confirm_key(key: &KeyEvent) -> bool {
-    matches!(key.code, KeyCode::Enter | KeyCode::Char(' '))
+pub fn is_confirm_key(keymap: &Keymap, key: &KeyEvent) -> bool {
+    keymap.action_for(context::LIST, key) == Some(action::CONFIRM)
 }
 
 pub fn hint_line(
@@ -315,7 +347,23 @@ pub fn render_tabbed_body(frame: &mut Frame, area: Rect, theme: &Theme, body: Ta
     }
 }
 
+/// [`handle_tabbed_key_with`] using the active keymap.
 pub fn handle_tabbed_key<Tab, CmdT>(
+    key: KeyEvent,
+    order: &[Tab],
+    active: &mut Tab,
+    spec: TabKeySpec<'_, CmdT>,
+) -> Option<CmdT>
+where
+    Tab: Copy + Eq,
+{
+    handle_tabbed_key_with(&keymap::active(), key, order, active, spec)
+}
+
+/// Switch tabs, move the list selection and confirm with `keymap`'s `tabs`
+/// and `list` bindings, handing other keys to the spec's handlers.
+pub fn handle_tabbed_key_with<Tab, CmdT>(
+    keymap: &Keymap,
     key: KeyEvent,
     order: &[Tab],
     active: &mut Tab,
@@ -324,13 +372,29 @@ pub fn handle_tabbed_key<Tab, CmdT>(
 where
     Tab: Copy + Eq,
 {
-    if apply_tab_navigation(order, active, &key) {
+    if apply_tab_navigation(keymap, order, active, &key) {
         return None;
     }
 
     match &mut spec {
         TabKeySpec::List(list) => {
-            if handle_list_navigation(
+            if let Some(pending) = list.pending_keys.as_deref_mut() {
+                match pending.resolve(keymap, context::LIST, &key) {
+                    Resolution::Pending => return None,
+                    Resolution::Action(name)
+                        if apply_list_action(
+                            name,
+                            list.list_state,
+                            list.len,
+                            LIST_VIEWPORT_HEIGHT_ESTIMATE,
+                        ) =>
+                    {
+                        return None;
+                    }
+                    _ => {}
+                }
+            } else if handle_list_navigation(
+                keymap,
                 &key,
                 list.list_state,
                 list.len,
@@ -348,7 +412,7 @@ where
                     return Some(cmd);
                 }
             }
-            if is_confirm_key(&key) {
+            if is_confirm_key(keymap, &key) {
                 if let Some(on_confirm) = list.on_confirm.take() {
                     return Some(on_confirm(selected));
                 }
@@ -360,7 +424,7 @@ where
                     return Some(cmd);
                 }
             }
-            if is_confirm_key(&key) {
+            if is_confirm_key(keymap, &key) {
                 if let Some(on_confirm) = plain.on_confirm.take() {
                     return Some(on_confirm());
                 }
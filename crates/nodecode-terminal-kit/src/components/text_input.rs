@@ -1,18 +1,281 @@
-use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::borrow::Cow;
+use std::cell::{OnceCell, RefCell};
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::path::Path;
+
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Position, Rect};
+use ratatui::Frame;
 use tui_textarea::{CursorMove, Input as TaInput, Key as TaKey, TextArea};
-use unicode_width::UnicodeWidthChar;
+use unicode_segmentation::UnicodeSegmentation;
+use zeroize::Zeroize;
 
+use super::component::UiComponent;
+use super::input_box::{InputBox, InputBoxOutcome};
+use super::validation::{InputConstraints, TextEdit, ValidationState};
+use crate::clipboard::{self, SharedClipboard};
+use crate::keymap::{self, action, context, Keymap};
 use crate::layout::text::{
-    byte_offset_to_cursor, cursor_to_byte_offset, find_visual_row, visual_column,
-    wrapped_row_ranges,
+    find_visual_row, floor_grapheme_boundary, grapheme_width, next_grapheme_boundary,
+    prev_grapheme_boundary, visual_column, wrapped_row_ranges_atomic, PrefixSums, WrapCache,
 };
+use crate::theme::Theme;
 
 #[derive(Debug, Clone)]
 pub struct TextInput {
     inner: TextArea<'static>,
-    text_cache: String,
+    /// The lines joined, built on first use after an edit.
+    text_cache: OnceCell<String>,
+    /// Bytes of each line plus its line break, for mapping byte offsets to
+    /// lines without walking the whole text.
+    line_lengths: PrefixSums,
     prefix: String,
     placeholder: Option<String>,
+    history: EditHistory,
+    secret: Option<SecretMask>,
+    revealed: bool,
+    constraints: Option<InputConstraints>,
+    /// Chips by the index their character encodes; `None` once nothing can
+    /// bring the chip back.
+    chips: Vec<Option<Chip>>,
+    paste_chip_lines: Option<usize>,
+    /// Used instead of the process-wide clipboard when set.
+    clipboard: Option<SharedClipboard>,
+    /// Rows of the visible text per line, filled in lazily by the geometry
+    /// methods and spliced by edits.
+    wrap_cache: RefCell<WrapCache>,
+}
+
+/// A non-editable token in a `TextInput`, such as an attached file or a
+/// large paste.
+///
+/// The text holds a single placeholder character for each chip, so the
+/// cursor steps over it and Backspace removes it in one go. It is drawn as
+/// `[label]`; [`TextInput::expanded_text`] swaps in the payload. Text set,
+/// typed or pasted in can't hold placeholders of its own: they are dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chip {
+    pub label: String,
+    pub payload: String,
+}
+
+impl Chip {
+    pub fn new(label: impl Into<String>, payload: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            payload: payload.into(),
+        }
+    }
+
+    /// `[📎 name]` for a file, carrying its path.
+    pub fn file(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy();
+        Self::new(format!("📎 {name}"), path.to_string_lossy())
+    }
+
+    /// `[Pasted N lines]`, carrying the pasted text.
+    pub fn pasted(text: impl Into<String>) -> Self {
+        let text = text.into();
+        let lines = text.lines().count().max(1);
+        let noun = if lines == 1 { "line" } else { "lines" };
+        Self::new(format!("Pasted {lines} {noun}"), text)
+    }
+
+    /// Line breaks in the label are drawn as spaces so a chip stays on one
+    /// line.
+    fn display(&self) -> String {
+        format!("[{}]", self.label.replace('\n', " "))
+    }
+
+    fn display_len(&self) -> usize {
+        self.label.len() + 2
+    }
+}
+
+/// Chips are stored in Supplementary Private Use Area-B, which fonts and
+/// input methods leave alone.
+const CHIP_BASE: u32 = 0x10_0000;
+const MAX_CHIPS: usize = 0xFFFE;
+
+fn chip_char(index: usize) -> char {
+    char::from_u32(CHIP_BASE + index as u32).unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+fn is_chip_char(ch: char) -> bool {
+    ch as u32 >= CHIP_BASE
+}
+
+/// `text` without the chip characters `keep` turns down, so text from
+/// outside can't pass for chips.
+fn strip_chip_chars(text: &str, keep: impl Fn(char) -> bool) -> Cow<'_, str> {
+    let stray = |ch: char| is_chip_char(ch) && !keep(ch);
+    if text.chars().any(stray) {
+        Cow::Owned(text.chars().filter(|&ch| !stray(ch)).collect())
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// How a secret `TextInput` is drawn while hidden.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretMask {
+    /// One mask character per character of the value, e.g. `•`.
+    Char(char),
+    /// Nothing at all; the cursor stays at the start.
+    Hidden,
+}
+
+impl Default for SecretMask {
+    fn default() -> Self {
+        Self::Char('•')
+    }
+}
+
+/// Default number of undo steps kept by a `TextInput`.
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// Wrapped rows of the visible text picked out for drawing.
+#[derive(Debug, Default)]
+pub(crate) struct VisibleRows {
+    /// Length of the visible text, without any suffix.
+    pub(crate) text_len: usize,
+    /// Each row's start in the visible text and its contents.
+    pub(crate) rows: Vec<(usize, String)>,
+    /// Chip labels on the lines drawn, as ranges of the visible text.
+    pub(crate) chips: Vec<Range<usize>>,
+}
+
+/// One replacement in the text, kept both ways so it can be undone and
+/// redone without copying the rest of the text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Change {
+    at: usize,
+    removed: String,
+    inserted: String,
+    cursor_before: usize,
+    cursor_after: usize,
+}
+
+impl Change {
+    fn zeroize(&mut self) {
+        self.removed.zeroize();
+        self.inserted.zeroize();
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum EditKind {
+    /// A single typed character; runs of these undo together.
+    Typing(char),
+    Other,
+}
+
+/// Undo/redo stacks of steps, each the changes made by one edit.
+///
+/// Consecutive typing at the cursor coalesces into one step that ends after
+/// the first whitespace, so undo removes roughly a word at a time.
+#[derive(Debug, Clone)]
+struct EditHistory {
+    undo: VecDeque<Vec<Change>>,
+    redo: Vec<Vec<Change>>,
+    limit: usize,
+    typing_at: Option<usize>,
+    /// Open edit group and whether it has recorded its step yet.
+    group: Option<bool>,
+    /// Set when a dropped step held chips, whose payloads may now be
+    /// unreachable.
+    dropped_chips: bool,
+}
+
+impl EditHistory {
+    fn new(limit: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit,
+            typing_at: None,
+            group: None,
+            dropped_chips: false,
+        }
+    }
+
+    fn record(&mut self, change: Change, kind: EditKind) {
+        for step in std::mem::take(&mut self.redo) {
+            self.drop_step(step);
+        }
+        let coalesce = match &mut self.group {
+            Some(recorded) => std::mem::replace(recorded, true),
+            None => {
+                matches!(kind, EditKind::Typing(_)) && self.typing_at == Some(change.cursor_before)
+            }
+        };
+        self.typing_at = match kind {
+            EditKind::Typing(ch) if !ch.is_whitespace() => Some(change.cursor_after),
+            _ => None,
+        };
+        match self.undo.back_mut() {
+            Some(step) if coalesce => step.push(change),
+            _ => self.push_undo(vec![change]),
+        }
+    }
+
+    fn push_undo(&mut self, step: Vec<Change>) {
+        self.undo.push_back(step);
+        self.trim();
+    }
+
+    fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        while self.undo.len() > self.limit {
+            if let Some(step) = self.undo.pop_front() {
+                self.drop_step(step);
+            }
+        }
+    }
+
+    fn drop_step(&mut self, step: Vec<Change>) {
+        self.dropped_chips |= step.iter().any(|change| {
+            change
+                .removed
+                .chars()
+                .chain(change.inserted.chars())
+                .any(is_chip_char)
+        });
+    }
+
+    /// Every character the steps kept can bring back.
+    fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.undo
+            .iter()
+            .chain(&self.redo)
+            .flatten()
+            .flat_map(|change| change.removed.chars().chain(change.inserted.chars()))
+    }
+
+    fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.typing_at = None;
+        self.group = None;
+    }
+
+    fn zeroize(&mut self) {
+        for change in self.undo.iter_mut().chain(self.redo.iter_mut()).flatten() {
+            change.zeroize();
+        }
+        self.clear();
+    }
 }
 
 impl Default for TextInput {
@@ -27,72 +290,123 @@ impl TextInput {
     }
 
     pub fn with_text(text: String) -> Self {
+        let text = strip_chip_chars(&text, |_| false).into_owned();
         let mut inner = textarea_from(&text);
         inner.move_cursor(CursorMove::Bottom);
         inner.move_cursor(CursorMove::End);
         let mut this = Self {
+            line_lengths: line_lengths(inner.lines()),
             inner,
-            text_cache: text,
+            text_cache: OnceCell::from(text),
             prefix: "❯".to_string(),
             placeholder: None,
+            history: EditHistory::new(DEFAULT_HISTORY_LIMIT),
+            secret: None,
+            revealed: false,
+            constraints: None,
+            chips: Vec::new(),
+            paste_chip_lines: None,
+            clipboard: None,
+            wrap_cache: RefCell::new(WrapCache::new()),
         };
         this.sync_placeholder();
         this
     }
 
+    /// The whole value. After an edit the lines are joined again on the
+    /// first call, so per-keystroke paths avoid it.
     pub fn text(&self) -> &str {
-        &self.text_cache
+        self.text_cache
+            .get_or_init(|| join_lines(self.inner.lines()))
     }
 
     pub fn is_empty(&self) -> bool {
-        self.text_cache.is_empty()
+        matches!(self.inner.lines(), [line] if line.is_empty())
     }
 
     pub fn cursor(&self) -> usize {
-        cursor_to_byte_offset(self.inner.lines(), self.inner.cursor())
+        self.offset_of(self.inner.cursor())
     }
 
+    /// Handle `key` and return the new text if it changed. Secret inputs
+    /// never hand out their text, so they always return `None`.
     pub fn handle_search_key(&mut self, key: KeyEvent) -> Option<String> {
-        let before = self.text_cache.clone();
-        if self.handle_key(key) && before != self.text_cache {
-            Some(self.text_cache.clone())
+        self.handle_search_key_with(&keymap::active(), key)
+    }
+
+    /// [`TextInput::handle_search_key`] with `keymap` instead of the active
+    /// one.
+    pub fn handle_search_key_with(&mut self, keymap: &Keymap, key: KeyEvent) -> Option<String> {
+        if self.secret.is_some() {
+            self.handle_key_with(keymap, key);
+            return None;
+        }
+        let before = self.text().to_string();
+        if self.handle_key_with(keymap, key) && before != self.text() {
+            Some(self.text().to_string())
         } else {
             None
         }
     }
 
+    /// Replace the text and move the cursor to the end. The replacement is
+    /// one undo step.
     pub fn set_text(&mut self, text: String) {
-        self.text_cache = text.clone();
-        self.inner = textarea_from(&text);
-        self.inner.move_cursor(CursorMove::Bottom);
-        self.inner.move_cursor(CursorMove::End);
-        self.sync_placeholder();
+        let text = strip_chip_chars(&text, |_| false).into_owned();
+        self.replace_text(text);
+    }
+
+    /// Like [`TextInput::set_text`], but for text taken from this input,
+    /// such as a history draft: chips it still holds stay chips.
+    pub(crate) fn restore_text(&mut self, text: String) {
+        let text = strip_chip_chars(&text, |ch| self.chip_at(ch).is_some()).into_owned();
+        self.replace_text(text);
+    }
+
+    fn replace_text(&mut self, text: String) {
+        if text != self.text() {
+            let change = Change {
+                at: 0,
+                removed: self.text().to_string(),
+                inserted: text.clone(),
+                cursor_before: self.cursor(),
+                cursor_after: text.len(),
+            };
+            self.history.record(change, EditKind::Other);
+            self.collect_chips();
+        }
+        let cursor = text.len();
+        self.reset_text(text);
+        self.settle(cursor);
     }
 
     pub fn clear(&mut self) {
         self.set_text(String::new());
     }
 
+    /// Insert `ch` at the cursor; chip characters are ignored.
     pub fn insert_char(&mut self, ch: char) {
-        self.apply_edit(|ta| ta.insert_char(ch));
+        if is_chip_char(ch) {
+            return;
+        }
+        self.apply_edit_as(EditKind::Typing(ch), |ta| ta.insert_char(ch));
     }
 
     pub fn insert_str(&mut self, s: &str) {
+        let s = strip_chip_chars(s, |_| false);
         self.apply_edit(|ta| {
-            ta.insert_str(s);
+            ta.insert_str(&s);
         });
     }
 
+    /// Delete the selection or the grapheme cluster before the cursor.
     pub fn delete_backward(&mut self) {
-        self.apply_edit(|ta| {
-            let _ = ta.delete_char();
-        });
+        self.delete_grapheme(false);
     }
 
+    /// Delete the selection or the grapheme cluster after the cursor.
     pub fn delete_forward(&mut self) {
-        self.apply_edit(|ta| {
-            let _ = ta.delete_next_char();
-        });
+        self.delete_grapheme(true);
     }
 
     pub fn delete_to_line_start(&mut self) {
@@ -113,51 +427,146 @@ impl TextInput {
         });
     }
 
+    /// Record edits until [`TextInput::end_edit_group`] as one undo step,
+    /// e.g. a vim change and the text typed after it.
+    pub fn begin_edit_group(&mut self) {
+        if self.history.group.is_none() {
+            self.history.group = Some(false);
+        }
+    }
+
+    pub fn end_edit_group(&mut self) {
+        self.history.group = None;
+        self.history.typing_at = None;
+    }
+
+    /// Revert the last edit; returns false when there is nothing to undo.
+    /// Ends any open edit group.
+    pub fn undo(&mut self) -> bool {
+        self.end_edit_group();
+        let Some(step) = self.history.undo.pop_back() else {
+            return false;
+        };
+        for change in step.iter().rev() {
+            self.apply_change(change.at, change.inserted.len(), &change.removed);
+        }
+        self.settle(step[0].cursor_before);
+        self.history.redo.push(step);
+        true
+    }
+
+    /// Reapply the last undone edit; returns false when there is nothing to
+    /// redo.
+    pub fn redo(&mut self) -> bool {
+        self.end_edit_group();
+        let Some(step) = self.history.redo.pop() else {
+            return false;
+        };
+        for change in &step {
+            self.apply_change(change.at, change.removed.len(), &change.inserted);
+        }
+        self.settle(step[step.len() - 1].cursor_after);
+        self.history.push_undo(step);
+        self.collect_chips();
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.history.redo.is_empty()
+    }
+
+    /// Cap the number of undo steps kept, dropping the oldest beyond it.
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.history.set_limit(limit);
+        self.collect_chips();
+    }
+
+    /// Forget all undo and redo steps, e.g. after submitting a prompt,
+    /// along with the payloads of chips no longer in the text.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+        self.history.dropped_chips = true;
+        self.collect_chips();
+    }
+
+    /// Drop the payloads of chips that neither the text nor a kept undo or
+    /// redo step holds, once a step holding chips has been dropped. Indices
+    /// aren't reused until every chip is gone, so a stale chip character
+    /// never picks up another chip's payload.
+    fn collect_chips(&mut self) {
+        if !std::mem::take(&mut self.history.dropped_chips) || self.chips.is_empty() {
+            return;
+        }
+        let mut held = vec![false; self.chips.len()];
+        for ch in self.text().chars().chain(self.history.chars()) {
+            if let Some(index) = (ch as u32).checked_sub(CHIP_BASE) {
+                if let Some(slot) = held.get_mut(index as usize) {
+                    *slot = true;
+                }
+            }
+        }
+        for (chip, held) in self.chips.iter_mut().zip(held) {
+            if !held {
+                *chip = None;
+            }
+        }
+        if self.chips.iter().all(Option::is_none) {
+            self.chips.clear();
+        }
+    }
+
     pub fn cursor_start(&mut self) {
+        self.inner.cancel_selection();
         self.inner.move_cursor(CursorMove::Head);
     }
 
     pub fn cursor_end(&mut self) {
+        self.inner.cancel_selection();
         self.inner.move_cursor(CursorMove::Bottom);
         self.inner.move_cursor(CursorMove::End);
     }
 
     pub fn cursor_visual_line_end(&mut self, content_width: u16) {
-        if content_width == 0 || self.text_cache.is_empty() {
+        if content_width == 0 || self.visible_is_empty() {
             self.cursor_end();
             return;
         }
-        let ranges = wrapped_row_ranges(&self.text_cache, content_width);
-        if ranges.is_empty() {
-            return;
-        }
-        let row_idx = find_visual_row(&ranges, self.cursor().min(self.text_cache.len()));
-        let (_, end) = ranges[row_idx];
-        self.set_cursor_byte_offset(end.min(self.text_cache.len()));
+        let row = self.cursor_visual_position(content_width).0 as usize;
+        let end = self.offset_at_visual_row(row, content_width, |_, (_, end)| end);
+        self.set_cursor_byte_offset(end);
     }
 
     pub fn cursor_left(&mut self) {
-        self.inner.move_cursor(CursorMove::Back);
+        self.inner.cancel_selection();
+        self.jump_to(self.prev_boundary(self.cursor()));
     }
 
     pub fn cursor_right(&mut self) {
-        self.inner.move_cursor(CursorMove::Forward);
+        self.inner.cancel_selection();
+        self.jump_to(self.next_boundary(self.cursor()));
     }
 
     pub fn cursor_word_left(&mut self) {
-        self.inner.move_cursor(CursorMove::WordBack);
+        self.inner.cancel_selection();
+        self.move_cursor(CursorMove::WordBack);
     }
 
     pub fn cursor_word_right(&mut self) {
-        self.inner.move_cursor(CursorMove::WordForward);
+        self.inner.cancel_selection();
+        self.move_cursor(CursorMove::WordForward);
     }
 
     pub fn cursor_move_vertical(&mut self, up: bool) -> bool {
         let before = self.cursor();
+        self.inner.cancel_selection();
         if up {
-            self.inner.move_cursor(CursorMove::Up);
+            self.move_cursor(CursorMove::Up);
         } else {
-            self.inner.move_cursor(CursorMove::Down);
+            self.move_cursor(CursorMove::Down);
         }
         before != self.cursor()
     }
@@ -171,47 +580,228 @@ impl TextInput {
     }
 
     pub fn cursor_move_visual_vertical(&mut self, up: bool, content_width: u16) -> bool {
-        if content_width == 0 || self.text_cache.is_empty() {
+        if content_width == 0 || self.visible_is_empty() {
+            return false;
+        }
+        let (row, goal_col) = self.cursor_visual_position(content_width);
+        let row = row as usize;
+        let target = if up {
+            row.checked_sub(1)
+        } else if row + 1 < self.visual_rows(content_width) {
+            Some(row + 1)
+        } else {
+            None
+        };
+        let Some(target) = target else {
             return false;
+        };
+        let target_offset = self.offset_at_visual(target, goal_col as usize, content_width);
+        self.set_cursor_byte_offset(target_offset);
+        true
+    }
+
+    /// Byte offset under a visual (row, column) of the wrapped text, clamped
+    /// to the nearest row and to the end of that row.
+    pub fn offset_at_visual(&self, row: usize, col: usize, content_width: u16) -> usize {
+        if self.active_mask() == Some(SecretMask::Hidden) {
+            return self.text_len();
         }
+        self.offset_at_visual_row(row, content_width, |line, range| {
+            offset_in_row(line, range, col)
+        })
+    }
+
+    /// Selected byte range, or `None` when nothing (or an empty range) is
+    /// selected.
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let (start, end) = self.inner.selection_range()?;
+        let range = self.offset_of(start)..self.offset_of(end);
+        (!range.is_empty()).then_some(range)
+    }
+
+    pub fn selected_text(&self) -> Option<&str> {
+        self.selection().map(|range| &self.text()[range])
+    }
+
+    /// Select `range` (byte offsets, clamped to the text), leaving the cursor
+    /// at its end.
+    pub fn set_selection(&mut self, range: Range<usize>) {
+        self.inner.cancel_selection();
+        self.jump_to(self.floor_boundary(range.start));
+        self.inner.start_selection();
+        self.jump_to(self.floor_boundary(range.end));
+    }
 
-        let ranges = wrapped_row_ranges(&self.text_cache, content_width);
-        if ranges.len() <= 1 {
+    pub fn select_all(&mut self) {
+        self.inner.select_all();
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.inner.cancel_selection();
+    }
+
+    /// Delete the selected text; returns false when nothing is selected.
+    pub fn delete_selection(&mut self) -> bool {
+        if self.selection().is_none() {
             return false;
         }
+        self.apply_edit(|ta| {
+            let _ = ta.delete_char();
+        })
+    }
 
-        let current = self.cursor().min(self.text_cache.len());
-        let current_idx = find_visual_row(&ranges, current);
-        let target_idx = if up {
-            current_idx.checked_sub(1)
-        } else if current_idx + 1 < ranges.len() {
-            Some(current_idx + 1)
-        } else {
-            None
+    /// Use `clipboard` for copy/cut/paste instead of the process-wide one;
+    /// `None` goes back to it.
+    pub fn set_clipboard(&mut self, clipboard: Option<SharedClipboard>) {
+        self.clipboard = clipboard;
+    }
+
+    /// Copy the selection to the clipboard. Secret inputs never copy.
+    pub fn copy_selection(&self) -> Result<bool, clipboard::ClipboardError> {
+        if self.secret.is_some() {
+            return Ok(false);
+        }
+        let Some(text) = self.selected_text() else {
+            return Ok(false);
         };
-        let target_idx = match target_idx {
-            Some(idx) => idx,
-            None => return false,
+        let text = self.expand(text);
+        match &self.clipboard {
+            Some(clipboard) => clipboard.copy(&text)?,
+            None => clipboard::copy(&text)?,
+        }
+        Ok(true)
+    }
+
+    /// Copy the selection to the clipboard and delete it.
+    pub fn cut_selection(&mut self) -> Result<bool, clipboard::ClipboardError> {
+        if !self.copy_selection()? {
+            return Ok(false);
+        }
+        Ok(self.delete_selection())
+    }
+
+    /// Insert text from the clipboard, replacing the selection.
+    pub fn paste_from_clipboard(&mut self) -> Result<bool, clipboard::ClipboardError> {
+        let text = match &self.clipboard {
+            Some(clipboard) => clipboard.paste()?,
+            None => clipboard::paste()?,
         };
+        Ok(match text {
+            Some(text) => self.paste(&text),
+            None => false,
+        })
+    }
 
-        let goal_col = self.cursor_visual_position(content_width).1 as usize;
-        let (start, end) = ranges[target_idx];
-        let slice = &self.text_cache[start..end];
-        let mut target_offset = start;
-        let mut acc = 0usize;
-        for (byte_idx, ch) in slice.char_indices() {
-            let w = UnicodeWidthChar::width(ch).unwrap_or(0).max(1);
-            if acc + w > goal_col {
-                break;
-            }
-            acc += w;
-            target_offset = start + byte_idx + ch.len_utf8();
+    /// Insert pasted text (e.g. from a bracketed-paste event) as one undo
+    /// step, replacing the selection and normalising `\r\n` line endings.
+    /// Text longer than the limit set with
+    /// [`TextInput::set_paste_chip_lines`] goes in as a [`Chip::pasted`].
+    pub fn paste(&mut self, text: &str) -> bool {
+        let text = strip_chip_chars(text, |_| false)
+            .replace("\r\n", "\n")
+            .replace('\r', "\n");
+        if self
+            .paste_chip_lines
+            .is_some_and(|max| text.lines().count() > max)
+        {
+            return self.insert_chip(Chip::pasted(text));
+        }
+        self.apply_edit(|ta| {
+            ta.insert_str(&text);
+        })
+    }
+
+    /// Paste text of more than `lines` lines as a chip instead of inline;
+    /// `None` (the default) always pastes inline.
+    pub fn set_paste_chip_lines(&mut self, lines: Option<usize>) {
+        self.paste_chip_lines = lines;
+    }
+
+    /// Insert `chip` at the cursor, replacing the selection, as one undo
+    /// step. Returns false if the edit was rejected.
+    pub fn insert_chip(&mut self, chip: Chip) -> bool {
+        if self.chips.len() >= MAX_CHIPS {
+            return false;
         }
-        if acc < goal_col {
-            target_offset = end;
+        let ch = chip_char(self.chips.len());
+        self.chips.push(Some(chip));
+        let inserted = self.apply_edit(|ta| ta.insert_char(ch));
+        if !inserted {
+            self.chips.pop();
         }
+        inserted
+    }
 
-        self.set_cursor_byte_offset(target_offset);
+    /// The chips in the text, in order, with their byte ranges in
+    /// [`TextInput::text`].
+    pub fn chips(&self) -> Vec<(Range<usize>, &Chip)> {
+        if self.chips.is_empty() {
+            return Vec::new();
+        }
+        self.text()
+            .char_indices()
+            .filter_map(|(idx, ch)| {
+                let chip = self.chip_at(ch)?;
+                Some((idx..idx + ch.len_utf8(), chip))
+            })
+            .collect()
+    }
+
+    /// The text with each chip replaced by its payload, e.g. for submitting.
+    pub fn expanded_text(&self) -> String {
+        self.expand(self.text())
+    }
+
+    /// Where each chip's `[label]` sits in [`TextInput::visible_text`]; empty
+    /// while a secret is masked.
+    pub fn visible_chip_ranges(&self) -> Vec<Range<usize>> {
+        if self.chips.is_empty() || self.active_mask().is_some() {
+            return Vec::new();
+        }
+        let mut ranges = Vec::new();
+        let mut acc = 0;
+        for ch in self.text().chars() {
+            let len = self.visible_len(ch);
+            if self.chip_at(ch).is_some() {
+                ranges.push(acc..acc + len);
+            }
+            acc += len;
+        }
+        ranges
+    }
+
+    /// Click to place the cursor and drag to select, using the geometry of
+    /// the last `InputBox::render`. Returns whether the event was inside the
+    /// text area.
+    pub fn handle_mouse(&mut self, mouse: MouseEvent, outcome: &InputBoxOutcome) -> bool {
+        let area = outcome.content_area;
+        let position = Position::new(mouse.column, mouse.row);
+        let dragging = matches!(mouse.kind, MouseEventKind::Drag(MouseButton::Left));
+        if !area.contains(position) && !dragging {
+            return false;
+        }
+        let row = outcome.scroll_offset as usize + mouse.row.saturating_sub(area.y) as usize;
+        let col = mouse.column.saturating_sub(area.x) as usize;
+        let offset = self.offset_at_visual(row, col, area.width.max(1));
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.inner.cancel_selection();
+                self.jump_to(offset);
+                self.inner.start_selection();
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if !self.inner.is_selecting() {
+                    self.inner.start_selection();
+                }
+                self.jump_to(offset);
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                if self.selection().is_none() {
+                    self.inner.cancel_selection();
+                }
+            }
+            _ => return false,
+        }
         true
     }
 
@@ -234,67 +824,772 @@ impl TextInput {
     }
 
     pub fn display_text(&self) -> String {
-        format!("{}{}", self.prefix, self.text_cache)
+        format!("{}{}", self.prefix, self.visible_text())
     }
 
+    /// Restrict what edits are accepted; see [`InputConstraints`]. The
+    /// current text is kept as is, and `set_text` is never filtered.
+    pub fn set_constraints(&mut self, constraints: Option<InputConstraints>) {
+        self.constraints = constraints;
+    }
+
+    pub fn constraints(&self) -> Option<&InputConstraints> {
+        self.constraints.as_ref()
+    }
+
+    /// Live validation of the current text; `Idle` without constraints.
+    pub fn validation(&self) -> ValidationState {
+        self.constraints
+            .as_ref()
+            .map_or(ValidationState::Idle, |constraints| {
+                constraints.validate(self.text())
+            })
+    }
+
+    /// Treat the value as a secret drawn with `mask`, or as plain text with
+    /// `None`. Secret inputs refuse to copy or cut and wipe their buffers
+    /// when dropped.
+    pub fn set_secret(&mut self, mask: Option<SecretMask>) {
+        self.secret = mask;
+        self.revealed = false;
+        self.wrap_cache.get_mut().invalidate();
+    }
+
+    pub fn secret_mask(&self) -> Option<SecretMask> {
+        self.secret
+    }
+
+    pub fn is_secret(&self) -> bool {
+        self.secret.is_some()
+    }
+
+    /// Whether a secret is currently drawn in the clear.
+    pub fn is_revealed(&self) -> bool {
+        self.revealed
+    }
+
+    pub fn set_revealed(&mut self, revealed: bool) {
+        self.revealed = revealed && self.secret.is_some();
+        self.wrap_cache.get_mut().invalidate();
+    }
+
+    /// Flip between masked and revealed; returns false for plain inputs.
+    pub fn toggle_reveal(&mut self) -> bool {
+        if self.secret.is_none() {
+            return false;
+        }
+        self.revealed = !self.revealed;
+        self.wrap_cache.get_mut().invalidate();
+        true
+    }
+
+    /// The text as drawn: the value with chips as `[label]`, or its mask
+    /// while a secret is hidden. Line breaks are kept so masked multi-line
+    /// values wrap the same way.
+    pub fn visible_text(&self) -> Cow<'_, str> {
+        match self.active_mask() {
+            None if self.chips.is_empty() => Cow::Borrowed(self.text()),
+            None => self.expand_with(self.text(), Chip::display),
+            Some(SecretMask::Hidden) => Cow::Borrowed(""),
+            Some(SecretMask::Char(mask)) => Cow::Owned(
+                self.text()
+                    .chars()
+                    .map(|ch| if ch == '\n' { ch } else { mask })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Map a byte offset in the value to the matching offset in
+    /// [`TextInput::visible_text`].
+    pub fn visible_offset(&self, offset: usize) -> usize {
+        let offset = offset.min(self.text_len());
+        if self.active_mask().is_none() && self.chips.is_empty() {
+            return offset;
+        }
+        self.text()[..offset]
+            .chars()
+            .map(|ch| self.visible_len(ch))
+            .sum()
+    }
+
+    /// Only the cursor's line is measured; rows above it come from the wrap
+    /// cache.
     pub fn cursor_visual_position(&self, content_width: u16) -> (u16, u16) {
-        let cursor = self.cursor().min(self.text_cache.len());
-        let ranges = wrapped_row_ranges(&self.text_cache, content_width);
-        if ranges.is_empty() {
+        if self.active_mask() == Some(SecretMask::Hidden) {
             return (0, 0);
         }
-        let row_idx = find_visual_row(&ranges, cursor);
-        let (start, end) = ranges[row_idx];
+        let (line_idx, col_chars) = self.inner.cursor();
+        let raw = &self.inner.lines()[line_idx];
+        let cursor: usize = raw
+            .chars()
+            .take(col_chars)
+            .map(|ch| self.visible_len(ch))
+            .sum();
+        let (line, _) = self.visible_line(raw);
+        let (rows_before, (start, end), row_idx) = self.with_wrap(content_width, |cache| {
+            let rows = cache.line_rows(line_idx);
+            let row_idx = find_visual_row(rows, cursor);
+            let range = rows.get(row_idx).copied().unwrap_or((0, line.len()));
+            (cache.rows_before(line_idx), range, row_idx)
+        });
         let clamped_cursor = cursor.min(end).max(start);
-        let col = visual_column(&self.text_cache[start..clamped_cursor]);
-        (row_idx as u16, col as u16)
+        // A cluster wider than the row overflows it; keep the cursor inside.
+        let col = visual_column(&line[start..clamped_cursor]).min(content_width as usize);
+        ((rows_before + row_idx) as u16, col as u16)
     }
 
+    /// Byte ranges of the wrapped rows of [`TextInput::visible_text`].
+    ///
+    /// Rows are cached per line, so after an edit only the lines it touched
+    /// are wrapped again.
     pub fn wrapped_rows(&self, content_width: u16) -> Vec<(usize, usize)> {
-        wrapped_row_ranges(&self.text_cache, content_width)
+        if self.active_mask() == Some(SecretMask::Hidden) {
+            return vec![(0, 0)];
+        }
+        self.with_wrap(content_width, WrapCache::rows)
+    }
+
+    /// Number of wrapped rows of the visible text followed by `suffix`,
+    /// e.g. an inline suggestion. Only the last line is wrapped again.
+    pub(crate) fn visual_rows_with_suffix(&self, content_width: u16, suffix: &str) -> usize {
+        if suffix.is_empty() || self.active_mask() == Some(SecretMask::Hidden) {
+            return self.visual_rows(content_width);
+        }
+        let last = self.inner.lines().len() - 1;
+        let (tail, _) = self.last_line_with_suffix(content_width, suffix);
+        self.with_wrap(content_width, |cache| {
+            cache.total_rows() - cache.line_rows(last).len() + tail.len()
+        })
+    }
+
+    /// Rows `window` of the visible text followed by `suffix`, for drawing
+    /// only what is on screen.
+    pub(crate) fn visible_rows(
+        &self,
+        content_width: u16,
+        suffix: &str,
+        window: Range<usize>,
+    ) -> VisibleRows {
+        let mut out = VisibleRows::default();
+        if self.active_mask() == Some(SecretMask::Hidden) {
+            if window.start == 0 && !window.is_empty() {
+                out.rows.push((0, String::new()));
+            }
+            return out;
+        }
+        let lines = self.inner.lines();
+        let last = lines.len() - 1;
+        let tail = (!suffix.is_empty()).then(|| self.last_line_with_suffix(content_width, suffix));
+        self.with_wrap(content_width, |cache| {
+            out.text_len = cache.text_len();
+            let last_start = cache.rows_before(last);
+            let (mut line_idx, mut skip) = if window.start >= last_start {
+                (last, window.start - last_start)
+            } else {
+                cache.row_line(window.start)
+            };
+            let mut remaining = window.len();
+            while remaining > 0 && line_idx <= last {
+                let start = cache.line_start(line_idx);
+                let (text, units) = self.visible_line(&lines[line_idx]);
+                let (rows, text) = match &tail {
+                    Some((rows, text)) if line_idx == last => (rows.as_slice(), Cow::from(text)),
+                    _ => (cache.line_rows(line_idx), text),
+                };
+                for &(s, e) in rows.iter().skip(skip).take(remaining) {
+                    out.rows.push((start + s, text[s..e].to_string()));
+                    remaining -= 1;
+                }
+                out.chips.extend(
+                    units
+                        .into_iter()
+                        .map(|unit| start + unit.start..start + unit.end),
+                );
+                skip = 0;
+                line_idx += 1;
+            }
+        });
+        out
     }
 
+    /// Apply a key press using the active keymap; see
+    /// [`TextInput::handle_key_with`].
     pub fn handle_key(&mut self, key: KeyEvent) -> bool {
-        match (key.code, key.modifiers) {
-            (KeyCode::Backspace, KeyModifiers::NONE) => {
-                let had_content = self.cursor() > 0;
+        self.handle_key_with(&keymap::active(), key)
+    }
+
+    /// Apply a key press using `keymap`'s `text_input` bindings.
+    ///
+    /// Unbound keys fall through to the underlying textarea, so characters are
+    /// typed and its built-in shortcuts keep working.
+    pub fn handle_key_with(&mut self, keymap: &Keymap, key: KeyEvent) -> bool {
+        if let Some(changed) = keymap
+            .action_for(context::TEXT_INPUT, &key)
+            .and_then(|name| self.apply_action(name))
+        {
+            return changed;
+        }
+        if matches!(key.code, KeyCode::Char(ch) if is_chip_char(ch)) {
+            return false;
+        }
+        let before = self.cursor();
+        let kind = match key.code {
+            KeyCode::Char(ch)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                EditKind::Typing(ch)
+            }
+            _ => EditKind::Other,
+        };
+        let changed = self.apply_edit_as(kind, |ta| {
+            ta.input(ta_input_from_key_event(key));
+        });
+        self.snap_cursor(before);
+        changed
+    }
+
+    /// Run a named `text_input` action. Returns whether the text changed, or
+    /// `None` if the action is not a text input action.
+    pub fn apply_action(&mut self, name: &str) -> Option<bool> {
+        let changed = match name {
+            action::DELETE_BACKWARD => {
+                let had_content = self.cursor() > 0 || self.selection().is_some();
                 self.delete_backward();
                 had_content
             }
-            (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
-                self.delete_to_line_start();
-                true
-            }
-            (KeyCode::Char('w'), KeyModifiers::CONTROL) => {
+            action::DELETE_FORWARD => self.delete_grapheme(true),
+            action::DELETE_WORD_BACKWARD => {
                 self.delete_word_backward();
                 true
             }
-            (KeyCode::Char('d'), KeyModifiers::CONTROL) => {
-                self.delete_forward();
+            action::DELETE_WORD_FORWARD => self.apply_edit(|ta| {
+                let _ = ta.delete_next_word();
+            }),
+            action::DELETE_TO_LINE_START => {
+                self.delete_to_line_start();
                 true
             }
-            _ => self.apply_edit(|ta| {
-                ta.input(ta_input_from_key_event(key));
+            action::DELETE_TO_LINE_END => self.apply_edit(|ta| {
+                let _ = ta.delete_line_by_end();
             }),
-        }
+            action::CURSOR_LEFT => {
+                self.cursor_left();
+                false
+            }
+            action::CURSOR_RIGHT => {
+                self.cursor_right();
+                false
+            }
+            action::CURSOR_UP => {
+                self.cursor_up_line();
+                false
+            }
+            action::CURSOR_DOWN => {
+                self.cursor_down_line();
+                false
+            }
+            action::WORD_LEFT => {
+                self.cursor_word_left();
+                false
+            }
+            action::WORD_RIGHT => {
+                self.cursor_word_right();
+                false
+            }
+            action::LINE_START => {
+                self.cursor_start();
+                false
+            }
+            action::LINE_END => {
+                self.inner.cancel_selection();
+                self.inner.move_cursor(CursorMove::End);
+                false
+            }
+            action::UNDO => self.undo(),
+            action::REDO => self.redo(),
+            action::SELECT_LEFT => self.extend_selection(CursorMove::Back),
+            action::SELECT_RIGHT => self.extend_selection(CursorMove::Forward),
+            action::SELECT_UP => self.extend_selection(CursorMove::Up),
+            action::SELECT_DOWN => self.extend_selection(CursorMove::Down),
+            action::SELECT_WORD_LEFT => self.extend_selection(CursorMove::WordBack),
+            action::SELECT_WORD_RIGHT => self.extend_selection(CursorMove::WordForward),
+            action::SELECT_LINE_START => self.extend_selection(CursorMove::Head),
+            action::SELECT_LINE_END => self.extend_selection(CursorMove::End),
+            action::SELECT_ALL => {
+                self.select_all();
+                false
+            }
+            // Clipboard failures leave the text untouched; there is nowhere
+            // to report them from a key handler.
+            action::COPY => {
+                let _ = self.copy_selection();
+                false
+            }
+            action::CUT => self.cut_selection().unwrap_or(false),
+            action::PASTE => self.paste_from_clipboard().unwrap_or(false),
+            action::TOGGLE_REVEAL if self.secret.is_some() => {
+                self.toggle_reveal();
+                false
+            }
+            _ => return None,
+        };
+        Some(changed)
     }
 
     pub fn visual_rows(&self, content_width: u16) -> usize {
-        wrapped_row_ranges(&self.text_cache, content_width).len()
+        if self.active_mask() == Some(SecretMask::Hidden) {
+            return 1;
+        }
+        self.with_wrap(content_width, WrapCache::total_rows)
     }
 
+    /// Move the cursor, dropping any selection. Offsets inside a grapheme
+    /// cluster move to its start.
     pub fn set_cursor_byte_offset(&mut self, offset: usize) {
-        let (row, col_chars) = byte_offset_to_cursor(self.inner.lines(), offset);
+        self.inner.cancel_selection();
+        self.jump_to(self.floor_boundary(offset));
+    }
+
+    fn jump_to(&mut self, offset: usize) {
+        let (row, local) = self.position_of(offset);
+        let col_chars = self.inner.lines()[row]
+            .char_indices()
+            .take_while(|&(idx, _)| idx < local)
+            .count();
         self.inner
             .move_cursor(CursorMove::Jump(row as u16, col_chars as u16));
     }
 
+    fn text_len(&self) -> usize {
+        self.line_lengths.total() - 1
+    }
+
+    fn line_start(&self, row: usize) -> usize {
+        self.line_lengths.prefix(row)
+    }
+
+    /// Line of `offset` and its byte offset within the line, clamped to the
+    /// end of the text.
+    fn position_of(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.text_len());
+        let row = self.line_lengths.find(offset);
+        (row, offset - self.line_start(row))
+    }
+
+    /// Byte offset of a textarea (row, char column) position.
+    fn offset_of(&self, (row, col_chars): (usize, usize)) -> usize {
+        let line = &self.inner.lines()[row];
+        let local = line
+            .char_indices()
+            .nth(col_chars)
+            .map_or(line.len(), |(idx, _)| idx);
+        self.line_start(row) + local
+    }
+
+    /// `offset` rounded down to a grapheme boundary. Clusters never span a
+    /// line break here, so only the offset's line is looked at.
+    fn floor_boundary(&self, offset: usize) -> usize {
+        let (row, local) = self.position_of(offset);
+        self.line_start(row) + floor_grapheme_boundary(&self.inner.lines()[row], local)
+    }
+
+    fn prev_boundary(&self, offset: usize) -> usize {
+        let (row, local) = self.position_of(offset);
+        if local == 0 {
+            return self.line_start(row).saturating_sub(1);
+        }
+        self.line_start(row) + prev_grapheme_boundary(&self.inner.lines()[row], local)
+    }
+
+    fn next_boundary(&self, offset: usize) -> usize {
+        let (row, local) = self.position_of(offset);
+        let line = &self.inner.lines()[row];
+        if local == line.len() {
+            return (self.line_start(row) + local + 1).min(self.text_len());
+        }
+        self.line_start(row) + next_grapheme_boundary(line, local)
+    }
+
+    /// Chars between two neighbouring grapheme boundaries.
+    fn chars_between(&self, start: usize, end: usize) -> usize {
+        let (row, local) = self.position_of(start);
+        self.inner.lines()[row]
+            .get(local..local + (end - start))
+            .map_or(1, |cluster| cluster.chars().count())
+    }
+
+    fn visible_is_empty(&self) -> bool {
+        self.is_empty() || self.active_mask() == Some(SecretMask::Hidden)
+    }
+
+    fn extend_selection(&mut self, movement: CursorMove) -> bool {
+        if !self.inner.is_selecting() {
+            self.inner.start_selection();
+        }
+        self.move_cursor(movement);
+        false
+    }
+
+    /// A textarea motion, which counts `char`s, kept to grapheme boundaries.
+    fn move_cursor(&mut self, movement: CursorMove) {
+        let before = self.cursor();
+        self.inner.move_cursor(movement);
+        self.snap_cursor(before);
+    }
+
+    /// Move a cursor that ended up inside a grapheme cluster to the
+    /// cluster's edge in the direction it was moving.
+    fn snap_cursor(&mut self, before: usize) {
+        let cursor = self.cursor();
+        let floor = self.floor_boundary(cursor);
+        if floor == cursor {
+            return;
+        }
+        let target = if cursor > before {
+            self.next_boundary(floor)
+        } else {
+            floor
+        };
+        self.jump_to(target);
+    }
+
+    fn delete_grapheme(&mut self, forward: bool) -> bool {
+        let cursor = self.cursor();
+        let chars = if self.selection().is_some() {
+            1
+        } else if forward {
+            self.chars_between(cursor, self.next_boundary(cursor))
+                .max(1)
+        } else {
+            let start = self.prev_boundary(cursor);
+            self.chars_between(start, cursor).max(1)
+        };
+        self.apply_edit(|ta| {
+            for _ in 0..chars {
+                let _ = if forward {
+                    ta.delete_next_char()
+                } else {
+                    ta.delete_char()
+                };
+            }
+        })
+    }
+
     fn apply_edit(&mut self, f: impl FnOnce(&mut TextArea<'static>)) -> bool {
-        let before = self.text_cache.clone();
+        self.apply_edit_as(EditKind::Other, f)
+    }
+
+    /// Run a textarea edit and bring the line index, wrap cache and history
+    /// up to date with it.
+    ///
+    /// Textarea edits only touch the lines around the cursor and the
+    /// selection, plus a neighbour when a line break is deleted, so only
+    /// those lines are compared and copied.
+    ///
+    /// For secret inputs every copy of the text made along the way is wiped
+    /// before it is dropped.
+    fn apply_edit_as(&mut self, kind: EditKind, f: impl FnOnce(&mut TextArea<'static>)) -> bool {
+        let secret = self.secret.is_some();
+        let selection = self.inner.selection_range();
+        if selection.is_some() {
+            // Typing over a selection starts a new undo step.
+            self.history.typing_at = None;
+        }
+        let cursor_before = self.cursor();
+        let old_count = self.inner.lines().len();
+        let row = self.inner.cursor().0;
+        let (first, last) = selection.map_or((row, row), |(s, e)| (s.0.min(row), e.0.max(row)));
+        let first = first.saturating_sub(1);
+        let old_last = (last + 1).min(old_count - 1);
+        let at = self.line_start(first);
+        let mut old_text = join_lines(&self.inner.lines()[first..=old_last]);
         f(&mut self.inner);
-        self.text_cache = join_lines(self.inner.lines());
+        if secret {
+            // Word and line deletions copy what they remove into the
+            // textarea's yank buffer; a secret must not linger there.
+            self.inner.set_yank_text(String::new());
+        }
+
+        // Lines below the edit only moved, by the change in the line count.
+        let lines = self.inner.lines();
+        let new_last = old_last + lines.len() - old_count;
+        let mut new_text = join_lines(&lines[first..=new_last]);
+        let diffed = (old_text != new_text).then(|| diff(at, &old_text, &new_text));
+        if secret {
+            old_text.zeroize();
+            new_text.zeroize();
+        }
+        let Some(diffed) = diffed else {
+            return false;
+        };
+        let mut change = Change {
+            cursor_before,
+            cursor_after: self.cursor(),
+            ..diffed
+        };
+        self.line_lengths.splice(
+            first,
+            old_last - first + 1,
+            lines[first..=new_last].iter().map(|line| line.len() + 1),
+        );
+        self.wrap_cache
+            .get_mut()
+            .splice(first, old_last - first + 1, new_last - first + 1);
+        self.invalidate_text();
+
+        if let Some(constraints) = &self.constraints {
+            let mut before = self.text().to_string();
+            before.replace_range(
+                change.at..change.at + change.inserted.len(),
+                &change.removed,
+            );
+            let edit = TextEdit {
+                before: &before,
+                at: change.at,
+                removed: &change.removed,
+                inserted: &change.inserted,
+                backward: cursor_before > change.at,
+            };
+            match constraints.admit(&edit) {
+                Some((mut admitted, _)) if admitted == self.text() => {
+                    if secret {
+                        admitted.zeroize();
+                        before.zeroize();
+                    }
+                }
+                // Masks move typed characters between slots and insert
+                // literals, so the result can differ from the edit.
+                Some((admitted, cursor)) if admitted != before => {
+                    self.reset_text(admitted);
+                    self.jump_to(cursor);
+                    let mut replaced = std::mem::replace(
+                        &mut change,
+                        Change {
+                            at: 0,
+                            removed: before,
+                            inserted: self.text().to_string(),
+                            cursor_before,
+                            cursor_after: self.cursor(),
+                        },
+                    );
+                    if secret {
+                        replaced.zeroize();
+                    }
+                }
+                rejected => {
+                    self.apply_change(change.at, change.inserted.len(), &change.removed);
+                    self.jump_to(cursor_before);
+                    if secret {
+                        change.zeroize();
+                        before.zeroize();
+                        if let Some((mut admitted, _)) = rejected {
+                            admitted.zeroize();
+                        }
+                    }
+                    return false;
+                }
+            }
+        }
+        debug_assert_eq!(self.line_lengths.len(), self.inner.lines().len());
+        self.sync_placeholder();
+        self.history.record(change, kind);
+        self.collect_chips();
+        true
+    }
+
+    /// Replay an undo or redo change: replace `len` bytes from `at` with
+    /// `with`.
+    fn apply_change(&mut self, at: usize, len: usize, with: &str) {
+        if with.contains('\r') {
+            // The textarea drops `\r` before `\n` when inserting.
+            let mut text = self.text().to_string();
+            text.replace_range(at..at + len, with);
+            self.reset_text(text);
+            return;
+        }
+        let first = self.position_of(at).0;
+        let removed = self.position_of(at + len).0 - first + 1;
+        self.inner.cancel_selection();
+        self.jump_to(at);
+        self.inner.start_selection();
+        self.jump_to(at + len);
+        self.inner.insert_str(with);
+        self.inner.cancel_selection();
+        let inserted = with.matches('\n').count() + 1;
+        self.line_lengths.splice(
+            first,
+            removed,
+            self.inner.lines()[first..first + inserted]
+                .iter()
+                .map(|line| line.len() + 1),
+        );
+        self.wrap_cache.get_mut().splice(first, removed, inserted);
+        self.invalidate_text();
+    }
+
+    /// Replace the whole text, rebuilding the line index and the wrap
+    /// cache.
+    fn reset_text(&mut self, text: String) {
+        let old = std::mem::replace(&mut self.inner, textarea_from(&text));
+        if self.secret.is_some() {
+            old.into_lines().zeroize();
+        }
+        self.line_lengths = line_lengths(self.inner.lines());
+        self.wrap_cache.get_mut().invalidate();
+        self.invalidate_text();
+        self.text_cache = OnceCell::from(text);
+    }
+
+    /// Drop the joined text after an edit, wiping it if it was a secret.
+    fn invalidate_text(&mut self) {
+        if let Some(mut text) = self.text_cache.take() {
+            if self.secret.is_some() {
+                text.zeroize();
+            }
+        }
+    }
+
+    /// Place the cursor after replacing text, without touching the history.
+    fn settle(&mut self, cursor: usize) {
+        debug_assert_eq!(self.line_lengths.len(), self.inner.lines().len());
+        self.set_cursor_byte_offset(cursor);
+        self.history.typing_at = None;
         self.sync_placeholder();
-        before != self.text_cache
+    }
+
+    fn active_mask(&self) -> Option<SecretMask> {
+        self.secret.filter(|_| !self.revealed)
+    }
+
+    /// Byte offset in the value of `visible` bytes into line `idx` as
+    /// drawn, rounded down to a grapheme boundary and to the start of a
+    /// chip.
+    fn offset_in_line(&self, idx: usize, visible: usize) -> usize {
+        let raw = &self.inner.lines()[idx];
+        let local = if self.active_mask().is_none() && self.chips.is_empty() {
+            visible.min(raw.len())
+        } else {
+            let mut acc = 0;
+            raw.char_indices()
+                .find(|&(_, ch)| {
+                    acc += self.visible_len(ch);
+                    acc > visible
+                })
+                .map_or(raw.len(), |(idx, _)| idx)
+        };
+        self.line_start(idx) + floor_grapheme_boundary(raw, local)
+    }
+
+    /// Byte offset in visual row `row` (clamped to the last row), picked by
+    /// `pick` from the row's visible line and its range in it.
+    fn offset_at_visual_row(
+        &self,
+        row: usize,
+        content_width: u16,
+        pick: impl FnOnce(&str, (usize, usize)) -> usize,
+    ) -> usize {
+        let lines = self.inner.lines();
+        let (line_idx, visible) = self.with_wrap(content_width, |cache| {
+            let (line_idx, row_idx) = cache.row_line(row);
+            let (text, _) = self.visible_line(&lines[line_idx]);
+            let range = cache
+                .line_rows(line_idx)
+                .get(row_idx)
+                .copied()
+                .unwrap_or((0, text.len()));
+            (line_idx, pick(&text, range))
+        });
+        self.offset_in_line(line_idx, visible)
+    }
+
+    /// The last visible line followed by `suffix`, and its rows.
+    fn last_line_with_suffix(
+        &self,
+        content_width: u16,
+        suffix: &str,
+    ) -> (Vec<(usize, usize)>, String) {
+        let lines = self.inner.lines();
+        let (line, units) = self.visible_line(&lines[lines.len() - 1]);
+        let mut text = line.into_owned();
+        text.push_str(suffix);
+        (
+            wrapped_row_ranges_atomic(&text, content_width, &units),
+            text,
+        )
+    }
+
+    /// Bytes `ch` of the value takes up in the visible text.
+    fn visible_len(&self, ch: char) -> usize {
+        match self.active_mask() {
+            Some(SecretMask::Char(mask)) if ch != '\n' => mask.len_utf8(),
+            Some(SecretMask::Hidden) => 0,
+            _ => self.chip_at(ch).map_or(ch.len_utf8(), Chip::display_len),
+        }
+    }
+
+    fn chip_at(&self, ch: char) -> Option<&Chip> {
+        let index = (ch as u32).checked_sub(CHIP_BASE)?;
+        self.chips.get(index as usize)?.as_ref()
+    }
+
+    fn expand(&self, text: &str) -> String {
+        self.expand_with(text, |chip| chip.payload.clone())
+            .into_owned()
+    }
+
+    fn expand_with<'t>(&self, text: &'t str, f: impl Fn(&Chip) -> String) -> Cow<'t, str> {
+        if !text.chars().any(|ch| self.chip_at(ch).is_some()) {
+            return Cow::Borrowed(text);
+        }
+        let mut out = String::with_capacity(text.len());
+        for ch in text.chars() {
+            match self.chip_at(ch) {
+                Some(chip) => out.push_str(&f(chip)),
+                None => out.push(ch),
+            }
+        }
+        Cow::Owned(out)
+    }
+
+    /// One line of the value as drawn, with the chips in it as atomic
+    /// units.
+    fn visible_line<'t>(&self, raw: &'t str) -> (Cow<'t, str>, Vec<Range<usize>>) {
+        match self.active_mask() {
+            Some(SecretMask::Char(mask)) => {
+                let masked = std::iter::repeat_n(mask, raw.chars().count()).collect();
+                (Cow::Owned(masked), Vec::new())
+            }
+            Some(SecretMask::Hidden) => (Cow::Borrowed(""), Vec::new()),
+            None if self.chips.is_empty() => (Cow::Borrowed(raw), Vec::new()),
+            None => {
+                let mut text = String::with_capacity(raw.len());
+                let mut units = Vec::new();
+                for ch in raw.chars() {
+                    match self.chip_at(ch) {
+                        Some(chip) => {
+                            let start = text.len();
+                            text.push_str(&chip.display());
+                            units.push(start..text.len());
+                        }
+                        None => text.push(ch),
+                    }
+                }
+                (Cow::Owned(text), units)
+            }
+        }
+    }
+
+    /// Bring the wrap cache up to date for `content_width` and read it.
+    fn with_wrap<R>(&self, content_width: u16, f: impl FnOnce(&WrapCache) -> R) -> R {
+        let lines = self.inner.lines();
+        let mut cache = self.wrap_cache.borrow_mut();
+        cache.update(content_width, lines.len(), |idx| {
+            self.visible_line(&lines[idx])
+        });
+        f(&cache)
     }
 
     fn sync_placeholder(&mut self) {
@@ -303,18 +1598,105 @@ impl TextInput {
     }
 }
 
+impl Drop for TextInput {
+    /// Best effort: copies left behind by earlier reallocations are out of
+    /// reach.
+    fn drop(&mut self) {
+        if self.secret.is_none() {
+            return;
+        }
+        if let Some(text) = self.text_cache.get_mut() {
+            text.zeroize();
+        }
+        std::mem::take(&mut self.inner).into_lines().zeroize();
+        self.history.zeroize();
+    }
+}
+
+/// A bare input drawn as an `InputBox`, e.g. as a `FocusTree` leaf. Emits an
+/// action whenever a key changes the text.
+impl UiComponent for TextInput {
+    type Action = ();
+
+    fn update(&mut self, _action: ()) {}
+
+    fn view(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        InputBox::new(self, theme).render(frame, area);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<()> {
+        TextInput::handle_key(self, key).then_some(())
+    }
+
+    fn key_contexts(&self) -> &[&str] {
+        &[context::TEXT_INPUT]
+    }
+}
+
+/// Offset of the last grapheme boundary in the row at or before `goal_col`.
+fn offset_in_row(text: &str, (start, end): (usize, usize), goal_col: usize) -> usize {
+    let mut target_offset = start;
+    let mut acc = 0usize;
+    for (byte_idx, grapheme) in text[start..end].grapheme_indices(true) {
+        let w = grapheme_width(grapheme);
+        if acc + w > goal_col {
+            break;
+        }
+        acc += w;
+        target_offset = start + byte_idx + grapheme.len();
+    }
+    target_offset
+}
+
 fn textarea_from(text: &str) -> TextArea<'static> {
-    if text.is_empty() {
+    let mut inner = if text.is_empty() {
         TextArea::default()
     } else {
         TextArea::from(text.split('\n'))
-    }
+    };
+    // `TextInput` keeps its own history so it survives `set_text`.
+    inner.set_max_histories(0);
+    inner
 }
 
 fn join_lines(lines: &[String]) -> String {
     lines.join("\n")
 }
 
+/// Bytes of each line plus its line break.
+fn line_lengths(lines: &[String]) -> PrefixSums {
+    PrefixSums::new(lines.iter().map(|line| line.len() + 1).collect())
+}
+
+/// The part of `new` that differs from `old`, both starting at byte `at`,
+/// as a change with no cursor positions yet.
+fn diff(at: usize, old: &str, new: &str) -> Change {
+    let mut prefix = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !new.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    let mut suffix = old[prefix..]
+        .bytes()
+        .rev()
+        .zip(new[prefix..].bytes().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !new.is_char_boundary(new.len() - suffix) {
+        suffix -= 1;
+    }
+    Change {
+        at: at + prefix,
+        removed: old[prefix..old.len() - suffix].to_string(),
+        inserted: new[prefix..new.len() - suffix].to_string(),
+        cursor_before: 0,
+        cursor_after: 0,
+    }
+}
+
 fn ta_input_from_key_event(key: KeyEvent) -> TaInput {
     let mapped = match key.code {
         KeyCode::Backspace => TaKey::Backspace,
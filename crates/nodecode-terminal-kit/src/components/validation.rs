@@ -0,0 +1,379 @@
+//! Constrained entry and live validation for `TextInput`.
+//!
+//! [`InputConstraints`] has two halves. Hard constraints (maximum length,
+//! character filters, number syntax and [`InputMask`] structure) reject an
+//! edit outright, so the text never leaves the allowed shape. Soft checks
+//! (number ranges, mask completeness, regex patterns and custom validators)
+//! produce a [`ValidationState`] that `InputBox::validation` draws as a
+//! coloured border and message.
+
+use std::fmt;
+use std::ops::{Range, RangeInclusive};
+use std::sync::Arc;
+
+use regex::Regex;
+
+/// Live validation result for the current text.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ValidationState {
+    /// Nothing to report, e.g. the input is empty.
+    #[default]
+    Idle,
+    /// Valid, with an optional message to show.
+    Valid(Option<String>),
+    Invalid(String),
+}
+
+impl ValidationState {
+    pub fn is_invalid(&self) -> bool {
+        matches!(self, Self::Invalid(_))
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            Self::Idle | Self::Valid(None) => None,
+            Self::Valid(Some(message)) | Self::Invalid(message) => Some(message),
+        }
+    }
+}
+
+/// A fixed-shape format such as `##:##`.
+///
+/// `#` takes a digit, `A` a letter, `*` any character, and everything else
+/// is a literal that is inserted automatically as the user types past it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputMask {
+    pattern: Vec<char>,
+}
+
+impl InputMask {
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.chars().collect(),
+        }
+    }
+
+    pub fn pattern(&self) -> String {
+        self.pattern.iter().collect()
+    }
+
+    /// Fit `text` to the mask, inserting literals where needed. `None` when
+    /// a character does not fit its slot or the text is too long.
+    pub fn apply(&self, text: &str) -> Option<String> {
+        self.fit(text.chars()).map(|(out, _)| out)
+    }
+
+    /// Apply `edit` to text that already fits the mask, keeping the typed
+    /// characters in their slots: those after the edit shift along the
+    /// placeholders and the literals stay put. Deleting only a literal
+    /// deletes the typed character beside it instead. Returns the new text
+    /// and the cursor after the edit.
+    pub fn edit(&self, edit: &TextEdit<'_>) -> Option<(String, usize)> {
+        if self.apply(edit.before).as_deref() != Some(edit.before) {
+            // Not in shape yet, e.g. after `set_text`: fit the result whole.
+            let after = edit.after();
+            let text = self.apply(&after)?;
+            let cursor = edit.at + edit.inserted.len() + text.len() - after.len();
+            return Some((text, cursor));
+        }
+        let typed: Vec<bool> = edit
+            .before
+            .chars()
+            .zip(&self.pattern)
+            .map(|(_, &slot)| is_placeholder(slot))
+            .collect();
+        let chars: Vec<char> = edit.before.chars().collect();
+        let mut start = edit.before[..edit.at].chars().count();
+        let mut end = start + edit.removed.chars().count();
+        if edit.inserted.is_empty() && !typed[start..end].contains(&true) {
+            if edit.backward {
+                start = typed[..start].iter().rposition(|&t| t).unwrap_or(start);
+            } else if let Some(idx) = typed[end..].iter().position(|&t| t) {
+                end += idx + 1;
+            }
+        }
+        let kept = |range: Range<usize>| {
+            chars[range.clone()]
+                .iter()
+                .zip(&typed[range])
+                .filter(|(_, &t)| t)
+                .map(|(&ch, _)| ch)
+                .collect::<Vec<_>>()
+        };
+        let head = kept(0..start);
+        let cursor_chars = head.len() + edit.inserted.chars().count();
+        let raw = head
+            .into_iter()
+            .chain(edit.inserted.chars())
+            .chain(kept(end..chars.len()));
+        let (text, ends) = self.fit(raw)?;
+        let cursor = cursor_chars.checked_sub(1).map_or(0, |idx| ends[idx]);
+        Some((text, cursor))
+    }
+
+    /// Fit `chars` to the mask, also returning where each one ends in the
+    /// result.
+    fn fit(&self, chars: impl Iterator<Item = char>) -> Option<(String, Vec<usize>)> {
+        let mut out = String::new();
+        let mut ends = Vec::new();
+        let mut slots = self.pattern.iter().copied();
+        for ch in chars {
+            loop {
+                let slot = slots.next()?;
+                if slot_accepts(slot, ch) {
+                    out.push(ch);
+                    break;
+                }
+                if is_placeholder(slot) {
+                    return None;
+                }
+                // A literal the user did not type: insert it and retry.
+                out.push(slot);
+            }
+            ends.push(out.len());
+        }
+        Some((out, ends))
+    }
+
+    /// Whether `text` fills every slot.
+    pub fn is_complete(&self, text: &str) -> bool {
+        text.chars().count() == self.pattern.len() && self.apply(text).as_deref() == Some(text)
+    }
+}
+
+/// One edit to constrained text: `removed` at byte `at` of `before` is
+/// replaced by `inserted`.
+#[derive(Debug, Clone, Copy)]
+pub struct TextEdit<'a> {
+    pub before: &'a str,
+    pub at: usize,
+    pub removed: &'a str,
+    pub inserted: &'a str,
+    /// Whether the cursor sat after the removed text, as with Backspace.
+    pub backward: bool,
+}
+
+impl TextEdit<'_> {
+    /// The text once the edit is made.
+    pub fn after(&self) -> String {
+        let mut text = self.before.to_string();
+        text.replace_range(self.at..self.at + self.removed.len(), self.inserted);
+        text
+    }
+}
+
+fn is_placeholder(slot: char) -> bool {
+    matches!(slot, '#' | 'A' | '*')
+}
+
+fn slot_accepts(slot: char, ch: char) -> bool {
+    match slot {
+        '#' => ch.is_ascii_digit(),
+        'A' => ch.is_alphabetic(),
+        '*' => true,
+        literal => literal == ch,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum NumberRange {
+    Integer(RangeInclusive<i64>),
+    Decimal(RangeInclusive<f64>),
+}
+
+impl NumberRange {
+    fn allows_negative(&self) -> bool {
+        match self {
+            Self::Integer(range) => *range.start() < 0,
+            Self::Decimal(range) => *range.start() < 0.0,
+        }
+    }
+
+    /// Whether `text` could still become a number of this kind.
+    fn accepts_partial(&self, text: &str) -> bool {
+        let digits = match text.strip_prefix('-') {
+            Some(rest) if self.allows_negative() => rest,
+            Some(_) => return false,
+            None => text,
+        };
+        let decimal = matches!(self, Self::Decimal(_));
+        let mut seen_point = false;
+        digits.chars().all(|ch| match ch {
+            '0'..='9' => true,
+            '.' if decimal && !seen_point => {
+                seen_point = true;
+                true
+            }
+            _ => false,
+        })
+    }
+
+    fn check(&self, text: &str) -> Result<(), String> {
+        match self {
+            Self::Integer(range) => match text.parse::<i64>() {
+                Ok(value) if range.contains(&value) => Ok(()),
+                Ok(_) => Err(format!(
+                    "must be between {} and {}",
+                    range.start(),
+                    range.end()
+                )),
+                Err(_) => Err("must be a whole number".to_string()),
+            },
+            Self::Decimal(range) => match text.parse::<f64>() {
+                Ok(value) if range.contains(&value) => Ok(()),
+                Ok(_) => Err(format!(
+                    "must be between {} and {}",
+                    range.start(),
+                    range.end()
+                )),
+                Err(_) => Err("must be a number".to_string()),
+            },
+        }
+    }
+}
+
+type CharFilter = Arc<dyn Fn(char) -> bool + Send + Sync>;
+type Validator = fn(&str) -> Result<(), String>;
+
+/// Rules for what a `TextInput` accepts and how its text is judged.
+#[derive(Clone, Default)]
+pub struct InputConstraints {
+    max_chars: Option<usize>,
+    char_filter: Option<CharFilter>,
+    number: Option<NumberRange>,
+    mask: Option<InputMask>,
+    pattern: Option<(Regex, String)>,
+    validators: Vec<Validator>,
+    valid_message: Option<String>,
+}
+
+impl fmt::Debug for InputConstraints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InputConstraints")
+            .field("max_chars", &self.max_chars)
+            .field("char_filter", &self.char_filter.is_some())
+            .field("number", &self.number)
+            .field("mask", &self.mask)
+            .field("pattern", &self.pattern.as_ref().map(|(re, _)| re.as_str()))
+            .field("validators", &self.validators.len())
+            .finish()
+    }
+}
+
+impl InputConstraints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reject edits that would make the text longer than `max` characters.
+    #[must_use]
+    pub fn max_chars(mut self, max: usize) -> Self {
+        self.max_chars = Some(max);
+        self
+    }
+
+    /// Reject edits that insert characters `filter` refuses. Line breaks are
+    /// filtered like any other character.
+    #[must_use]
+    pub fn allow(mut self, filter: impl Fn(char) -> bool + Send + Sync + 'static) -> Self {
+        self.char_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Accept only an optional sign and digits; values outside `range` are
+    /// invalid.
+    #[must_use]
+    pub fn integer(mut self, range: RangeInclusive<i64>) -> Self {
+        self.number = Some(NumberRange::Integer(range));
+        self
+    }
+
+    /// Like [`InputConstraints::integer`] with one decimal point allowed.
+    #[must_use]
+    pub fn decimal(mut self, range: RangeInclusive<f64>) -> Self {
+        self.number = Some(NumberRange::Decimal(range));
+        self
+    }
+
+    /// Keep the text in the shape of `mask`; it is invalid until complete.
+    #[must_use]
+    pub fn mask(mut self, mask: InputMask) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// Mark text that `regex` does not match as invalid with `message`.
+    #[must_use]
+    pub fn pattern(mut self, regex: Regex, message: impl Into<String>) -> Self {
+        self.pattern = Some((regex, message.into()));
+        self
+    }
+
+    /// Add a check in the style of `SimpleTextStep`'s validator.
+    #[must_use]
+    pub fn validator(mut self, validator: Validator) -> Self {
+        self.validators.push(validator);
+        self
+    }
+
+    /// Message shown when non-empty text passes every check.
+    #[must_use]
+    pub fn valid_message(mut self, message: impl Into<String>) -> Self {
+        self.valid_message = Some(message.into());
+        self
+    }
+
+    /// The text `edit` should produce and the cursor after it, or `None` to
+    /// reject the edit.
+    pub fn admit(&self, edit: &TextEdit<'_>) -> Option<(String, usize)> {
+        let (text, cursor) = match &self.mask {
+            Some(mask) => mask.edit(edit)?,
+            None => (edit.after(), edit.at + edit.inserted.len()),
+        };
+        if self.max_chars.is_some_and(|max| text.chars().count() > max) {
+            return None;
+        }
+        if let Some(filter) = &self.char_filter {
+            if !text.chars().all(|ch| filter(ch)) {
+                return None;
+            }
+        }
+        if let Some(number) = &self.number {
+            if !number.accepts_partial(&text) {
+                return None;
+            }
+        }
+        Some((text, cursor))
+    }
+
+    /// Judge `text`; empty text is [`ValidationState::Idle`].
+    pub fn validate(&self, text: &str) -> ValidationState {
+        if text.is_empty() {
+            return ValidationState::Idle;
+        }
+        match self.check(text) {
+            Ok(()) => ValidationState::Valid(self.valid_message.clone()),
+            Err(message) => ValidationState::Invalid(message),
+        }
+    }
+
+    /// The first failed check, for submit-time validation.
+    pub fn check(&self, text: &str) -> Result<(), String> {
+        if let Some(mask) = &self.mask {
+            if !mask.is_complete(text) {
+                return Err(format!("expected {}", mask.pattern()));
+            }
+        }
+        if let Some(number) = &self.number {
+            number.check(text)?;
+        }
+        if let Some((regex, message)) = &self.pattern {
+            if !regex.is_match(text) {
+                return Err(message.clone());
+            }
+        }
+        self.validators
+            .iter()
+            .try_for_each(|validator| validator(text))
+    }
+}
@@ -0,0 +1,717 @@
+//! Vim-style modal editing on top of `TextInput`.
+//!
+//! A [`VimLayer`] sits in front of an input and interprets keys by mode:
+//!
+//! - insert: keys go to the input as usual; esc returns to normal mode.
+//! - normal: motions `h l w b e 0 ^ $`, operators `d c y` (with a motion,
+//!   doubled for whole lines, or as `D C Y`), `x`, `p`/`P`, `i a I A o O`,
+//!   `u`/ctrl+r, counts such as `3w` or `d2w`, and `.` to repeat the last
+//!   change.
+//! - visual: motions extend a character selection that `d`/`x`, `c` and
+//!   `y` act on.
+//!
+//! The layer starts in insert mode so the prompt behaves normally until esc
+//! is pressed. Show the mode with
+//! `InputBox::prompt_override(Some(layer.mode().label()))`. Enter is never
+//! consumed, so the caller decides whether it submits.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use zeroize::Zeroize;
+
+use super::text_input::TextInput;
+use crate::keymap::{self, Keymap};
+use crate::layout::text::{next_grapheme_boundary, prev_grapheme_boundary};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VimMode {
+    Normal,
+    #[default]
+    Insert,
+    Visual,
+}
+
+impl VimMode {
+    /// Short label for a prompt or status line.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Normal => "NORMAL",
+            Self::Insert => "INSERT",
+            Self::Visual => "VISUAL",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+impl Operator {
+    fn from_char(ch: char) -> Option<Self> {
+        match ch {
+            'd' => Some(Self::Delete),
+            'c' => Some(Self::Change),
+            'y' => Some(Self::Yank),
+            _ => None,
+        }
+    }
+
+    fn key(self) -> char {
+        match self {
+            Self::Delete => 'd',
+            Self::Change => 'c',
+            Self::Yank => 'y',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Motion {
+    Left,
+    Right,
+    WordForward,
+    WordBackward,
+    WordEnd,
+    LineStart,
+    FirstNonBlank,
+    LineEnd,
+    /// Whole lines, from a doubled operator such as `dd`.
+    Line,
+}
+
+impl Motion {
+    fn from_key(key: &KeyEvent) -> Option<Self> {
+        Some(match key.code {
+            KeyCode::Char('h') | KeyCode::Left | KeyCode::Backspace => Self::Left,
+            KeyCode::Char('l') | KeyCode::Right => Self::Right,
+            KeyCode::Char('w') => Self::WordForward,
+            KeyCode::Char('b') => Self::WordBackward,
+            KeyCode::Char('e') => Self::WordEnd,
+            KeyCode::Char('0') | KeyCode::Home => Self::LineStart,
+            KeyCode::Char('^') => Self::FirstNonBlank,
+            KeyCode::Char('$') | KeyCode::End => Self::LineEnd,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InsertAt {
+    Cursor,
+    After,
+    FirstNonBlank,
+    LineEnd,
+    LineBelow,
+    LineAbove,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Operate(Operator, Motion),
+    DeleteChar,
+    Paste { before: bool },
+    Insert(InsertAt),
+}
+
+/// The last change, replayed by `.`.
+#[derive(Debug, Clone)]
+struct Change {
+    command: Command,
+    count: usize,
+    /// Keys typed in the insert mode the change ended in.
+    inserted: Vec<KeyEvent>,
+}
+
+/// Modal key handling for one `TextInput`.
+#[derive(Debug, Clone, Default)]
+pub struct VimLayer {
+    mode: VimMode,
+    count: Option<usize>,
+    pending: Option<(Operator, Option<usize>)>,
+    register: String,
+    last_change: Option<Change>,
+    /// The change being typed in insert mode.
+    recording: Option<Change>,
+    visual_anchor: usize,
+    visual_cursor: usize,
+    /// Passed to the input for insert-mode keys; the active keymap when
+    /// `None`.
+    keymap: Option<Arc<Keymap>>,
+}
+
+impl Drop for VimLayer {
+    fn drop(&mut self) {
+        self.register.zeroize();
+    }
+}
+
+/// Text for the register, or `None` for secret inputs, whose text is never
+/// copied out (as with `TextInput::copy_selection`).
+fn yank(input: &TextInput, text: impl FnOnce(&str) -> String) -> Option<String> {
+    (!input.is_secret()).then(|| text(input.text()))
+}
+
+impl VimLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Type insert-mode keys with `keymap` instead of the active one.
+    #[must_use]
+    pub fn with_keymap(mut self, keymap: Arc<Keymap>) -> Self {
+        self.keymap = Some(keymap);
+        self
+    }
+
+    fn keymap(&self) -> Arc<Keymap> {
+        self.keymap.clone().unwrap_or_else(keymap::active)
+    }
+
+    pub fn mode(&self) -> VimMode {
+        self.mode
+    }
+
+    /// Text last deleted or yanked. Whole-line text ends with `\n`.
+    pub fn register(&self) -> &str {
+        &self.register
+    }
+
+    /// Whether a count or operator is waiting for more keys.
+    pub fn is_pending(&self) -> bool {
+        self.count.is_some() || self.pending.is_some()
+    }
+
+    /// Switch modes from outside, e.g. to start a prompt in normal mode.
+    pub fn set_mode(&mut self, input: &mut TextInput, mode: VimMode) {
+        match mode {
+            VimMode::Normal => self.enter_normal(input),
+            VimMode::Insert => {
+                input.clear_selection();
+                self.mode = VimMode::Insert;
+            }
+            VimMode::Visual => self.enter_visual(input),
+        }
+    }
+
+    /// Interpret `key` for `input`. Returns false for keys the layer leaves
+    /// to the caller: enter, and unbound keys in normal and visual mode.
+    pub fn handle_key(&mut self, input: &mut TextInput, key: KeyEvent) -> bool {
+        if key.code == KeyCode::Enter {
+            return false;
+        }
+        match self.mode {
+            VimMode::Insert => self.insert_key(input, key),
+            VimMode::Normal => self.normal_key(input, key),
+            VimMode::Visual => self.visual_key(input, key),
+        }
+    }
+
+    fn insert_key(&mut self, input: &mut TextInput, key: KeyEvent) -> bool {
+        if key.code == KeyCode::Esc {
+            if let Some(change) = self.recording.take() {
+                self.last_change = Some(change);
+            }
+            self.enter_normal(input);
+            input.end_edit_group();
+            return true;
+        }
+        if let Some(change) = &mut self.recording {
+            change.inserted.push(key);
+        }
+        input.handle_key_with(&self.keymap(), key);
+        true
+    }
+
+    fn normal_key(&mut self, input: &mut TextInput, key: KeyEvent) -> bool {
+        if key.code == KeyCode::Esc {
+            self.count = None;
+            self.pending = None;
+            return true;
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.count = None;
+            self.pending = None;
+            if key.code == KeyCode::Char('r') {
+                input.redo();
+                return true;
+            }
+            return false;
+        }
+        if let KeyCode::Char(digit @ '0'..='9') = key.code {
+            if digit != '0' || self.count.is_some() {
+                let value = digit.to_digit(10).unwrap_or_default() as usize;
+                self.count = Some(self.count.unwrap_or(0).saturating_mul(10) + value);
+                return true;
+            }
+        }
+        let count = self.count.take();
+
+        if let Some((operator, op_count)) = self.pending.take() {
+            let total = op_count.unwrap_or(1) * count.unwrap_or(1);
+            let motion = match key.code {
+                KeyCode::Char(ch) if ch == operator.key() => Some(Motion::Line),
+                _ => Motion::from_key(&key),
+            };
+            // Anything else cancels the operator, as in vim.
+            if let Some(motion) = motion {
+                self.execute(input, Command::Operate(operator, motion), total);
+            }
+            return true;
+        }
+
+        if let Some(motion) = Motion::from_key(&key) {
+            let text = input.text();
+            let target = motion_target(text, input.cursor(), motion, count.unwrap_or(1));
+            input.set_cursor_byte_offset(target);
+            clamp_normal(input);
+            return true;
+        }
+
+        let KeyCode::Char(ch) = key.code else {
+            return false;
+        };
+        let command = match ch {
+            'd' | 'c' | 'y' => {
+                self.pending = Operator::from_char(ch).map(|operator| (operator, count));
+                return true;
+            }
+            'D' => Command::Operate(Operator::Delete, Motion::LineEnd),
+            'C' => Command::Operate(Operator::Change, Motion::LineEnd),
+            'Y' => Command::Operate(Operator::Yank, Motion::Line),
+            'x' => Command::DeleteChar,
+            'p' => Command::Paste { before: false },
+            'P' => Command::Paste { before: true },
+            'i' => Command::Insert(InsertAt::Cursor),
+            'a' => Command::Insert(InsertAt::After),
+            'I' => Command::Insert(InsertAt::FirstNonBlank),
+            'A' => Command::Insert(InsertAt::LineEnd),
+            'o' => Command::Insert(InsertAt::LineBelow),
+            'O' => Command::Insert(InsertAt::LineAbove),
+            'v' => {
+                self.enter_visual(input);
+                return true;
+            }
+            'u' => {
+                for _ in 0..count.unwrap_or(1) {
+                    input.undo();
+                }
+                clamp_normal(input);
+                return true;
+            }
+            '.' => {
+                self.repeat(input, count);
+                return true;
+            }
+            _ => return false,
+        };
+        self.execute(input, command, count.unwrap_or(1));
+        true
+    }
+
+    fn visual_key(&mut self, input: &mut TextInput, key: KeyEvent) -> bool {
+        if key.code == KeyCode::Esc || key.code == KeyCode::Char('v') {
+            self.count = None;
+            self.enter_normal(input);
+            return true;
+        }
+        if let KeyCode::Char(digit @ '0'..='9') = key.code {
+            if digit != '0' || self.count.is_some() {
+                let value = digit.to_digit(10).unwrap_or_default() as usize;
+                self.count = Some(self.count.unwrap_or(0).saturating_mul(10) + value);
+                return true;
+            }
+        }
+        let count = self.count.take().unwrap_or(1);
+        if let Some(motion) = Motion::from_key(&key) {
+            self.visual_cursor = motion_target(input.text(), self.visual_cursor, motion, count);
+            self.select_visual(input);
+            return true;
+        }
+        let operator = match key.code {
+            KeyCode::Char('d' | 'x') => Operator::Delete,
+            KeyCode::Char('c') => Operator::Change,
+            KeyCode::Char('y') => Operator::Yank,
+            _ => return false,
+        };
+        let range = self.visual_range(input.text());
+        let yanked = yank(input, |text| text[range.clone()].to_string());
+        input.begin_edit_group();
+        self.apply_operator(input, operator, range, yanked);
+        if self.mode != VimMode::Insert {
+            input.end_edit_group();
+        }
+        true
+    }
+
+    fn execute(&mut self, input: &mut TextInput, command: Command, count: usize) {
+        // The change, and any text typed after it, undo as one step.
+        input.begin_edit_group();
+        let change = Change {
+            command,
+            count,
+            inserted: Vec::new(),
+        };
+        match command {
+            Command::Operate(operator, motion) => {
+                let text = input.text();
+                let cursor = input.cursor();
+                if motion == Motion::Line {
+                    let (range, lines) = line_range(text, cursor, count, operator);
+                    let yanked = yank(input, |text| format!("{}\n", &text[lines]));
+                    self.apply_operator(input, operator, range, yanked);
+                    if self.mode == VimMode::Normal {
+                        let text = input.text();
+                        let target = first_non_blank(text, input.cursor().min(text.len()));
+                        input.set_cursor_byte_offset(target);
+                        clamp_normal(input);
+                    }
+                } else {
+                    let range = operator_range(text, cursor, motion, count, operator);
+                    let yanked = yank(input, |text| text[range.clone()].to_string());
+                    self.apply_operator(input, operator, range, yanked);
+                }
+            }
+            Command::DeleteChar => {
+                let text = input.text();
+                let cursor = input.cursor();
+                let line_end = line_bounds(text, cursor).1;
+                let end = (0..count).fold(cursor, |pos, _| {
+                    next_grapheme_boundary(text, pos).min(line_end)
+                });
+                let yanked = yank(input, |text| text[cursor..end].to_string());
+                self.apply_operator(input, Operator::Delete, cursor..end, yanked);
+            }
+            Command::Paste { before } => self.paste(input, before, count),
+            Command::Insert(at) => self.insert_at(input, at),
+        }
+        if self.mode == VimMode::Insert {
+            self.recording = Some(change);
+            return;
+        }
+        input.end_edit_group();
+        if !matches!(command, Command::Operate(Operator::Yank, _)) {
+            self.last_change = Some(change);
+        }
+    }
+
+    fn repeat(&mut self, input: &mut TextInput, count: Option<usize>) {
+        let Some(change) = self.last_change.clone() else {
+            return;
+        };
+        let count = count.unwrap_or(change.count);
+        self.execute(input, change.command, count);
+        if self.mode == VimMode::Insert {
+            let keymap = self.keymap();
+            for key in &change.inserted {
+                input.handle_key_with(&keymap, *key);
+            }
+            self.recording = None;
+            self.enter_normal(input);
+            input.end_edit_group();
+        }
+        self.last_change = Some(Change { count, ..change });
+    }
+
+    fn apply_operator(
+        &mut self,
+        input: &mut TextInput,
+        operator: Operator,
+        range: Range<usize>,
+        yanked: Option<String>,
+    ) {
+        let start = range.start;
+        if let Some(yanked) = yanked.filter(|yanked| !yanked.is_empty()) {
+            self.register.zeroize();
+            self.register = yanked;
+        }
+        match operator {
+            Operator::Yank => {
+                input.set_cursor_byte_offset(start);
+                self.mode = VimMode::Normal;
+                clamp_normal(input);
+                return;
+            }
+            Operator::Delete | Operator::Change => {
+                if !range.is_empty() {
+                    input.set_selection(range);
+                    input.delete_selection();
+                }
+                input.set_cursor_byte_offset(start);
+            }
+        }
+        if operator == Operator::Change {
+            self.mode = VimMode::Insert;
+        } else {
+            self.mode = VimMode::Normal;
+            clamp_normal(input);
+        }
+    }
+
+    fn paste(&mut self, input: &mut TextInput, before: bool, count: usize) {
+        if self.register.is_empty() {
+            return;
+        }
+        let text = input.text();
+        let cursor = input.cursor();
+        let (line_start, line_end) = line_bounds(text, cursor);
+        if let Some(lines) = self.register.strip_suffix('\n') {
+            let block = vec![lines; count].join("\n");
+            let (at, inserted, cursor_after) = if before {
+                (line_start, format!("{block}\n"), line_start)
+            } else {
+                (line_end, format!("\n{block}"), line_end + 1)
+            };
+            input.set_cursor_byte_offset(at);
+            input.insert_str(&inserted);
+            input.set_cursor_byte_offset(cursor_after);
+        } else {
+            let at = if before || line_start == line_end {
+                cursor
+            } else {
+                next_grapheme_boundary(text, cursor).min(line_end)
+            };
+            let inserted = self.register.repeat(count);
+            input.set_cursor_byte_offset(at);
+            input.insert_str(&inserted);
+            let end = at + inserted.len();
+            input.set_cursor_byte_offset(prev_grapheme_boundary(input.text(), end).max(at));
+        }
+        self.mode = VimMode::Normal;
+        clamp_normal(input);
+    }
+
+    fn insert_at(&mut self, input: &mut TextInput, at: InsertAt) {
+        let text = input.text();
+        let cursor = input.cursor();
+        let (line_start, line_end) = line_bounds(text, cursor);
+        match at {
+            InsertAt::Cursor => {}
+            InsertAt::After => {
+                input.set_cursor_byte_offset(next_grapheme_boundary(text, cursor).min(line_end))
+            }
+            InsertAt::FirstNonBlank => input.set_cursor_byte_offset(first_non_blank(text, cursor)),
+            InsertAt::LineEnd => input.set_cursor_byte_offset(line_end),
+            InsertAt::LineBelow => {
+                input.set_cursor_byte_offset(line_end);
+                input.insert_char('\n');
+            }
+            InsertAt::LineAbove => {
+                input.set_cursor_byte_offset(line_start);
+                input.insert_char('\n');
+                input.set_cursor_byte_offset(line_start);
+            }
+        }
+        input.clear_selection();
+        self.mode = VimMode::Insert;
+    }
+
+    fn enter_normal(&mut self, input: &mut TextInput) {
+        let was_insert = self.mode == VimMode::Insert;
+        self.mode = VimMode::Normal;
+        self.pending = None;
+        input.clear_selection();
+        if was_insert {
+            // Leaving insert mode steps back onto the last typed character.
+            let (line_start, _) = line_bounds(input.text(), input.cursor());
+            if input.cursor() > line_start {
+                input.set_cursor_byte_offset(prev_grapheme_boundary(input.text(), input.cursor()));
+            }
+        }
+        clamp_normal(input);
+    }
+
+    fn enter_visual(&mut self, input: &mut TextInput) {
+        self.mode = VimMode::Visual;
+        self.visual_anchor = input.cursor();
+        self.visual_cursor = input.cursor();
+        self.select_visual(input);
+    }
+
+    fn visual_range(&self, text: &str) -> Range<usize> {
+        let start = self.visual_anchor.min(self.visual_cursor);
+        let last = self.visual_anchor.max(self.visual_cursor);
+        start..next_grapheme_boundary(text, last)
+    }
+
+    fn select_visual(&self, input: &mut TextInput) {
+        let range = self.visual_range(input.text());
+        input.set_selection(range);
+    }
+}
+
+/// Keep the normal-mode cursor on a character rather than past the end of
+/// a non-empty line.
+fn clamp_normal(input: &mut TextInput) {
+    let text = input.text();
+    let cursor = input.cursor();
+    let (line_start, line_end) = line_bounds(text, cursor);
+    if cursor >= line_end && line_end > line_start {
+        input.set_cursor_byte_offset(prev_grapheme_boundary(text, line_end));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Blank,
+    Word,
+    Punct,
+}
+
+fn class(ch: char) -> CharClass {
+    if ch.is_whitespace() {
+        CharClass::Blank
+    } else if ch.is_alphanumeric() || ch == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punct
+    }
+}
+
+fn char_at(text: &str, pos: usize) -> Option<char> {
+    text.get(pos..)?.chars().next()
+}
+
+/// Start and end (before the `\n`) of the line containing `pos`.
+fn line_bounds(text: &str, pos: usize) -> (usize, usize) {
+    let pos = pos.min(text.len());
+    let start = text[..pos].rfind('\n').map_or(0, |idx| idx + 1);
+    let end = text[pos..].find('\n').map_or(text.len(), |idx| pos + idx);
+    (start, end)
+}
+
+fn first_non_blank(text: &str, pos: usize) -> usize {
+    let (start, end) = line_bounds(text, pos);
+    text[start..end]
+        .char_indices()
+        .find(|(_, ch)| !ch.is_whitespace())
+        .map_or(end, |(idx, _)| start + idx)
+}
+
+fn word_forward(text: &str, pos: usize) -> usize {
+    let mut pos = pos;
+    if let Some(ch) = char_at(text, pos) {
+        let start_class = class(ch);
+        while char_at(text, pos).is_some_and(|ch| class(ch) == start_class) {
+            pos = next_grapheme_boundary(text, pos);
+        }
+    }
+    while char_at(text, pos).is_some_and(|ch| class(ch) == CharClass::Blank) {
+        pos = next_grapheme_boundary(text, pos);
+    }
+    pos
+}
+
+fn word_end(text: &str, pos: usize) -> usize {
+    let mut pos = next_grapheme_boundary(text, pos);
+    while char_at(text, pos).is_some_and(|ch| class(ch) == CharClass::Blank) {
+        pos = next_grapheme_boundary(text, pos);
+    }
+    let Some(ch) = char_at(text, pos) else {
+        return prev_grapheme_boundary(text, text.len());
+    };
+    let run = class(ch);
+    while char_at(text, next_grapheme_boundary(text, pos)).is_some_and(|ch| class(ch) == run) {
+        pos = next_grapheme_boundary(text, pos);
+    }
+    pos
+}
+
+fn word_backward(text: &str, pos: usize) -> usize {
+    let mut pos = pos;
+    while pos > 0
+        && char_at(text, prev_grapheme_boundary(text, pos))
+            .is_some_and(|ch| class(ch) == CharClass::Blank)
+    {
+        pos = prev_grapheme_boundary(text, pos);
+    }
+    if pos == 0 {
+        return 0;
+    }
+    let run = char_at(text, prev_grapheme_boundary(text, pos)).map_or(CharClass::Blank, class);
+    while pos > 0
+        && char_at(text, prev_grapheme_boundary(text, pos)).is_some_and(|ch| class(ch) == run)
+    {
+        pos = prev_grapheme_boundary(text, pos);
+    }
+    pos
+}
+
+fn motion_target(text: &str, pos: usize, motion: Motion, count: usize) -> usize {
+    let (line_start, line_end) = line_bounds(text, pos);
+    let repeat = |step: fn(&str, usize) -> usize| (0..count).fold(pos, |pos, _| step(text, pos));
+    match motion {
+        Motion::Left => (0..count).fold(pos, |pos, _| {
+            prev_grapheme_boundary(text, pos).max(line_start)
+        }),
+        Motion::Right => (0..count).fold(pos, |pos, _| {
+            next_grapheme_boundary(text, pos).min(line_end)
+        }),
+        Motion::WordForward => repeat(word_forward),
+        Motion::WordBackward => repeat(word_backward),
+        Motion::WordEnd => repeat(word_end),
+        Motion::LineStart => line_start,
+        Motion::FirstNonBlank => first_non_blank(text, pos),
+        Motion::LineEnd | Motion::Line => line_end,
+    }
+}
+
+/// Text an operator with a character motion covers.
+fn operator_range(
+    text: &str,
+    pos: usize,
+    motion: Motion,
+    count: usize,
+    operator: Operator,
+) -> Range<usize> {
+    let on_blank = char_at(text, pos).is_none_or(|ch| class(ch) == CharClass::Blank);
+    // `cw` on a word changes to its end, like `ce`, except that the cursor
+    // already on a word's last character counts as being at the first end.
+    if operator == Operator::Change && motion == Motion::WordForward && !on_blank {
+        let run = char_at(text, pos).map(class);
+        let at_end = char_at(text, next_grapheme_boundary(text, pos)).map(class) != run;
+        let first = if at_end { pos } else { word_end(text, pos) };
+        let target = (1..count).fold(first, |pos, _| word_end(text, pos));
+        return pos..next_grapheme_boundary(text, target);
+    }
+    let target = motion_target(text, pos, motion, count);
+    match motion {
+        Motion::WordEnd => pos..next_grapheme_boundary(text, target),
+        Motion::WordForward => {
+            // The last word on a line stops at the line break.
+            let end = text[pos..target].find('\n').map_or(target, |idx| pos + idx);
+            pos..end.max(pos)
+        }
+        _ if target < pos => target..pos,
+        _ => pos..target,
+    }
+}
+
+/// `count` whole lines from the cursor's, for `dd`, `cc` and `yy`: the range
+/// to remove and the lines' text without the final break. Deleting takes a
+/// line break with the lines; changing keeps one empty line.
+fn line_range(
+    text: &str,
+    pos: usize,
+    count: usize,
+    operator: Operator,
+) -> (Range<usize>, Range<usize>) {
+    let (start, mut end) = line_bounds(text, pos);
+    for _ in 1..count {
+        if end >= text.len() {
+            break;
+        }
+        end = line_bounds(text, end + 1).1;
+    }
+    let range = match operator {
+        Operator::Change => start..end,
+        _ if end < text.len() => start..end + 1,
+        _ if start > 0 => start - 1..end,
+        _ => start..end,
+    };
+    (range, start..end)
+}
@@ -0,0 +1,530 @@
+//! Configurable key bindings shared by the kit's interactive components.
+//!
+//! A [`Keymap`] maps key sequences to named actions per context (`picker`,
+//! `text_input`, ...). Components take a keymap through their `_with`
+//! handlers or a `with_keymap`-style option and fall back to the
+//! process-wide active one, so an app can switch to a vim or emacs flavoured
+//! preset, or load user overrides from TOML, without touching component code:
+//!
+//! ```toml
+//! preset = "vim"
+//!
+//! [list]
+//! jump_top = "g g"
+//! select_next = ["j", "down"]
+//!
+//! [text_input]
+//! delete_word_backward = []   # unbind
+//! ```
+//!
+//! Keys that are not bound in a component's context keep their built-in
+//! behaviour (e.g. plain characters are typed into text inputs).
+
+mod chord;
+mod presets;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock};
+
+use ratatui::crossterm::event::KeyEvent;
+
+pub use chord::{KeyChord, KeySequence};
+pub use presets::Preset;
+
+/// Context names consulted by the kit's components.
+pub mod context {
+    /// Editing keys for `TextInput`, including picker search fields.
+    pub const TEXT_INPUT: &str = "text_input";
+    /// `key_to_picker_msg`; consulted before the search field.
+    pub const PICKER: &str = "picker";
+    /// Tab switching in tabbed dialogs.
+    pub const TABS: &str = "tabs";
+    /// Selection lists in tabbed dialogs.
+    pub const LIST: &str = "list";
+    /// Wizard steps such as `SimpleTextStep` and `SummaryStep`.
+    pub const WIZARD: &str = "wizard";
+    /// Program-level keys handled by the runtime.
+    pub const PROGRAM: &str = "program";
+    /// The "?" help overlay.
+    pub const HELP: &str = "help";
+    /// Focus cycling in `FocusTree`.
+    pub const FOCUS: &str = "focus";
+    /// Dismissing the top layer of an `OverlayStack`.
+    pub const OVERLAY: &str = "overlay";
+    /// Prompt history recall in `InputHistory`.
+    pub const HISTORY: &str = "history";
+    /// Keys while an `InputHistory` reverse search is open.
+    pub const REVERSE_SEARCH: &str = "reverse_search";
+    /// Candidate navigation while a `Completer` is showing suggestions.
+    pub const COMPLETION: &str = "completion";
+}
+
+/// Action names understood by the kit's components.
+pub mod action {
+    pub const CONFIRM: &str = "confirm";
+    pub const CLOSE: &str = "close";
+    pub const CANCEL: &str = "cancel";
+    pub const SUBMIT: &str = "submit";
+    pub const PREVIOUS: &str = "previous";
+    pub const NEW_ITEM: &str = "new_item";
+    pub const QUIT: &str = "quit";
+    pub const SHOW_HELP: &str = "show_help";
+    /// Stop the program and return to the shell; see [`ConflictKind::Suspend`].
+    ///
+    /// [`ConflictKind::Suspend`]: super::ConflictKind::Suspend
+    pub const SUSPEND: &str = "suspend";
+
+    pub const SELECT_PREV: &str = "select_prev";
+    pub const SELECT_NEXT: &str = "select_next";
+    pub const PAGE_UP: &str = "page_up";
+    pub const PAGE_DOWN: &str = "page_down";
+    pub const JUMP_TOP: &str = "jump_top";
+    pub const JUMP_BOTTOM: &str = "jump_bottom";
+
+    pub const NEXT_TAB: &str = "next_tab";
+    pub const PREV_TAB: &str = "prev_tab";
+    pub const FOCUS_NEXT: &str = "focus_next";
+    pub const FOCUS_PREV: &str = "focus_prev";
+
+    pub const DELETE_BACKWARD: &str = "delete_backward";
+    pub const DELETE_FORWARD: &str = "delete_forward";
+    pub const DELETE_WORD_BACKWARD: &str = "delete_word_backward";
+    pub const DELETE_WORD_FORWARD: &str = "delete_word_forward";
+    pub const DELETE_TO_LINE_START: &str = "delete_to_line_start";
+    pub const DELETE_TO_LINE_END: &str = "delete_to_line_end";
+    pub const CURSOR_LEFT: &str = "cursor_left";
+    pub const CURSOR_RIGHT: &str = "cursor_right";
+    pub const CURSOR_UP: &str = "cursor_up";
+    pub const CURSOR_DOWN: &str = "cursor_down";
+    pub const WORD_LEFT: &str = "word_left";
+    pub const WORD_RIGHT: &str = "word_right";
+    pub const LINE_START: &str = "line_start";
+    pub const LINE_END: &str = "line_end";
+    pub const UNDO: &str = "undo";
+    pub const REDO: &str = "redo";
+    pub const SELECT_LEFT: &str = "select_left";
+    pub const SELECT_RIGHT: &str = "select_right";
+    pub const SELECT_UP: &str = "select_up";
+    pub const SELECT_DOWN: &str = "select_down";
+    pub const SELECT_WORD_LEFT: &str = "select_word_left";
+    pub const SELECT_WORD_RIGHT: &str = "select_word_right";
+    pub const SELECT_LINE_START: &str = "select_line_start";
+    pub const SELECT_LINE_END: &str = "select_line_end";
+    pub const SELECT_ALL: &str = "select_all";
+    pub const COPY: &str = "copy";
+    pub const CUT: &str = "cut";
+    pub const PASTE: &str = "paste";
+    pub const TOGGLE_REVEAL: &str = "toggle_reveal";
+    pub const HISTORY_PREV: &str = "history_prev";
+    pub const HISTORY_NEXT: &str = "history_next";
+    pub const HISTORY_SEARCH: &str = "history_search";
+    pub const COMPLETE: &str = "complete";
+    pub const ACCEPT_SUGGESTION: &str = "accept_suggestion";
+}
+
+/// Errors raised while parsing bindings or loading a keymap.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum KeymapError {
+    #[error("empty key binding")]
+    EmptyBinding,
+    #[error("unknown key `{0}`")]
+    UnknownKey(String),
+    #[error("unknown modifier `{0}`")]
+    UnknownModifier(String),
+    #[error("unknown keymap preset `{0}`")]
+    UnknownPreset(String),
+    #[error("invalid keymap TOML: {0}")]
+    Toml(String),
+    #[error("`{0}` must be a key string or a list of key strings")]
+    InvalidValue(String),
+    #[error("conflicting key bindings: {}", join_conflicts(.0))]
+    Conflicts(Vec<Conflict>),
+}
+
+/// A key sequence bound to an action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binding {
+    pub keys: KeySequence,
+    pub action: String,
+}
+
+/// Two bindings that cannot both be reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub context: String,
+    pub kind: ConflictKind,
+    pub first: Binding,
+    pub second: Binding,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// The same keys are bound to two different actions.
+    Duplicate,
+    /// `first` is a prefix of `second`, so `second` can never complete.
+    Prefix,
+    /// `first` suspends the program from the `program` context before any
+    /// component sees the key, so `second` in `context` is never reached.
+    Suspend,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ConflictKind::Duplicate => write!(
+                f,
+                "[{}] `{}` is bound to both `{}` and `{}`",
+                self.context, self.first.keys, self.first.action, self.second.action
+            ),
+            ConflictKind::Prefix => write!(
+                f,
+                "[{}] `{}` ({}) shadows `{}` ({})",
+                self.context,
+                self.first.keys,
+                self.first.action,
+                self.second.keys,
+                self.second.action
+            ),
+            ConflictKind::Suspend => write!(
+                f,
+                "[{}] `{}` ({}) is taken by `{}` ({}) in [{}]",
+                self.context,
+                self.second.keys,
+                self.second.action,
+                self.first.keys,
+                self.first.action,
+                context::PROGRAM
+            ),
+        }
+    }
+}
+
+fn join_conflicts(conflicts: &[Conflict]) -> String {
+    conflicts
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Outcome of looking up the keys pressed so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution<'a> {
+    /// The keys complete a binding.
+    Action(&'a str),
+    /// The keys are the start of a longer binding; wait for more.
+    Pending,
+    /// Nothing is bound to these keys.
+    Unbound,
+}
+
+/// Key bindings grouped by context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    contexts: BTreeMap<String, Vec<Binding>>,
+}
+
+impl Default for Keymap {
+    /// The kit's default bindings; see [`Preset::Default`].
+    fn default() -> Self {
+        Preset::Default.keymap()
+    }
+}
+
+impl Keymap {
+    /// A keymap without any bindings.
+    pub fn empty() -> Self {
+        Self {
+            contexts: BTreeMap::new(),
+        }
+    }
+
+    /// Parse `keys` and bind them to `action` in `context`.
+    pub fn bind(&mut self, context: &str, keys: &str, action: &str) -> Result<(), KeymapError> {
+        self.insert(context, keys.parse()?, action);
+        Ok(())
+    }
+
+    /// Bind `keys` to `action`, keeping any other keys bound to the action.
+    pub fn insert(&mut self, context: &str, keys: KeySequence, action: &str) {
+        let bindings = self.contexts.entry(context.to_string()).or_default();
+        if !bindings
+            .iter()
+            .any(|b| b.keys == keys && b.action == action)
+        {
+            bindings.push(Binding {
+                keys,
+                action: action.to_string(),
+            });
+        }
+    }
+
+    /// Builder form of [`Keymap::insert`].
+    #[must_use]
+    pub fn with(mut self, context: &str, keys: KeySequence, action: &str) -> Self {
+        self.insert(context, keys, action);
+        self
+    }
+
+    /// Remove every binding of `action` in `context`.
+    pub fn unbind(&mut self, context: &str, action: &str) {
+        if let Some(bindings) = self.contexts.get_mut(context) {
+            bindings.retain(|b| b.action != action);
+        }
+    }
+
+    /// Replace the keys of `action`, taking them away from any other action in
+    /// the same context. An empty `keys` leaves the action unbound.
+    pub fn remap(&mut self, context: &str, action: &str, keys: Vec<KeySequence>) {
+        let bindings = self.contexts.entry(context.to_string()).or_default();
+        bindings.retain(|b| b.action != action && !keys.contains(&b.keys));
+        for keys in keys {
+            self.insert(context, keys, action);
+        }
+    }
+
+    /// Apply `overrides` on top of this keymap: every action they mention is
+    /// remapped to exactly the keys they give it.
+    pub fn merge(&mut self, overrides: &Keymap) {
+        for (context, bindings) in &overrides.contexts {
+            let mut by_action: Vec<(&str, Vec<KeySequence>)> = Vec::new();
+            for binding in bindings {
+                match by_action.iter_mut().find(|(a, _)| *a == binding.action) {
+                    Some((_, keys)) => keys.push(binding.keys.clone()),
+                    None => by_action.push((&binding.action, vec![binding.keys.clone()])),
+                }
+            }
+            for (action, keys) in by_action {
+                self.remap(context, action, keys);
+            }
+        }
+    }
+
+    pub fn contexts(&self) -> impl Iterator<Item = &str> {
+        self.contexts.keys().map(String::as_str)
+    }
+
+    pub fn bindings(&self, context: &str) -> &[Binding] {
+        self.contexts.get(context).map_or(&[], Vec::as_slice)
+    }
+
+    /// Keys bound to `action` in `context`, in binding order.
+    pub fn keys_for<'a>(
+        &'a self,
+        context: &str,
+        action: &'a str,
+    ) -> impl Iterator<Item = &'a KeySequence> + 'a {
+        self.bindings(context)
+            .iter()
+            .filter(move |b| b.action == action)
+            .map(|b| &b.keys)
+    }
+
+    /// Action bound to the single key press `key`; multi-key sequences are
+    /// ignored. Use [`PendingKeys`] where sequences should be honoured.
+    pub fn action_for(&self, context: &str, key: &KeyEvent) -> Option<&str> {
+        let chord = KeyChord::from_event(key);
+        self.bindings(context)
+            .iter()
+            .find(|b| b.keys.chords() == [chord])
+            .map(|b| b.action.as_str())
+    }
+
+    /// Look up the chords pressed so far. An exact match wins over a longer
+    /// binding that starts with the same keys.
+    pub fn resolve(&self, context: &str, chords: &[KeyChord]) -> Resolution<'_> {
+        let bindings = self.bindings(context);
+        if let Some(binding) = bindings.iter().find(|b| b.keys.chords() == chords) {
+            return Resolution::Action(&binding.action);
+        }
+        if !chords.is_empty() && bindings.iter().any(|b| b.keys.chords().starts_with(chords)) {
+            Resolution::Pending
+        } else {
+            Resolution::Unbound
+        }
+    }
+
+    /// Bindings that shadow each other within a context, and bindings in
+    /// other contexts that the `program` context's suspend keys take first.
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let mut conflicts = Vec::new();
+        for (context, bindings) in &self.contexts {
+            for (idx, first) in bindings.iter().enumerate() {
+                for second in &bindings[idx + 1..] {
+                    let (kind, first, second) = if first.keys == second.keys {
+                        if first.action == second.action {
+                            continue;
+                        }
+                        (ConflictKind::Duplicate, first, second)
+                    } else if first.keys.is_prefix_of(&second.keys) {
+                        (ConflictKind::Prefix, first, second)
+                    } else if second.keys.is_prefix_of(&first.keys) {
+                        (ConflictKind::Prefix, second, first)
+                    } else {
+                        continue;
+                    };
+                    conflicts.push(Conflict {
+                        context: context.clone(),
+                        kind,
+                        first: first.clone(),
+                        second: second.clone(),
+                    });
+                }
+            }
+        }
+        let suspend_keys = self
+            .bindings(context::PROGRAM)
+            .iter()
+            .filter(|b| b.action == action::SUSPEND);
+        for suspend in suspend_keys {
+            for (name, bindings) in &self.contexts {
+                if name == context::PROGRAM {
+                    continue;
+                }
+                for binding in bindings {
+                    if suspend.keys == binding.keys || suspend.keys.is_prefix_of(&binding.keys) {
+                        conflicts.push(Conflict {
+                            context: name.clone(),
+                            kind: ConflictKind::Suspend,
+                            first: suspend.clone(),
+                            second: binding.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Fail with [`KeymapError::Conflicts`] if any bindings shadow each other.
+    pub fn validate(&self) -> Result<(), KeymapError> {
+        let conflicts = self.conflicts();
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(KeymapError::Conflicts(conflicts))
+        }
+    }
+
+    /// Load a keymap from TOML.
+    ///
+    /// Each table is a context whose keys are action names and whose values
+    /// are a key sequence or a list of them. An optional top-level `preset`
+    /// names the base keymap; every action listed is remapped onto it (see
+    /// [`Keymap::remap`]) and an empty list unbinds the action. Without a
+    /// preset only the listed bindings exist. The result is rejected if it
+    /// contains conflicts.
+    pub fn from_toml(source: &str) -> Result<Self, KeymapError> {
+        let table: toml::Table = source
+            .parse()
+            .map_err(|err: toml::de::Error| KeymapError::Toml(err.message().to_string()))?;
+
+        let mut keymap = Self::empty();
+        let mut remaps = Vec::new();
+        for (name, value) in &table {
+            match value {
+                toml::Value::String(preset) if name == "preset" => {
+                    keymap = preset.parse::<Preset>()?.keymap();
+                }
+                toml::Value::Table(actions) => {
+                    for (action, keys) in actions {
+                        let keys = toml_key_list(keys)
+                            .ok_or_else(|| KeymapError::InvalidValue(format!("{name}.{action}")))?;
+                        let keys = keys
+                            .into_iter()
+                            .map(KeySequence::from_str)
+                            .collect::<Result<Vec<_>, _>>()?;
+                        remaps.push((name, action, keys));
+                    }
+                }
+                _ => return Err(KeymapError::InvalidValue(name.clone())),
+            }
+        }
+
+        for (context, action, keys) in remaps {
+            keymap.remap(context, action, keys);
+        }
+        keymap.validate()?;
+        Ok(keymap)
+    }
+}
+
+fn toml_key_list(value: &toml::Value) -> Option<Vec<&str>> {
+    match value {
+        toml::Value::String(keys) => Some(vec![keys.as_str()]),
+        toml::Value::Array(items) => items.iter().map(toml::Value::as_str).collect(),
+        _ => None,
+    }
+}
+
+/// Chords typed so far towards a multi-key binding such as `g g`.
+///
+/// Components that honour sequences keep one of these next to their state.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PendingKeys {
+    chords: Vec<KeyChord>,
+}
+
+impl PendingKeys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn chords(&self) -> &[KeyChord] {
+        &self.chords
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chords.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.chords.clear();
+    }
+
+    /// Feed `key` and resolve it together with the chords before it.
+    ///
+    /// When the key breaks off a pending sequence, the earlier chords are
+    /// dropped and `key` is looked up on its own.
+    pub fn resolve<'k>(
+        &mut self,
+        keymap: &'k Keymap,
+        context: &str,
+        key: &KeyEvent,
+    ) -> Resolution<'k> {
+        let chord = KeyChord::from_event(key);
+        self.chords.push(chord);
+        match keymap.resolve(context, &self.chords) {
+            Resolution::Pending => Resolution::Pending,
+            Resolution::Unbound if self.chords.len() > 1 => {
+                self.chords.clear();
+                self.resolve(keymap, context, key)
+            }
+            resolved => {
+                self.chords.clear();
+                resolved
+            }
+        }
+    }
+}
+
+static ACTIVE: OnceLock<RwLock<Arc<Keymap>>> = OnceLock::new();
+
+fn active_slot() -> &'static RwLock<Arc<Keymap>> {
+    ACTIVE.get_or_init(|| RwLock::new(Arc::new(Keymap::default())))
+}
+
+/// The keymap components consult when given none; [`Keymap::default`]
+/// until replaced.
+pub fn active() -> Arc<Keymap> {
+    Arc::clone(&active_slot().read().unwrap_or_else(PoisonError::into_inner))
+}
+
+/// Replace the process-wide keymap, e.g. after loading user settings.
+pub fn set_active(keymap: Keymap) {
+    *active_slot()
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Arc::new(keymap);
+}
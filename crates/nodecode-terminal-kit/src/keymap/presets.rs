@@ -0,0 +1,196 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::action::*;
+use super::context::*;
+use super::{KeySequence, Keymap, KeymapError};
+
+/// Built-in keymaps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Preset {
+    /// The kit's standard bindings: arrows, enter, esc and tab.
+    #[default]
+    Default,
+    /// Default plus `j`/`k`, `g g`/`G` and `ctrl+d`/`ctrl+u` in lists, and
+    /// `ctrl+n`/`ctrl+p`, `ctrl+j`/`ctrl+k` in pickers.
+    Vim,
+    /// Default plus readline-style editing and `ctrl+n`/`ctrl+p`/`ctrl+g`
+    /// navigation; `ctrl+y` pastes and `alt+_` redoes.
+    Emacs,
+}
+
+const DEFAULT_BINDINGS: &[(&str, &str, &str)] = &[
+    (TEXT_INPUT, DELETE_BACKWARD, "backspace"),
+    (TEXT_INPUT, DELETE_TO_LINE_START, "ctrl+u"),
+    (TEXT_INPUT, DELETE_WORD_BACKWARD, "ctrl+w"),
+    (TEXT_INPUT, DELETE_FORWARD, "ctrl+d"),
+    (TEXT_INPUT, DELETE_FORWARD, "delete"),
+    // ctrl+z suspends; without the enhanced keyboard protocol ctrl+_ arrives
+    // as ctrl+7.
+    (TEXT_INPUT, UNDO, "ctrl+_"),
+    (TEXT_INPUT, UNDO, "ctrl+7"),
+    (TEXT_INPUT, REDO, "ctrl+y"),
+    (TEXT_INPUT, SELECT_LEFT, "shift+left"),
+    (TEXT_INPUT, SELECT_RIGHT, "shift+right"),
+    (TEXT_INPUT, SELECT_UP, "shift+up"),
+    (TEXT_INPUT, SELECT_DOWN, "shift+down"),
+    (TEXT_INPUT, SELECT_WORD_LEFT, "ctrl+shift+left"),
+    (TEXT_INPUT, SELECT_WORD_RIGHT, "ctrl+shift+right"),
+    (TEXT_INPUT, SELECT_LINE_START, "shift+home"),
+    (TEXT_INPUT, SELECT_LINE_END, "shift+end"),
+    // Without the enhanced keyboard protocol ctrl+shift+c arrives as ctrl+c.
+    (TEXT_INPUT, COPY, "alt+c"),
+    (TEXT_INPUT, COPY, "ctrl+insert"),
+    (TEXT_INPUT, CUT, "ctrl+x"),
+    (TEXT_INPUT, PASTE, "ctrl+v"),
+    (TEXT_INPUT, TOGGLE_REVEAL, "alt+r"),
+    (PICKER, CLOSE, "esc"),
+    (PICKER, CONFIRM, "enter"),
+    (PICKER, SELECT_PREV, "up"),
+    (PICKER, SELECT_NEXT, "down"),
+    (PICKER, PAGE_UP, "pageup"),
+    (PICKER, PAGE_DOWN, "pagedown"),
+    (PICKER, JUMP_TOP, "home"),
+    (PICKER, JUMP_BOTTOM, "end"),
+    (TABS, NEXT_TAB, "tab"),
+    (TABS, PREV_TAB, "shift+tab"),
+    (FOCUS, FOCUS_NEXT, "tab"),
+    (FOCUS, FOCUS_PREV, "shift+tab"),
+    (OVERLAY, CLOSE, "esc"),
+    (HISTORY, HISTORY_PREV, "up"),
+    (HISTORY, HISTORY_NEXT, "down"),
+    (HISTORY, HISTORY_SEARCH, "ctrl+r"),
+    (REVERSE_SEARCH, HISTORY_SEARCH, "ctrl+r"),
+    (REVERSE_SEARCH, CONFIRM, "enter"),
+    (REVERSE_SEARCH, CANCEL, "esc"),
+    (COMPLETION, SELECT_PREV, "up"),
+    (COMPLETION, SELECT_NEXT, "down"),
+    (COMPLETION, COMPLETE, "tab"),
+    (COMPLETION, ACCEPT_SUGGESTION, "right"),
+    (COMPLETION, CANCEL, "esc"),
+    (LIST, SELECT_PREV, "up"),
+    (LIST, SELECT_NEXT, "down"),
+    (LIST, CONFIRM, "enter"),
+    (LIST, CONFIRM, "space"),
+    (LIST, NEW_ITEM, "n"),
+    (WIZARD, CANCEL, "esc"),
+    (WIZARD, SUBMIT, "enter"),
+    (WIZARD, PREVIOUS, "left"),
+    (WIZARD, PREVIOUS, "h"),
+    (PROGRAM, QUIT, "esc"),
+    (PROGRAM, QUIT, "q"),
+    (PROGRAM, QUIT, "ctrl+c"),
+    (PROGRAM, SHOW_HELP, "?"),
+    // Raw mode turns off the terminal's own ctrl+z, so the runtime suspends
+    // on this binding instead.
+    (PROGRAM, SUSPEND, "ctrl+z"),
+    (HELP, CLOSE, "esc"),
+    (HELP, CLOSE, "?"),
+    (HELP, SELECT_PREV, "up"),
+    (HELP, SELECT_NEXT, "down"),
+    (HELP, PAGE_UP, "pageup"),
+    (HELP, PAGE_DOWN, "pagedown"),
+    (HELP, JUMP_TOP, "home"),
+    (HELP, JUMP_BOTTOM, "end"),
+];
+
+const VIM_BINDINGS: &[(&str, &str, &str)] = &[
+    (TEXT_INPUT, DELETE_BACKWARD, "ctrl+h"),
+    (PICKER, SELECT_NEXT, "ctrl+n"),
+    (PICKER, SELECT_NEXT, "ctrl+j"),
+    (PICKER, SELECT_PREV, "ctrl+p"),
+    (PICKER, SELECT_PREV, "ctrl+k"),
+    (LIST, SELECT_NEXT, "j"),
+    (LIST, SELECT_PREV, "k"),
+    (LIST, JUMP_TOP, "g g"),
+    (LIST, JUMP_BOTTOM, "G"),
+    (LIST, PAGE_DOWN, "ctrl+d"),
+    (LIST, PAGE_UP, "ctrl+u"),
+    (HELP, SELECT_NEXT, "ctrl+n"),
+    (HELP, SELECT_PREV, "ctrl+p"),
+];
+
+const EMACS_BINDINGS: &[(&str, &str, &str)] = &[
+    (TEXT_INPUT, CURSOR_LEFT, "ctrl+b"),
+    (TEXT_INPUT, CURSOR_RIGHT, "ctrl+f"),
+    (TEXT_INPUT, WORD_LEFT, "alt+b"),
+    (TEXT_INPUT, WORD_RIGHT, "alt+f"),
+    (TEXT_INPUT, LINE_START, "ctrl+a"),
+    (TEXT_INPUT, LINE_END, "ctrl+e"),
+    (TEXT_INPUT, DELETE_TO_LINE_END, "ctrl+k"),
+    (TEXT_INPUT, DELETE_WORD_FORWARD, "alt+d"),
+    (TEXT_INPUT, DELETE_WORD_BACKWARD, "alt+backspace"),
+    (TEXT_INPUT, COPY, "alt+w"),
+    (TEXT_INPUT, PASTE, "ctrl+y"),
+    (TEXT_INPUT, REDO, "alt+_"),
+    (PICKER, CLOSE, "ctrl+g"),
+    (PICKER, SELECT_NEXT, "ctrl+n"),
+    (PICKER, SELECT_PREV, "ctrl+p"),
+    (PICKER, PAGE_DOWN, "ctrl+v"),
+    (PICKER, PAGE_UP, "alt+v"),
+    (PICKER, JUMP_TOP, "alt+<"),
+    (PICKER, JUMP_BOTTOM, "alt+>"),
+    (LIST, SELECT_NEXT, "ctrl+n"),
+    (LIST, SELECT_PREV, "ctrl+p"),
+    (LIST, PAGE_DOWN, "ctrl+v"),
+    (LIST, PAGE_UP, "alt+v"),
+    (LIST, JUMP_TOP, "alt+<"),
+    (LIST, JUMP_BOTTOM, "alt+>"),
+    (WIZARD, CANCEL, "ctrl+g"),
+    (HELP, CLOSE, "ctrl+g"),
+    (OVERLAY, CLOSE, "ctrl+g"),
+    (HISTORY, HISTORY_PREV, "ctrl+p"),
+    (HISTORY, HISTORY_NEXT, "ctrl+n"),
+    (REVERSE_SEARCH, CANCEL, "ctrl+g"),
+    (HELP, SELECT_NEXT, "ctrl+n"),
+    (HELP, SELECT_PREV, "ctrl+p"),
+];
+
+impl Preset {
+    pub const ALL: [Preset; 3] = [Preset::Default, Preset::Vim, Preset::Emacs];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::Default => "default",
+            Preset::Vim => "vim",
+            Preset::Emacs => "emacs",
+        }
+    }
+
+    pub fn keymap(self) -> Keymap {
+        let extra = match self {
+            Preset::Default => &[][..],
+            Preset::Vim => VIM_BINDINGS,
+            Preset::Emacs => EMACS_BINDINGS,
+        };
+        let mut keymap = Keymap::empty();
+        for (context, action, keys) in DEFAULT_BINDINGS.iter().chain(extra) {
+            let keys: KeySequence = keys
+                .parse()
+                .unwrap_or_else(|err| panic!("preset binding `{keys}`: {err}"));
+            // A preset's own keys take over from the defaults.
+            if let Some(bindings) = keymap.contexts.get_mut(*context) {
+                bindings.retain(|b| b.keys != keys);
+            }
+            keymap.insert(context, keys, action);
+        }
+        keymap
+    }
+}
+
+impl FromStr for Preset {
+    type Err = KeymapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|preset| preset.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| KeymapError::UnknownPreset(s.to_string()))
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -2,11 +2,14 @@
 
 //! Pure picker framework utilities shared by design-only consumers.
 
+use std::sync::Arc;
+
 use crate::components::{dialog_shell, text_input::TextInput};
+use crate::keymap::{self, action, context, Keymap, PendingKeys, Resolution};
 use crate::theme::{to_ratatui, Theme};
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
-use ratatui::crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::crossterm::event::{KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::layout::Rect;
 use ratatui::style::{Modifier, Style};
 use ratatui::widgets::{ListItem, Padding};
@@ -155,12 +158,23 @@ pub enum FilterMode<T> {
 /// Picker options controlling behavior.
 pub struct PickerOptions<T> {
     pub filter: FilterMode<T>,
+    /// Keys for this picker; the active keymap when `None`.
+    pub keymap: Option<Arc<Keymap>>,
 }
 
 impl<T> PickerOptions<T> {
     #[must_use]
     pub fn no_section(filter: FilterMode<T>) -> Self {
-        Self { filter }
+        Self {
+            filter,
+            keymap: None,
+        }
+    }
+
+    #[must_use]
+    pub fn keymap(mut self, keymap: Arc<Keymap>) -> Self {
+        self.keymap = Some(keymap);
+        self
     }
 }
 
@@ -173,6 +187,8 @@ pub struct PickerState<T> {
     pub search_input: TextInput,
     pub filtered_indices: Vec<usize>,
     pub list: ListState,
+    /// Keys typed towards a multi-key `picker` binding.
+    pub pending_keys: PendingKeys,
 }
 
 impl<T> Default for PickerState<T> {
@@ -184,6 +200,7 @@ impl<T> Default for PickerState<T> {
             search_input: TextInput::new(),
             filtered_indices: Vec::new(),
             list: ListState::new(),
+            pending_keys: PendingKeys::new(),
         }
     }
 }
@@ -212,6 +229,7 @@ impl<T> PickerState<T> {
         self.is_loading = false;
         self.search_input.clear();
         self.filtered_indices.clear();
+        self.pending_keys.clear();
     }
 
     pub fn update_filter(&mut self, opts: &PickerOptions<T>) {
@@ -356,12 +374,20 @@ pub trait PickerHooks<T, Custom = ()> {
 
 impl<T, Custom> PickerHooks<T, Custom> for () {}
 
+/// Keymap contexts consulted by [`key_to_picker_msg`], for the
+/// `UiComponent::key_contexts` of components built on a picker.
+pub const KEY_CONTEXTS: &[&str] = &[context::PICKER, context::TEXT_INPUT];
+
 /// Translate a key event into a picker message.
+///
+/// Keys are looked up in the `picker` context of the options' keymap (the
+/// active one by default) first; anything unbound there goes to the search
+/// field.
 pub fn key_to_picker_msg<T, H, Custom>(
     state: &mut PickerState<T>,
     key: KeyEvent,
     visible_height: Option<usize>,
-    _opts: &PickerOptions<T>,
+    opts: &PickerOptions<T>,
     hooks: &H,
 ) -> Option<PickerMsg<Custom>>
 where
@@ -377,19 +403,30 @@ where
         return Some(msg);
     }
 
-    match key.code {
-        KeyCode::Esc => return Some(PickerMsg::Close),
-        KeyCode::Enter => return Some(PickerMsg::Confirm),
-        KeyCode::Up => return Some(PickerMsg::SelectPrevious),
-        KeyCode::Down => return Some(PickerMsg::SelectNext),
-        KeyCode::PageUp => return Some(PickerMsg::PageUp(visible_height.unwrap_or(10))),
-        KeyCode::PageDown => return Some(PickerMsg::PageDown(visible_height.unwrap_or(10))),
-        KeyCode::Home => return Some(PickerMsg::JumpTop),
-        KeyCode::End => return Some(PickerMsg::JumpBottom),
-        _ => {}
+    let keymap = opts.keymap.clone().unwrap_or_else(keymap::active);
+    match state.pending_keys.resolve(&keymap, context::PICKER, &key) {
+        Resolution::Pending => return None,
+        Resolution::Action(name) => {
+            let page = visible_height.unwrap_or(10);
+            let msg = match name {
+                action::CLOSE => Some(PickerMsg::Close),
+                action::CONFIRM => Some(PickerMsg::Confirm),
+                action::SELECT_PREV => Some(PickerMsg::SelectPrevious),
+                action::SELECT_NEXT => Some(PickerMsg::SelectNext),
+                action::PAGE_UP => Some(PickerMsg::PageUp(page)),
+                action::PAGE_DOWN => Some(PickerMsg::PageDown(page)),
+                action::JUMP_TOP => Some(PickerMsg::JumpTop),
+                action::JUMP_BOTTOM => Some(PickerMsg::JumpBottom),
+                _ => None,
+            };
+            if msg.is_some() {
+                return msg;
+            }
+        }
+        Resolution::Unbound => {}
     }
 
-    if let Some(after) = state.search_input.handle_search_key(key) {
+    if let Some(after) = state.search_input.handle_search_key_with(&keymap, key) {
         return if after.is_empty() {
             Some(PickerMsg::ClearSearch)
         } else {
@@ -1,63 +1,352 @@
-use unicode_width::UnicodeWidthChar;
+//! Wrapping and cursor geometry for editable text.
+//!
+//! Everything here steps through grapheme clusters, so emoji ZWJ sequences,
+//! flags and combining accents count as one unit, and widths match what
+//! ratatui draws: zero-width clusters share the previous cell and clusters
+//! containing control characters are not drawn.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use unicode_segmentation::{GraphemeCursor, UnicodeSegmentation};
+use unicode_width::UnicodeWidthStr;
+
+/// Columns a grapheme cluster takes up when drawn.
+pub fn grapheme_width(grapheme: &str) -> usize {
+    if grapheme.contains(char::is_control) {
+        0
+    } else {
+        grapheme.width()
+    }
+}
+
+/// The grapheme boundary at or before `offset`.
+pub fn floor_grapheme_boundary(text: &str, offset: usize) -> usize {
+    let offset = floor_char_boundary(text, offset);
+    let mut cursor = GraphemeCursor::new(offset, text.len(), true);
+    if cursor.is_boundary(text, 0).unwrap_or(true) {
+        return offset;
+    }
+    cursor.prev_boundary(text, 0).ok().flatten().unwrap_or(0)
+}
+
+/// The grapheme boundary before `offset`, or 0.
+pub fn prev_grapheme_boundary(text: &str, offset: usize) -> usize {
+    let offset = floor_char_boundary(text, offset);
+    let mut cursor = GraphemeCursor::new(offset, text.len(), true);
+    cursor.prev_boundary(text, 0).ok().flatten().unwrap_or(0)
+}
+
+/// The grapheme boundary after `offset`, or the end of the text.
+pub fn next_grapheme_boundary(text: &str, offset: usize) -> usize {
+    let offset = floor_char_boundary(text, offset);
+    let mut cursor = GraphemeCursor::new(offset, text.len(), true);
+    cursor
+        .next_boundary(text, 0)
+        .ok()
+        .flatten()
+        .unwrap_or(text.len())
+}
+
+fn floor_char_boundary(text: &str, offset: usize) -> usize {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
 
 pub fn wrapped_row_ranges(text: &str, content_width: u16) -> Vec<(usize, usize)> {
+    wrapped_row_ranges_atomic(text, content_width, &[])
+}
+
+/// Like [`wrapped_row_ranges`], but rows only break inside one of the
+/// sorted, disjoint `units` when it is wider than a row.
+pub fn wrapped_row_ranges_atomic(
+    text: &str,
+    content_width: u16,
+    units: &[Range<usize>],
+) -> Vec<(usize, usize)> {
+    let inside_unit = |pos: usize| units.iter().any(|unit| unit.start < pos && pos < unit.end);
     let width = content_width.max(1) as usize;
     let mut rows = Vec::new();
     let mut start = 0usize;
     let mut width_cols = 0usize;
     let mut last_break = 0usize;
-    let len = text.len();
-    let mut idx = 0usize;
-    while idx < len {
-        let Some(ch) = text[idx..].chars().next() else {
-            break;
-        };
-        let ch_len = ch.len_utf8();
-        if ch == '\n' {
+    let mut graphemes = text.grapheme_indices(true).peekable();
+    while let Some(&(idx, grapheme)) = graphemes.peek() {
+        if idx > start && units.iter().any(|unit| unit.start == idx) {
+            last_break = idx;
+        }
+        if grapheme == "\n" || grapheme == "\r\n" {
             rows.push((start, idx));
-            idx += ch_len;
-            start = idx;
+            graphemes.next();
+            start = idx + grapheme.len();
             width_cols = 0;
-            last_break = idx;
+            last_break = start;
             continue;
         }
-        let ch_w = UnicodeWidthChar::width(ch).unwrap_or(0).max(1);
-        if width_cols > 0 && width_cols + ch_w > width {
+        let g_w = grapheme_width(grapheme);
+        if width_cols > 0 && width_cols + g_w > width {
             let wrap_pos = if last_break > start { last_break } else { idx };
             rows.push((start, wrap_pos));
             start = wrap_pos;
-            width_cols = 0;
+            // Clusters after the break point move to the new row.
+            width_cols = visual_column(&text[wrap_pos..idx]);
             continue;
         }
-        width_cols += ch_w;
-        if ch.is_whitespace() || ch == '@' || ch == '/' {
-            last_break = idx + ch_len;
+        width_cols += g_w;
+        let end = idx + grapheme.len();
+        let breakable =
+            grapheme.chars().all(char::is_whitespace) || grapheme == "@" || grapheme == "/";
+        if breakable && !inside_unit(end) {
+            last_break = end;
         }
-        idx += ch_len;
+        graphemes.next();
     }
-    rows.push((start, len));
+    rows.push((start, text.len()));
     rows
 }
 
-pub fn visual_column(slice: &str) -> usize {
-    slice
-        .chars()
-        .map(|c| UnicodeWidthChar::width(c).unwrap_or(0).max(1))
-        .sum()
+/// Running totals over a list of counts, e.g. the bytes or rows of each
+/// line, so finding where an item starts does not sum everything before it.
+///
+/// Updating one count is O(log n); inserting or removing items rebuilds the
+/// totals in O(n).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PrefixSums {
+    values: Vec<usize>,
+    /// Fenwick tree, 1-based; `tree[0]` is unused.
+    tree: Vec<usize>,
 }
 
-pub fn find_visual_row(ranges: &[(usize, usize)], cursor: usize) -> usize {
-    if ranges.is_empty() {
-        return 0;
+impl PrefixSums {
+    pub(crate) fn new(values: Vec<usize>) -> Self {
+        let mut tree = Vec::with_capacity(values.len() + 1);
+        tree.push(0);
+        tree.extend_from_slice(&values);
+        for idx in 1..tree.len() {
+            let parent = idx + lowest_bit(idx);
+            if parent < tree.len() {
+                tree[parent] += tree[idx];
+            }
+        }
+        Self { values, tree }
     }
-    let mut current_idx = 0usize;
-    for (idx, (start, _)) in ranges.iter().enumerate() {
-        if cursor < *start {
-            break;
+
+    pub(crate) fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub(crate) fn get(&self, idx: usize) -> usize {
+        self.values[idx]
+    }
+
+    pub(crate) fn set(&mut self, idx: usize, value: usize) {
+        let old = std::mem::replace(&mut self.values[idx], value);
+        let mut node = idx + 1;
+        while node < self.tree.len() {
+            self.tree[node] = self.tree[node] - old + value;
+            node += lowest_bit(node);
         }
-        current_idx = idx;
     }
-    current_idx.min(ranges.len().saturating_sub(1))
+
+    /// Replace `removed` counts from `first` with `inserted`.
+    pub(crate) fn splice(
+        &mut self,
+        first: usize,
+        removed: usize,
+        inserted: impl IntoIterator<Item = usize>,
+    ) {
+        let inserted: Vec<usize> = inserted.into_iter().collect();
+        if inserted.len() == removed {
+            for (offset, value) in inserted.into_iter().enumerate() {
+                self.set(first + offset, value);
+            }
+            return;
+        }
+        let mut values = std::mem::take(&mut self.values);
+        values.splice(first..first + removed, inserted);
+        *self = Self::new(values);
+    }
+
+    /// Sum of the counts before `idx`.
+    pub(crate) fn prefix(&self, idx: usize) -> usize {
+        let mut node = idx.min(self.values.len());
+        let mut sum = 0;
+        while node > 0 {
+            sum += self.tree[node];
+            node -= lowest_bit(node);
+        }
+        sum
+    }
+
+    pub(crate) fn total(&self) -> usize {
+        self.prefix(self.values.len())
+    }
+
+    /// The item that `target` falls in, i.e. the last `idx` with
+    /// `prefix(idx) <= target`, clamped to the last item.
+    pub(crate) fn find(&self, target: usize) -> usize {
+        let mut idx = 0;
+        let mut rest = target;
+        let mut step = self.tree.len().checked_next_power_of_two().unwrap_or(0) / 2;
+        while step > 0 {
+            let next = idx + step;
+            if next < self.tree.len() && self.tree[next] <= rest {
+                idx = next;
+                rest -= self.tree[next];
+            }
+            step /= 2;
+        }
+        idx.min(self.values.len().saturating_sub(1))
+    }
+}
+
+fn lowest_bit(idx: usize) -> usize {
+    idx & idx.wrapping_neg()
+}
+
+/// Wrapped rows of a text split into lines, kept per line so that an edit
+/// only rewraps the lines it touched.
+///
+/// Lines are supplied by index, each as its text and the atomic units in
+/// it; row offsets are relative to the line until they are joined with one
+/// separator byte between lines. Line starts and row counts are kept as
+/// running totals, so finding a line or a row costs O(log n) however long
+/// the text is.
+#[derive(Debug, Clone, Default)]
+pub struct WrapCache {
+    width: u16,
+    lines: Vec<Option<Vec<(usize, usize)>>>,
+    /// Lines waiting to be wrapped by the next `update`.
+    stale: Vec<usize>,
+    /// Bytes of each line plus its separator.
+    bytes: PrefixSums,
+    /// Rows of each line.
+    rows: PrefixSums,
+}
+
+impl WrapCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop every line, e.g. when the whole text or its rendering changed.
+    pub fn invalidate(&mut self) {
+        self.lines.clear();
+        self.stale.clear();
+    }
+
+    /// `removed` lines from `first` were replaced by `inserted` lines.
+    pub fn splice(&mut self, first: usize, removed: usize, inserted: usize) {
+        if first + removed > self.lines.len() {
+            self.invalidate();
+            return;
+        }
+        self.lines
+            .splice(first..first + removed, (0..inserted).map(|_| None));
+        let end = first + removed;
+        self.stale.retain(|&idx| idx < first || idx >= end);
+        for idx in &mut self.stale {
+            if *idx >= end {
+                *idx = *idx + inserted - removed;
+            }
+        }
+        self.stale.extend(first..first + inserted);
+        self.bytes.splice(first, removed, (0..inserted).map(|_| 0));
+        self.rows.splice(first, removed, (0..inserted).map(|_| 0));
+    }
+
+    /// Wrap the lines that are missing at `content_width`. A different width
+    /// or line count starts over.
+    pub fn update<'a, F>(&mut self, content_width: u16, line_count: usize, line: F)
+    where
+        F: Fn(usize) -> (Cow<'a, str>, Vec<Range<usize>>),
+    {
+        if self.width != content_width || self.lines.len() != line_count {
+            self.width = content_width;
+            let mut bytes = Vec::with_capacity(line_count);
+            let mut rows = Vec::with_capacity(line_count);
+            self.lines = (0..line_count)
+                .map(|idx| {
+                    let (text, units) = line(idx);
+                    let wrapped = wrapped_row_ranges_atomic(&text, content_width, &units);
+                    bytes.push(text.len() + 1);
+                    rows.push(wrapped.len());
+                    Some(wrapped)
+                })
+                .collect();
+            self.stale.clear();
+            self.bytes = PrefixSums::new(bytes);
+            self.rows = PrefixSums::new(rows);
+            return;
+        }
+        for idx in std::mem::take(&mut self.stale) {
+            let (text, units) = line(idx);
+            let wrapped = wrapped_row_ranges_atomic(&text, content_width, &units);
+            self.bytes.set(idx, text.len() + 1);
+            self.rows.set(idx, wrapped.len());
+            self.lines[idx] = Some(wrapped);
+        }
+    }
+
+    /// Rows of line `idx`, relative to its start; empty before `update`.
+    pub fn line_rows(&self, idx: usize) -> &[(usize, usize)] {
+        self.lines
+            .get(idx)
+            .and_then(Option::as_deref)
+            .unwrap_or_default()
+    }
+
+    /// Number of rows above line `idx`.
+    pub fn rows_before(&self, idx: usize) -> usize {
+        self.rows.prefix(idx)
+    }
+
+    /// Byte offset where line `idx` starts in the joined text.
+    pub fn line_start(&self, idx: usize) -> usize {
+        self.bytes.prefix(idx)
+    }
+
+    /// Length of the joined text.
+    pub fn text_len(&self) -> usize {
+        self.bytes.total().saturating_sub(1)
+    }
+
+    pub fn total_rows(&self) -> usize {
+        self.rows.total()
+    }
+
+    /// The line that row `row` belongs to and the row's index within it,
+    /// clamped to the last row.
+    pub fn row_line(&self, row: usize) -> (usize, usize) {
+        let row = row.min(self.total_rows().saturating_sub(1));
+        let line = self.rows.find(row);
+        (line, row - self.rows.prefix(line))
+    }
+
+    /// Every row, as byte ranges of the joined text.
+    pub fn rows(&self) -> Vec<(usize, usize)> {
+        let mut rows = Vec::with_capacity(self.total_rows());
+        let mut start = 0;
+        for (idx, line) in self.lines.iter().enumerate() {
+            let line = line.as_deref().unwrap_or_default();
+            rows.extend(line.iter().map(|(s, e)| (start + s, start + e)));
+            start += self.bytes.get(idx);
+        }
+        rows
+    }
+}
+
+pub fn visual_column(slice: &str) -> usize {
+    slice.graphemes(true).map(grapheme_width).sum()
+}
+
+pub fn find_visual_row(ranges: &[(usize, usize)], cursor: usize) -> usize {
+    ranges
+        .partition_point(|(start, _)| *start <= cursor)
+        .saturating_sub(1)
 }
 
 pub fn cursor_to_byte_offset(lines: &[String], cursor: (usize, usize)) -> usize {
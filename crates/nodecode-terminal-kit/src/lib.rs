@@ -10,12 +10,19 @@
 //! - No networking/server logic.
 //! - No product-specific orchestration.
 
+/// Clipboard backends (OSC 52, system tools) for text input copy/paste.
+pub mod clipboard;
 /// Reusable UI components (dialogs, inputs, lists, overlays).
 pub mod components;
+/// Configurable key bindings, presets and conflict detection.
+pub mod keymap;
 /// Layout helpers and branding/text utilities.
 pub mod layout;
 /// Low-level primitives shared across components.
 pub mod primitives;
+/// Golden snapshot helpers for component tests.
+#[cfg(feature = "testing")]
+pub mod testing;
 /// Theme model, palette, styling, and color helpers.
 pub mod theme;
 /// Generic multi-step wizard building blocks.
@@ -25,11 +32,14 @@ pub mod wizard;
 pub mod prelude {
     /// Low-level prelude for design-system composition.
     pub mod core {
+        pub use crate::clipboard;
         pub use crate::components::{
-            component, dialog_shell, dropdown, help_bar, input_box, key_hints, lines_viewport,
-            list, list_items, overlay_dialog, picker, picker_dialog, search_bar, tabbed_dialog,
-            tabbed_prompt_dialog, text_input,
+            completion, component, dialog_shell, dropdown, focus, help, help_bar, highlight,
+            input_box, input_history, key_hints, lines_viewport, list, list_items, overlay_dialog,
+            overlay_stack, picker, picker_dialog, search_bar, tabbed_dialog, tabbed_prompt_dialog,
+            text_input, validation, vim,
         };
+        pub use crate::keymap;
         pub use crate::layout::{branding, picker_kit, section_stack, text as layout_text};
         pub use crate::primitives::{
             geom, path, rich_text, scrollbar, shimmer, text as primitive_text,
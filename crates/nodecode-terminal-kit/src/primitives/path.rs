@@ -1,5 +1,6 @@
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::Span;
+use std::ops::Range;
 use std::path::PathBuf;
 
 pub trait PathFormatter {
@@ -39,14 +40,64 @@ impl MentionPathFormatter {
         Self { config }
     }
 
-    fn to_relative_path(&self, path: &str) -> String {
-        let base_dir = self
-            .config
+    /// Byte ranges of the mentions in `text`: the config's prefix followed
+    /// by a path at the start of a word, and absolute paths under the base
+    /// directory with any `@` before them. Ranges are sorted and disjoint.
+    pub fn mention_ranges(&self, text: &str) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        let prefix = self.config.prefix.as_str();
+        if !prefix.is_empty() {
+            for (start, _) in text.match_indices(prefix) {
+                let at_word_start = text[..start]
+                    .chars()
+                    .next_back()
+                    .is_none_or(char::is_whitespace);
+                let path_start = start + prefix.len();
+                let end = path_start + Self::path_len(&text[path_start..]);
+                if at_word_start && end > path_start {
+                    ranges.push(start..end);
+                }
+            }
+        }
+
+        let base_dir = self.base_dir();
+        let base_str = base_dir.to_string_lossy();
+        if !base_str.is_empty() {
+            let mut offset = 0;
+            while let Some((before_end, _, path_end)) =
+                self.find_next_abs_segment(&text[offset..], base_str.as_ref())
+            {
+                ranges.push(offset + before_end..offset + path_end);
+                offset += path_end;
+            }
+        }
+
+        ranges.sort_by_key(|range| (range.start, std::cmp::Reverse(range.end)));
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            if merged.last().is_none_or(|last| range.start >= last.end) {
+                merged.push(range);
+            }
+        }
+        merged
+    }
+
+    fn path_len(s: &str) -> usize {
+        s.find(|c: char| c.is_whitespace() || Self::PATH_BOUNDARY_CHARS.contains(c))
+            .unwrap_or(s.len())
+    }
+
+    fn base_dir(&self) -> PathBuf {
+        self.config
             .base_dir
             .as_ref()
             .cloned()
             .or_else(|| std::env::current_dir().ok())
-            .unwrap_or_else(|| PathBuf::from("."));
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    fn to_relative_path(&self, path: &str) -> String {
+        let base_dir = self.base_dir();
 
         let path_buf = PathBuf::from(path);
         if let Ok(relative) = path_buf.strip_prefix(&base_dir) {
@@ -84,11 +135,7 @@ impl MentionPathFormatter {
             pos
         };
 
-        let path_remaining = &s[pos..];
-        let rel_end = path_remaining
-            .find(|c: char| c.is_whitespace() || Self::PATH_BOUNDARY_CHARS.contains(c))
-            .unwrap_or(path_remaining.len());
-        let path_end = pos + rel_end;
+        let path_end = pos + Self::path_len(&s[pos..]);
         Some((before_end, pos, path_end))
     }
 }
@@ -99,13 +146,7 @@ impl PathFormatter for MentionPathFormatter {
     }
 
     fn transform_text_with_style(&self, text: &str, base_style: Style) -> Vec<Span<'static>> {
-        let base_dir = self
-            .config
-            .base_dir
-            .as_ref()
-            .cloned()
-            .or_else(|| std::env::current_dir().ok())
-            .unwrap_or_else(|| PathBuf::from("."));
+        let base_dir = self.base_dir();
 
         let base_str = base_dir.to_string_lossy();
         let mut spans = Vec::new();
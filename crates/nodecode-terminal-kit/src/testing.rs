@@ -0,0 +1,181 @@
+//! Golden snapshot helpers for component tests.
+//!
+//! Components render into a `TestBackend` buffer which is serialised to a
+//! plain-text snapshot: the screen text first, then one line per run of
+//! identically styled cells. Snapshots live next to the tests that own them and
+//! are refreshed by running the tests with `UPDATE_SNAPSHOTS=1`.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use ratatui::backend::TestBackend;
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::style::{Color, Modifier};
+use ratatui::{Frame, Terminal};
+use unicode_width::UnicodeWidthStr;
+
+/// Environment variable that switches snapshot assertions into update mode.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "UPDATE_SNAPSHOTS";
+
+/// Render a single frame of `width` x `height` cells and return its buffer.
+pub fn render_to_buffer(width: u16, height: u16, draw: impl FnOnce(&mut Frame)) -> Buffer {
+    let mut terminal = match Terminal::new(TestBackend::new(width, height)) {
+        Ok(terminal) => terminal,
+        Err(never) => match never {},
+    };
+    let mut draw = Some(draw);
+    let drawn = terminal.draw(|frame| {
+        if let Some(draw) = draw.take() {
+            draw(frame);
+        }
+    });
+    if let Err(never) = drawn {
+        match never {}
+    }
+    terminal.backend().buffer().clone()
+}
+
+/// Serialise a buffer into the snapshot text format.
+///
+/// ```text
+/// size 12x2
+/// │Hello       │
+/// │            │
+/// styles
+/// 0 0..5 fg=#ffffff bg=#000000 mod=BOLD
+/// ```
+///
+/// Every row is framed by `│` so trailing blanks survive editors. Style runs are
+/// listed as `row start..end` (end exclusive) and cells with the default style
+/// are omitted.
+pub fn buffer_to_snapshot(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut out = format!("size {}x{}\n", area.width, area.height);
+
+    for y in area.top()..area.bottom() {
+        out.push('│');
+        let mut hidden = 0usize;
+        for x in area.left()..area.right() {
+            // Cells covered by a preceding wide glyph are not printed.
+            if hidden > 0 {
+                hidden -= 1;
+                continue;
+            }
+            let symbol = buffer[(x, y)].symbol();
+            out.push_str(symbol);
+            hidden = symbol.width().saturating_sub(1);
+        }
+        out.push_str("│\n");
+    }
+
+    out.push_str("styles\n");
+    for y in area.top()..area.bottom() {
+        let mut x = area.left();
+        while x < area.right() {
+            let style = cell_style(&buffer[(x, y)]);
+            let start = x;
+            while x < area.right() && cell_style(&buffer[(x, y)]) == style {
+                x += 1;
+            }
+            if !style.is_empty() {
+                let _ = writeln!(
+                    out,
+                    "{} {}..{} {}",
+                    y - area.top(),
+                    start - area.left(),
+                    x - area.left(),
+                    style
+                );
+            }
+        }
+    }
+    out
+}
+
+/// Compare `actual` with the golden file `dir/name.snap`.
+///
+/// With `UPDATE_SNAPSHOTS` set the golden file is (re)written instead. Panics
+/// with a line diff when the snapshot is missing or differs.
+pub fn assert_snapshot_in(dir: impl AsRef<Path>, name: &str, actual: &str) {
+    let path = dir.as_ref().join(format!("{name}.snap"));
+    if std::env::var_os(UPDATE_SNAPSHOTS_ENV).is_some() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .unwrap_or_else(|err| panic!("create {}: {err}", parent.display()));
+        }
+        fs::write(&path, actual).unwrap_or_else(|err| panic!("write {}: {err}", path.display()));
+        return;
+    }
+
+    let expected = match fs::read_to_string(&path) {
+        Ok(expected) => expected,
+        Err(err) => panic!(
+            "snapshot `{name}` missing at {} ({err}); run with {UPDATE_SNAPSHOTS_ENV}=1 to create it\n\n{actual}",
+            path.display()
+        ),
+    };
+    if expected != actual {
+        panic!(
+            "snapshot `{name}` does not match {}; run with {UPDATE_SNAPSHOTS_ENV}=1 to accept\n\n{}",
+            path.display(),
+            line_diff(&expected, actual)
+        );
+    }
+}
+
+/// Assert a buffer against `tests/snapshots/<name>.snap` in the calling crate.
+#[macro_export]
+macro_rules! assert_buffer_snapshot {
+    ($name:expr, $buffer:expr $(,)?) => {
+        $crate::testing::assert_snapshot_in(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots"),
+            $name,
+            &$crate::testing::buffer_to_snapshot($buffer),
+        )
+    };
+}
+
+fn cell_style(cell: &Cell) -> String {
+    let mut parts = Vec::new();
+    if cell.fg != Color::Reset {
+        parts.push(format!("fg={}", color_name(cell.fg)));
+    }
+    if cell.bg != Color::Reset {
+        parts.push(format!("bg={}", color_name(cell.bg)));
+    }
+    if cell.modifier != Modifier::empty() {
+        let names: Vec<&str> = cell.modifier.iter_names().map(|(name, _)| name).collect();
+        parts.push(format!("mod={}", names.join("|")));
+    }
+    parts.join(" ")
+}
+
+fn color_name(color: Color) -> String {
+    match color {
+        Color::Rgb(r, g, b) => format!("#{r:02x}{g:02x}{b:02x}"),
+        Color::Indexed(idx) => format!("@{idx}"),
+        other => format!("{other:?}"),
+    }
+}
+
+fn line_diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let mut out = String::new();
+    for idx in 0..expected.len().max(actual.len()) {
+        match (expected.get(idx), actual.get(idx)) {
+            (Some(e), Some(a)) if e == a => {}
+            (e, a) => {
+                let _ = writeln!(out, "line {}:", idx + 1);
+                if let Some(e) = e {
+                    let _ = writeln!(out, "- {e}");
+                }
+                if let Some(a) = a {
+                    let _ = writeln!(out, "+ {a}");
+                }
+            }
+        }
+    }
+    out
+}
@@ -6,6 +6,7 @@ use ratatui::Frame;
 use std::fmt::Debug;
 use std::hash::Hash;
 
+use crate::keymap::context;
 use crate::theme::Theme;
 
 /// Trait for items that can be configured via wizards
@@ -49,6 +50,12 @@ pub trait WizardStep<T: WizardItem>: Debug + Send {
     fn content_height(&self) -> u16 {
         3 // Default: 3 lines for input boxes
     }
+
+    /// Keymap contexts consulted by `handle_key`, most specific first; used
+    /// to derive the wizard's help.
+    fn key_contexts(&self) -> &[&str] {
+        &[context::WIZARD]
+    }
 }
 
 /// Action returned by step's handle_key
@@ -127,6 +134,11 @@ impl<T: WizardItem> WizardFlow<T> {
         step.handle_key(key, item)
     }
 
+    /// Keymap contexts of the active step, for help.
+    pub fn key_contexts(&self) -> &[&str] {
+        self.current_step().key_contexts()
+    }
+
     pub fn can_go_back(&self) -> bool {
         self.current_step_idx > 0
     }
@@ -3,7 +3,9 @@
 use super::framework::{WizardFlow, WizardItem};
 use crate::components::list::ListState;
 use crate::components::text_input::TextInput;
+use crate::keymap::Keymap;
 use ratatui::crossterm::event::KeyEvent;
+use std::sync::Arc;
 
 pub struct GenericWizardModel<T: WizardItem> {
     pub items: Vec<T>,
@@ -14,6 +16,9 @@ pub struct GenericWizardModel<T: WizardItem> {
     pub view_mode: ViewMode,
     pub pending_delete: Option<T::Id>,
     pub is_open: bool,
+    /// Keys shown in the footers; the active keymap when `None`. Steps take
+    /// theirs with `with_keymap`.
+    pub keymap: Option<Arc<Keymap>>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -34,6 +39,7 @@ impl<T: WizardItem> Default for GenericWizardModel<T> {
             view_mode: ViewMode::List,
             pending_delete: None,
             is_open: false,
+            keymap: None,
         }
     }
 }
@@ -1,9 +1,11 @@
+use crate::keymap::{self, action, context, Keymap};
 use crate::theme::Theme;
 use crate::wizard::{StepAction, WizardItem, WizardStep};
-use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::crossterm::event::KeyEvent;
 use ratatui::layout::Rect;
 use ratatui::widgets::{Block, Paragraph};
 use ratatui::{text::Text, Frame};
+use std::sync::Arc;
 
 /// Lightweight summary/review step driven by callbacks.
 #[derive(Debug)]
@@ -14,6 +16,7 @@ pub struct SummaryStep<T: WizardItem> {
     lines: fn(&T) -> Vec<ratatui::text::Line<'static>>,
     on_key: Option<fn(KeyEvent, &mut T) -> Option<StepAction>>,
     height: Option<u16>,
+    keymap: Option<Arc<Keymap>>,
 }
 
 impl<T: WizardItem> SummaryStep<T> {
@@ -29,6 +32,7 @@ impl<T: WizardItem> SummaryStep<T> {
             lines,
             on_key: None,
             height: None,
+            keymap: None,
         }
     }
 
@@ -46,6 +50,12 @@ impl<T: WizardItem> SummaryStep<T> {
         self.height = Some(height);
         self
     }
+
+    /// Handle keys with `keymap` instead of the active one.
+    pub fn with_keymap(mut self, keymap: Arc<Keymap>) -> Self {
+        self.keymap = Some(keymap);
+        self
+    }
 }
 
 impl<T: WizardItem> WizardStep<T> for SummaryStep<T> {
@@ -76,10 +86,11 @@ impl<T: WizardItem> WizardStep<T> for SummaryStep<T> {
                 return action;
             }
         }
-        match key.code {
-            KeyCode::Esc => StepAction::Cancel,
-            KeyCode::Left | KeyCode::Char('h') => StepAction::Previous,
-            KeyCode::Enter => StepAction::Save,
+        let keymap = self.keymap.clone().unwrap_or_else(keymap::active);
+        match keymap.action_for(context::WIZARD, &key) {
+            Some(action::CANCEL) => StepAction::Cancel,
+            Some(action::PREVIOUS) => StepAction::Previous,
+            Some(action::SUBMIT) => StepAction::Save,
             _ => StepAction::Continue,
         }
     }
@@ -1,13 +1,16 @@
 use crate::components::input_box::InputBox;
-use crate::components::text_input::TextInput;
+use crate::components::text_input::{SecretMask, TextInput};
+use crate::components::validation::InputConstraints;
+use crate::keymap::{self, action, context, Keymap};
 use crate::theme::{to_ratatui, Theme};
 use crate::wizard::framework::{StepAction, WizardItem, WizardStep};
 use crate::wizard::layout::input_step_layout;
-use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::crossterm::event::KeyEvent;
 use ratatui::layout::Rect;
 use ratatui::style::{Modifier, Style};
 use ratatui::widgets::Paragraph;
 use ratatui::Frame;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct SimpleTextStep<T: WizardItem> {
@@ -21,6 +24,7 @@ pub struct SimpleTextStep<T: WizardItem> {
     input: TextInput,
     validation_error: Option<String>,
     status: Option<fn(&T) -> Option<Result<String, String>>>,
+    keymap: Option<Arc<Keymap>>,
 }
 
 impl<T: WizardItem> SimpleTextStep<T> {
@@ -47,6 +51,7 @@ impl<T: WizardItem> SimpleTextStep<T> {
             input,
             validation_error: None,
             status: None,
+            keymap: None,
         }
     }
 
@@ -54,6 +59,25 @@ impl<T: WizardItem> SimpleTextStep<T> {
         self.status = Some(status);
         self
     }
+
+    /// Mask the value, e.g. for API tokens. `toggle_reveal` (alt+r by
+    /// default) shows it while editing.
+    pub fn with_secret(mut self, mask: SecretMask) -> Self {
+        self.input.set_secret(Some(mask));
+        self
+    }
+
+    /// Handle keys with `keymap` instead of the active one.
+    pub fn with_keymap(mut self, keymap: Arc<Keymap>) -> Self {
+        self.keymap = Some(keymap);
+        self
+    }
+
+    /// Filter edits and validate live; the validator still runs on submit.
+    pub fn with_constraints(mut self, constraints: InputConstraints) -> Self {
+        self.input.set_constraints(Some(constraints));
+        self
+    }
 }
 
 impl<T: WizardItem> WizardStep<T> for SimpleTextStep<T> {
@@ -72,11 +96,14 @@ impl<T: WizardItem> WizardStep<T> for SimpleTextStep<T> {
         input.set_placeholder(self.placeholder);
         input.set_text((self.getter)(item));
 
-        let _ = InputBox::new(&input, theme)
+        let mut input_box = InputBox::new(&input, theme)
             .follow_cursor(true)
             .title_override(Some(self.field_label))
-            .cursor_active(true)
-            .render(frame, input_area);
+            .cursor_active(true);
+        if input.constraints().is_some() {
+            input_box = input_box.validation(input.validation());
+        }
+        let _ = input_box.render(frame, input_area);
 
         if let Some(status_fn) = self.status {
             if let Some(status) = status_fn(item) {
@@ -102,11 +129,16 @@ impl<T: WizardItem> WizardStep<T> for SimpleTextStep<T> {
     }
 
     fn handle_key(&mut self, key: KeyEvent, item: &mut T) -> StepAction {
-        match key.code {
-            KeyCode::Esc => return StepAction::Cancel,
-            KeyCode::Enter => {
+        let keymap = self.keymap.clone().unwrap_or_else(keymap::active);
+        match keymap.action_for(context::WIZARD, &key) {
+            Some(action::CANCEL) => return StepAction::Cancel,
+            Some(action::SUBMIT) => {
                 (self.setter)(item, self.input.text().to_string());
-                match (self.validator)(self.input.text()) {
+                let constraints = self
+                    .input
+                    .constraints()
+                    .map_or(Ok(()), |constraints| constraints.check(self.input.text()));
+                match constraints.and_then(|()| (self.validator)(self.input.text())) {
                     Ok(_) => {
                         self.validation_error = None;
                         return StepAction::Next;
@@ -120,7 +152,7 @@ impl<T: WizardItem> WizardStep<T> for SimpleTextStep<T> {
             _ => {}
         }
 
-        if self.input.handle_key(key) {
+        if self.input.handle_key_with(&keymap, key) {
             (self.setter)(item, self.input.text().to_string());
             self.validation_error = None;
         }
@@ -131,4 +163,8 @@ impl<T: WizardItem> WizardStep<T> for SimpleTextStep<T> {
     fn validate(&self, item: &T) -> Result<(), String> {
         (self.validator)((self.getter)(item).as_str())
     }
+
+    fn key_contexts(&self) -> &[&str] {
+        &[context::WIZARD, context::TEXT_INPUT]
+    }
 }
@@ -2,7 +2,9 @@
 
 use super::framework::{ItemListView, WizardItem};
 use super::model::{GenericWizardModel, ViewMode};
+use crate::components::help::{hint_entries, hint_pair, hint_text};
 use crate::components::{dialog_shell, tabbed_dialog};
+use crate::keymap::{self, action, context};
 use crate::theme::{to_ratatui, Theme, ThemeElement};
 use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::style::{Modifier, Style};
@@ -194,18 +196,44 @@ fn render_list_view<T, L>(
         );
     }
 
-    let help_text = if model.items.is_empty() {
-        let tab_hint = if tab_labels.len() > 1 {
-            "  tab next"
-        } else {
-            ""
-        };
-        format!("esc close{}  n new item", tab_hint)
+    let keymap = model.keymap.clone().unwrap_or_else(keymap::active);
+    let close = hint_entries(&keymap, context::WIZARD, &[(action::CANCEL, "close")]);
+    let mut entries = Vec::new();
+    if model.items.is_empty() {
+        entries.extend(close);
+        if tab_labels.len() > 1 {
+            entries.extend(hint_entries(
+                &keymap,
+                context::TABS,
+                &[(action::NEXT_TAB, "next")],
+            ));
+        }
+        entries.extend(hint_entries(
+            &keymap,
+            context::LIST,
+            &[(action::NEW_ITEM, "new item")],
+        ));
     } else {
-        "enter edit  esc close  ↑↓ navigate  n new".to_string()
-    };
+        entries.extend(hint_entries(
+            &keymap,
+            context::LIST,
+            &[(action::CONFIRM, "edit")],
+        ));
+        entries.extend(close);
+        entries.extend(hint_pair(
+            &keymap,
+            context::LIST,
+            (action::SELECT_PREV, action::SELECT_NEXT),
+            "navigate",
+        ));
+        entries.extend(hint_entries(
+            &keymap,
+            context::LIST,
+            &[(action::NEW_ITEM, "new")],
+        ));
+    }
 
-    render_footer_text(frame, layout.footer, theme, &help_text);
+    render_footer_text(frame, layout.footer, theme, &hint_text(&entries));
 }
 
 fn render_wizard_view<T>(
@@ -260,17 +288,24 @@ fn render_wizard_view<T>(
         Some(ratatui::layout::Alignment::Left),
     );
 
-    let nav_text = wizard.current_step().navigation_hint().unwrap_or_else(|| {
-        if step_num == 1 {
-            "enter next  esc cancel"
-        } else if step_num == step_count {
-            "enter save  esc cancel  ← back"
-        } else {
-            "enter next  esc cancel  ← back"
+    let nav_text = match wizard.current_step().navigation_hint() {
+        Some(hint) => hint.to_string(),
+        None => {
+            let submit = if step_num > 1 && step_num == step_count {
+                "save"
+            } else {
+                "next"
+            };
+            let mut labels = vec![(action::SUBMIT, submit), (action::CANCEL, "cancel")];
+            if step_num > 1 {
+                labels.push((action::PREVIOUS, "back"));
+            }
+            let keymap = model.keymap.clone().unwrap_or_else(keymap::active);
+            hint_text(&hint_entries(&keymap, context::WIZARD, &labels))
         }
-    });
+    };
 
-    render_footer_text(frame, layout.footer, theme, nav_text);
+    render_footer_text(frame, layout.footer, theme, &nav_text);
 }
 
 fn render_confirmation_view<T>(
@@ -0,0 +1,149 @@
+//! Atomic chips in `TextInput` and `InputBox`.
+
+use nodecode_terminal_kit::components::input_box::InputBox;
+use nodecode_terminal_kit::components::input_history::InputHistory;
+use nodecode_terminal_kit::components::text_input::{Chip, TextInput};
+use nodecode_terminal_kit::theme::{to_ratatui, Theme};
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::Terminal;
+
+fn press(input: &mut TextInput, code: KeyCode) {
+    input.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+}
+
+fn press_history(history: &mut InputHistory, input: &mut TextInput, code: KeyCode) {
+    history.handle_key(input, KeyEvent::new(code, KeyModifiers::NONE), 80);
+}
+
+#[test]
+fn chips_are_single_units_for_the_cursor_and_backspace() {
+    let mut input = TextInput::with_text("see ".into());
+    assert!(input.insert_chip(Chip::file("/tmp/report.pdf")));
+    input.insert_str(" now");
+    assert_eq!(input.visible_text(), "see [📎 report.pdf] now");
+    assert_eq!(input.expanded_text(), "see /tmp/report.pdf now");
+    let chips = input.chips();
+    assert_eq!(chips.len(), 1);
+    assert_eq!(chips[0].1.label, "📎 report.pdf");
+    let range = chips[0].0.clone();
+
+    // From after the chip, one step left lands before it.
+    input.set_cursor_byte_offset(range.end);
+    press(&mut input, KeyCode::Left);
+    assert_eq!(input.cursor(), range.start);
+    press(&mut input, KeyCode::Right);
+    press(&mut input, KeyCode::Backspace);
+    assert_eq!(input.visible_text(), "see  now");
+    assert!(input.chips().is_empty());
+
+    input.undo();
+    assert_eq!(input.visible_text(), "see [📎 report.pdf] now");
+}
+
+#[test]
+fn chip_characters_from_outside_are_dropped() {
+    let stray = "a\u{100000}b";
+    let mut input = TextInput::with_text(stray.into());
+    assert_eq!(input.text(), "ab");
+    input.insert_chip(Chip::new("one", "payload"));
+
+    input.set_text(stray.into());
+    assert_eq!(input.text(), "ab");
+    input.insert_str(stray);
+    input.paste(stray);
+    input.insert_char('\u{100000}');
+    press(&mut input, KeyCode::Char('\u{100000}'));
+    assert_eq!(input.text(), "ababab");
+    assert!(input.chips().is_empty());
+}
+
+#[test]
+fn recalled_text_keeps_only_chips_still_held() {
+    let mut history = InputHistory::new();
+    let mut input = TextInput::new();
+    input.insert_chip(Chip::new("one", "payload"));
+    input.insert_str(" tail");
+    history.push(input.text()).unwrap();
+    input.clear();
+
+    // Undo can still bring the chip back, so recalling it keeps it.
+    press_history(&mut history, &mut input, KeyCode::Up);
+    assert_eq!(input.expanded_text(), "payload tail");
+
+    input.clear();
+    input.clear_history();
+    history.reset_navigation();
+    press_history(&mut history, &mut input, KeyCode::Up);
+    assert_eq!(input.text(), " tail");
+    assert!(input.chips().is_empty());
+}
+
+#[test]
+fn chip_payloads_are_dropped_with_the_last_step_holding_them() {
+    let mut history = InputHistory::new();
+    let mut input = TextInput::new();
+    input.set_history_limit(1);
+    input.insert_chip(Chip::new("one", "payload"));
+    input.insert_str(" tail");
+    history.push(input.text()).unwrap();
+    input.set_text("x".into());
+    input.clear();
+    assert!(input.undo());
+    assert!(!input.can_undo());
+    input.clear();
+
+    press_history(&mut history, &mut input, KeyCode::Up);
+    assert_eq!(input.text(), " tail");
+    assert!(input.chips().is_empty());
+}
+
+#[test]
+fn large_pastes_become_chips() {
+    let mut input = TextInput::new();
+    input.set_paste_chip_lines(Some(2));
+    input.paste("short");
+    input.paste("a\r\nb\r\nc");
+    assert_eq!(input.visible_text(), "short[Pasted 3 lines]");
+    assert_eq!(input.expanded_text(), "shorta\nb\nc");
+    assert!(input.undo());
+    assert_eq!(input.text(), "short");
+}
+
+#[test]
+fn cursor_math_and_wrapping_treat_chips_as_units() {
+    let mut input = TextInput::with_text("ab ".into());
+    input.insert_chip(Chip::new("x y", "payload"));
+    // "ab [x y]" is 8 columns; the cursor sits after the chip.
+    assert_eq!(input.cursor_visual_position(20), (0, 8));
+    // At width 6 the chip moves to the next row whole instead of breaking
+    // at its inner space.
+    assert_eq!(input.wrapped_rows(6), vec![(0, 3), (3, 8)]);
+    assert_eq!(input.cursor_visual_position(6), (1, 5));
+    // A click inside the label lands before the chip.
+    assert_eq!(input.offset_at_visual(1, 2, 6), 3);
+}
+
+#[test]
+fn input_box_draws_chips_as_badges() {
+    let theme = Theme::default();
+    let mut input = TextInput::new();
+    input.insert_chip(Chip::pasted("one\ntwo"));
+    let mut terminal = Terminal::new(TestBackend::new(24, 1)).unwrap();
+    terminal
+        .draw(|frame| {
+            InputBox::new(&input, &theme)
+                .prompt_override(Some(""))
+                .prompt_gap(0)
+                .padding_top(0)
+                .padding_bottom(0)
+                .render(frame, frame.area());
+        })
+        .unwrap();
+    let buffer = terminal.backend().buffer();
+    let row = (0..24).map(|x| buffer[(x, 0)].symbol()).collect::<String>();
+    assert_eq!(row.trim_end(), "[Pasted 2 lines]");
+    assert_eq!(buffer[(0, 0)].bg, to_ratatui(theme.background_badge));
+    assert_eq!(buffer[(15, 0)].bg, to_ratatui(theme.background_badge));
+    assert_ne!(buffer[(16, 0)].bg, to_ratatui(theme.background_badge));
+}
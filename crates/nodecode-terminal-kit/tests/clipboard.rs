@@ -0,0 +1,103 @@
+//! Clipboard backends and selection rendering in `InputBox`.
+
+use nodecode_terminal_kit::clipboard::{
+    self, osc52_sequence, tmux_passthrough, Clipboard, MemoryClipboard, Osc52Clipboard,
+    SharedClipboard,
+};
+use nodecode_terminal_kit::components::input_box::InputBox;
+use nodecode_terminal_kit::components::text_input::TextInput;
+use nodecode_terminal_kit::theme::{to_ratatui, Theme};
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::Rect;
+use ratatui::Terminal;
+
+#[test]
+fn osc52_copies_base64_text_to_the_terminal() {
+    assert_eq!(osc52_sequence("hi"), "\x1b]52;c;aGk=\x07");
+    assert_eq!(
+        tmux_passthrough("\x1b]52;c;aGk=\x07"),
+        "\x1bPtmux;\x1b\x1b]52;c;aGk=\x07\x1b\\"
+    );
+
+    let mut osc = Osc52Clipboard::new(Vec::new());
+    osc.set_text("hi").unwrap();
+    assert_eq!(osc.writer().as_slice(), b"\x1b]52;c;aGk=\x07");
+    assert_eq!(osc.get_text().unwrap(), Some("hi".into()));
+}
+
+#[test]
+fn cut_and_paste_go_through_the_active_clipboard() {
+    // The only test in this binary that touches the process-wide clipboard.
+    clipboard::set_active(MemoryClipboard::new());
+
+    let mut input = TextInput::with_text("copy me".into());
+    input.set_selection(0..5);
+    assert!(input.handle_key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL)));
+    assert_eq!(input.text(), "me");
+    assert_eq!(clipboard::paste().unwrap(), Some("copy ".into()));
+
+    input.handle_key(KeyEvent::new(KeyCode::Char('v'), KeyModifiers::CONTROL));
+    assert_eq!(input.text(), "copy me");
+}
+
+#[test]
+fn inputs_can_hold_their_own_clipboard() {
+    let shared = SharedClipboard::new(MemoryClipboard::new());
+    let mut source = TextInput::with_text("one two".into());
+    source.set_clipboard(Some(shared.clone()));
+    let mut target = TextInput::new();
+    target.set_clipboard(Some(shared.clone()));
+    let mut other = TextInput::new();
+    other.set_clipboard(Some(SharedClipboard::new(MemoryClipboard::new())));
+
+    source.set_selection(4..7);
+    assert_eq!(source.copy_selection(), Ok(true));
+    assert_eq!(shared.paste().unwrap(), Some("two".into()));
+    assert_eq!(target.paste_from_clipboard(), Ok(true));
+    assert_eq!(target.text(), "two");
+    assert_eq!(other.paste_from_clipboard(), Ok(false));
+}
+
+#[test]
+fn mouse_drag_selects_and_input_box_highlights_it() {
+    let theme = Theme::default();
+    let mut input = TextInput::with_text("drag across".into());
+    let mut terminal = Terminal::new(TestBackend::new(20, 3)).unwrap();
+    let area = Rect::new(0, 0, 20, 3);
+    let mut outcome = Default::default();
+    terminal
+        .draw(|frame| outcome = InputBox::new(&input, &theme).render(frame, area))
+        .unwrap();
+
+    let content = outcome.content_area;
+    let mouse = |kind, column| MouseEvent {
+        kind,
+        column: content.x + column,
+        row: content.y,
+        modifiers: KeyModifiers::NONE,
+    };
+    assert!(input.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), 5), &outcome));
+    assert!(input.handle_mouse(mouse(MouseEventKind::Drag(MouseButton::Left), 11), &outcome));
+    assert!(input.handle_mouse(mouse(MouseEventKind::Up(MouseButton::Left), 11), &outcome));
+    assert_eq!(input.selected_text(), Some("across"));
+
+    terminal
+        .draw(|frame| {
+            InputBox::new(&input, &theme).render(frame, area);
+        })
+        .unwrap();
+    let buffer = terminal.backend().buffer();
+    let selection = to_ratatui(theme.selection);
+    assert_ne!(buffer[(content.x + 4, content.y)].bg, selection);
+    assert_eq!(buffer[(content.x + 5, content.y)].bg, selection);
+    assert_eq!(buffer[(content.x + 10, content.y)].bg, selection);
+
+    // A plain click collapses the selection.
+    input.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), 0), &outcome);
+    input.handle_mouse(mouse(MouseEventKind::Up(MouseButton::Left), 0), &outcome);
+    assert_eq!(input.selection(), None);
+    assert_eq!(input.cursor(), 0);
+}
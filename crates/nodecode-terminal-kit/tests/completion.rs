@@ -0,0 +1,203 @@
+//! Inline completion: providers, ghost text, acceptance and the dropdown.
+
+use std::fs;
+
+use nodecode_terminal_kit::components::completion::{
+    token_at, Completer, MentionProvider, PathProvider, SlashCommand, SlashCommandProvider,
+    WordListProvider,
+};
+use nodecode_terminal_kit::components::text_input::TextInput;
+use nodecode_terminal_kit::primitives::path::PathDisplayConfig;
+use nodecode_terminal_kit::theme::Theme;
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::Rect;
+use ratatui::Terminal;
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+/// Type `text` the way an app would: completion keys first, then the input.
+fn type_str(completer: &mut Completer, input: &mut TextInput, text: &str) {
+    for ch in text.chars() {
+        let key = key(KeyCode::Char(ch));
+        if !completer.handle_key(input, key) {
+            input.handle_key(key);
+        }
+        completer.update(input);
+    }
+}
+
+#[test]
+fn tokens_are_split_on_whitespace_around_the_cursor() {
+    assert_eq!(token_at("git sta", 7), 4..7);
+    assert_eq!(token_at("git status now", 6), 4..10);
+    assert_eq!(token_at("a  ", 3), 3..3);
+    assert_eq!(token_at("é\u{3000}ab", 6), 5..7);
+}
+
+#[test]
+fn selected_candidate_shows_as_ghost_text_and_tab_accepts_it() {
+    let mut completer = Completer::new().with_provider(WordListProvider::new(["status", "stash"]));
+    let mut input = TextInput::new();
+    type_str(&mut completer, &mut input, "git stat");
+
+    assert!(completer.is_open());
+    assert_eq!(completer.ghost_text(&input), Some("us"));
+    assert!(completer.handle_key(&mut input, key(KeyCode::Tab)));
+    assert_eq!(input.text(), "git status ");
+    assert!(!completer.is_open());
+
+    // Ghost text is only offered at the end of the input.
+    type_str(&mut completer, &mut input, "st");
+    input.handle_key(key(KeyCode::Left));
+    completer.update(&input);
+    assert!(completer.is_open());
+    assert_eq!(completer.ghost_text(&input), None);
+    assert!(!completer.handle_key(&mut input, key(KeyCode::Right)));
+}
+
+#[test]
+fn slash_commands_complete_only_as_the_first_token() {
+    let commands = SlashCommandProvider::new(vec![
+        SlashCommand::new("help").description("Show help"),
+        SlashCommand::new("history"),
+    ]);
+    let mut completer = Completer::new()
+        .with_provider(commands)
+        .with_provider(MentionProvider::new(["alice", "bob"]));
+    let mut input = TextInput::new();
+
+    type_str(&mut completer, &mut input, "/h");
+    let labels: Vec<_> = completer.candidates().map(|c| c.label.as_str()).collect();
+    assert_eq!(labels, ["/help", "/history"]);
+    completer.handle_key(&mut input, key(KeyCode::Down));
+    assert!(completer.handle_key(&mut input, key(KeyCode::Right)));
+    assert_eq!(input.text(), "/history ");
+
+    type_str(&mut completer, &mut input, "/h");
+    assert!(!completer.is_open());
+
+    type_str(&mut completer, &mut input, " @b");
+    assert_eq!(completer.ghost_text(&input), Some("ob"));
+    assert!(completer.handle_key(&mut input, key(KeyCode::Esc)));
+    assert!(!completer.is_open());
+    assert!(!completer.handle_key(&mut input, key(KeyCode::Tab)));
+}
+
+#[test]
+fn paths_complete_relative_to_the_base_dir_one_level_at_a_time() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src").join("main.rs"), "").unwrap();
+    fs::write(dir.path().join("README.md"), "").unwrap();
+    fs::write(dir.path().join(".env"), "").unwrap();
+
+    let provider = PathProvider::new(PathDisplayConfig {
+        base_dir: Some(dir.path().to_path_buf()),
+        ..PathDisplayConfig::default()
+    });
+    let mut completer = Completer::new().with_provider(provider);
+    let mut input = TextInput::new();
+
+    type_str(&mut completer, &mut input, "see @");
+    let labels: Vec<_> = completer.candidates().map(|c| c.label.as_str()).collect();
+    assert_eq!(labels, ["README.md", "src/"]);
+
+    type_str(&mut completer, &mut input, "sr");
+    completer.handle_key(&mut input, key(KeyCode::Tab));
+    // Directories keep completing instead of ending the token.
+    assert_eq!(input.text(), "see @src/");
+    assert_eq!(completer.ghost_text(&input), Some("main.rs"));
+    completer.handle_key(&mut input, key(KeyCode::Tab));
+    assert_eq!(input.text(), "see @src/main.rs ");
+}
+
+#[test]
+fn paths_match_the_typed_name_before_the_listing_is_capped() {
+    let dir = tempfile::tempdir().unwrap();
+    for idx in (0..600).rev() {
+        fs::write(dir.path().join(format!("log{idx:03}.txt")), "").unwrap();
+    }
+    fs::write(dir.path().join("zeta.txt"), "").unwrap();
+
+    let provider = PathProvider::new(PathDisplayConfig {
+        base_dir: Some(dir.path().to_path_buf()),
+        ..PathDisplayConfig::default()
+    });
+    let mut completer = Completer::new().with_provider(provider);
+    let mut input = TextInput::new();
+
+    type_str(&mut completer, &mut input, "@ze");
+    let labels: Vec<_> = completer.candidates().map(|c| c.label.as_str()).collect();
+    assert_eq!(labels, ["zeta.txt"]);
+
+    input.clear();
+    type_str(&mut completer, &mut input, "@log");
+    assert_eq!(
+        completer.candidates().next().map(|c| c.label.as_str()),
+        Some("log000.txt")
+    );
+}
+
+#[test]
+fn paths_stay_inside_the_base_dir() {
+    let root = tempfile::tempdir().unwrap();
+    let base = root.path().join("project");
+    fs::create_dir(&base).unwrap();
+    fs::write(root.path().join("secret.txt"), "").unwrap();
+
+    let provider = PathProvider::new(PathDisplayConfig {
+        base_dir: Some(base),
+        ..PathDisplayConfig::default()
+    });
+    let mut completer = Completer::new().with_provider(provider);
+    let mut input = TextInput::new();
+    for query in ["@../", "@./../", &format!("@{}/", root.path().display())] {
+        input.clear();
+        type_str(&mut completer, &mut input, query);
+        assert_eq!(completer.candidates().count(), 0, "{query}");
+    }
+}
+
+#[test]
+fn path_listings_are_reused_while_typing_a_name() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("alpha.txt"), "").unwrap();
+
+    let provider = PathProvider::new(PathDisplayConfig {
+        base_dir: Some(dir.path().to_path_buf()),
+        ..PathDisplayConfig::default()
+    });
+    let mut completer = Completer::new().with_provider(provider);
+    let mut input = TextInput::new();
+    type_str(&mut completer, &mut input, "@a");
+    fs::write(dir.path().join("alpine.txt"), "").unwrap();
+    type_str(&mut completer, &mut input, "l");
+    let labels: Vec<_> = completer.candidates().map(|c| c.label.as_str()).collect();
+    assert_eq!(labels, ["alpha.txt"]);
+}
+
+#[test]
+fn dropdown_renders_below_the_input() {
+    let commands = SlashCommandProvider::new(vec![
+        SlashCommand::new("help").description("Show help"),
+        SlashCommand::new("history"),
+    ]);
+    let mut completer = Completer::new().with_provider(commands);
+    let mut input = TextInput::new();
+    type_str(&mut completer, &mut input, "/");
+
+    let theme = Theme::default();
+    let mut terminal = Terminal::new(TestBackend::new(30, 4)).unwrap();
+    terminal
+        .draw(|frame| {
+            assert!(completer.render(frame, Rect::new(0, 0, 30, 1), None, &theme));
+        })
+        .unwrap();
+    let buffer = terminal.backend().buffer();
+    let row = |y: u16| (0..30).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+    assert!(row(1).starts_with(" /help  Show help"));
+    assert!(row(2).starts_with(" /history"));
+}
@@ -0,0 +1,398 @@
+//! Golden snapshots for the kit's composite components.
+//!
+//! Run with `UPDATE_SNAPSHOTS=1 cargo test -p nodecode-terminal-kit` to accept
+//! intentional rendering changes.
+
+use nodecode_terminal_kit::assert_buffer_snapshot;
+use nodecode_terminal_kit::components::dropdown::{
+    dropdown_item_base_style, render_dropdown, DropdownList,
+};
+use nodecode_terminal_kit::components::help::{help_sections, render_compact_help, HelpOverlay};
+use nodecode_terminal_kit::components::input_box::InputBox;
+use nodecode_terminal_kit::components::list::ListState;
+use nodecode_terminal_kit::components::tabbed_dialog::{ListBody, TabBody, TextBody};
+use nodecode_terminal_kit::components::tabbed_prompt_dialog::{
+    render_tabbed_prompt_dialog, SearchSpec, DEFAULT_PROMPT_DIALOG_OPTS,
+};
+use nodecode_terminal_kit::components::text_input::{SecretMask, TextInput};
+use nodecode_terminal_kit::keymap::{context, Keymap, Preset};
+use nodecode_terminal_kit::testing::render_to_buffer;
+use nodecode_terminal_kit::theme::{Theme, ThemeElement};
+use nodecode_terminal_kit::wizard::framework::{
+    ItemListView, WizardFlow, WizardItem, WizardMode, WizardStep,
+};
+use nodecode_terminal_kit::wizard::model::{GenericWizardModel, ViewMode};
+use nodecode_terminal_kit::wizard::text_step::SimpleTextStep;
+use nodecode_terminal_kit::wizard::view::generic_wizard_view;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Text};
+use ratatui::widgets::ListItem;
+
+#[derive(Debug, Clone)]
+struct Server {
+    name: String,
+    url: String,
+}
+
+impl WizardItem for Server {
+    type Id = String;
+
+    fn id(&self) -> Self::Id {
+        self.name.clone()
+    }
+
+    fn display_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn is_valid(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn default_item() -> Self {
+        Self {
+            name: String::new(),
+            url: String::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ServerList;
+
+impl ItemListView<Server> for ServerList {
+    fn render_item(&self, item: &Server, is_selected: bool, theme: &Theme) -> ListItem<'static> {
+        let marker = if is_selected { "› " } else { "  " };
+        ListItem::new(format!("{marker}{}  {}", item.name, item.url))
+            .style(dropdown_item_base_style(theme, is_selected))
+    }
+}
+
+fn servers() -> Vec<Server> {
+    vec![
+        Server {
+            name: "local".into(),
+            url: "http://127.0.0.1".into(),
+        },
+        Server {
+            name: "staging".into(),
+            url: "https://stage.example".into(),
+        },
+    ]
+}
+
+fn wizard_tabs() -> Vec<String> {
+    vec!["Servers".to_string(), "Hooks".to_string()]
+}
+
+fn name_step() -> Box<dyn WizardStep<Server>> {
+    Box::new(SimpleTextStep::new(
+        "Name",
+        "A short unique name",
+        "Server name",
+        "e.g. production",
+        |server: &Server| server.name.clone(),
+        |server: &mut Server, value| server.name = value,
+        |value| {
+            if value.is_empty() {
+                Err("Name is required".into())
+            } else {
+                Ok(())
+            }
+        },
+    ))
+}
+
+#[test]
+fn input_box_with_text() {
+    let theme = Theme::default();
+    let input = TextInput::with_text("hello world".to_string());
+    let buffer = render_to_buffer(30, 5, |frame| {
+        let _ = InputBox::new(&input, &theme)
+            .cursor_active(true)
+            .render(frame, frame.area());
+    });
+    assert_buffer_snapshot!("input_box_with_text", &buffer);
+}
+
+#[test]
+fn input_box_placeholder_and_hint() {
+    let theme = Theme::default();
+    let mut input = TextInput::new();
+    input.set_placeholder("Ask anything");
+    let buffer = render_to_buffer(30, 5, |frame| {
+        let _ = InputBox::new(&input, &theme)
+            .title_override(Some("Prompt"))
+            .right_hint(Some("⏎ send"))
+            .render(frame, frame.area());
+    });
+    assert_buffer_snapshot!("input_box_placeholder_and_hint", &buffer);
+}
+
+#[test]
+fn dropdown_below_input() {
+    let theme = Theme::default();
+    let items = ["alpha", "beta", "gamma", "delta"];
+    let mut list = DropdownList::new();
+    list.reset("", items.to_vec());
+    list.set_selected(1);
+    let buffer = render_to_buffer(20, 6, |frame| {
+        let input_area = Rect::new(0, 0, 20, 2);
+        render_dropdown(
+            frame,
+            input_area,
+            None,
+            &theme,
+            &mut list,
+            |idx, selected| {
+                ListItem::new(items[idx]).style(dropdown_item_base_style(&theme, selected))
+            },
+        );
+    });
+    assert_buffer_snapshot!("dropdown_below_input", &buffer);
+}
+
+#[test]
+fn tabbed_prompt_dialog_with_search_and_list() {
+    let theme = Theme::default();
+    let search = TextInput::with_text("sta".to_string());
+    let list_state = ListState {
+        selected: 1,
+        viewport_offset: 0,
+    };
+    let rows = ["local", "staging", "production"];
+    let buffer = render_to_buffer(48, 16, |frame| {
+        render_tabbed_prompt_dialog(
+            frame,
+            frame.area(),
+            &theme,
+            DEFAULT_PROMPT_DIALOG_OPTS,
+            &[0usize, 1],
+            0,
+            |tab| [" Servers ", " Hooks "][tab].to_string(),
+            |_, active, theme| {
+                if active {
+                    theme
+                        .style(ThemeElement::Selection)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    theme.style(ThemeElement::Tertiary)
+                }
+            },
+            "Select a server",
+            Some(SearchSpec {
+                input: &search,
+                title: Some("Filter"),
+            }),
+            TabBody::List(ListBody {
+                list_state: &list_state,
+                total: rows.len(),
+                hint_lines: vec![Line::from("3 servers")],
+                hint_rows: None,
+                render_item: Box::new(|idx, selected| {
+                    ListItem::new(rows[idx]).style(if selected {
+                        Style::default().add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default()
+                    })
+                }),
+                empty: None,
+            }),
+            "enter select  esc close",
+        );
+    });
+    assert_buffer_snapshot!("tabbed_prompt_dialog_with_search_and_list", &buffer);
+}
+
+#[test]
+fn tabbed_prompt_dialog_with_text_body() {
+    let theme = Theme::default();
+    let buffer = render_to_buffer(40, 12, |frame| {
+        render_tabbed_prompt_dialog(
+            frame,
+            frame.area(),
+            &theme,
+            DEFAULT_PROMPT_DIALOG_OPTS,
+            &[0usize],
+            0,
+            |_| " About ".to_string(),
+            |_, _, theme| theme.style(ThemeElement::Selection),
+            "",
+            None,
+            TabBody::Text(TextBody {
+                text: Text::from("Nothing to configure yet."),
+                style: ThemeElement::Secondary,
+            }),
+            "esc close",
+        );
+    });
+    assert_buffer_snapshot!("tabbed_prompt_dialog_with_text_body", &buffer);
+}
+
+#[test]
+fn wizard_list_view() {
+    let theme = Theme::default();
+    let mut model = GenericWizardModel::<Server>::default();
+    model.open(servers());
+    model.selected_idx = 1;
+    let buffer = render_to_buffer(50, 14, |frame| {
+        generic_wizard_view(
+            &model,
+            &ServerList,
+            frame,
+            frame.area(),
+            &theme,
+            "Servers",
+            "Servers",
+            &wizard_tabs(),
+            0,
+            0,
+        );
+    });
+    assert_buffer_snapshot!("wizard_list_view", &buffer);
+}
+
+#[test]
+fn wizard_empty_list_view() {
+    let theme = Theme::default();
+    let mut model = GenericWizardModel::<Server>::default();
+    model.open(Vec::new());
+    let buffer = render_to_buffer(50, 12, |frame| {
+        generic_wizard_view(
+            &model,
+            &ServerList,
+            frame,
+            frame.area(),
+            &theme,
+            "Servers",
+            "Servers",
+            &wizard_tabs(),
+            0,
+            0,
+        );
+    });
+    assert_buffer_snapshot!("wizard_empty_list_view", &buffer);
+}
+
+#[test]
+fn wizard_text_step() {
+    let theme = Theme::default();
+    let mut model = GenericWizardModel::<Server>::default();
+    model.open(servers());
+    let mut flow = WizardFlow::new(vec![name_step(), name_step()], WizardMode::Creating);
+    flow.item_mut().name = "prod".into();
+    model.wizard = Some(flow);
+    model.view_mode = ViewMode::Wizard;
+    let buffer = render_to_buffer(50, 16, |frame| {
+        generic_wizard_view(
+            &model,
+            &ServerList,
+            frame,
+            frame.area(),
+            &theme,
+            "New server",
+            "Servers",
+            &wizard_tabs(),
+            0,
+            1,
+        );
+    });
+    assert_buffer_snapshot!("wizard_text_step", &buffer);
+}
+
+#[test]
+fn wizard_secret_text_step_masks_the_value() {
+    let theme = Theme::default();
+    let mut model = GenericWizardModel::<Server>::default();
+    model.open(servers());
+    let token = SimpleTextStep::new(
+        "Token",
+        "API token for the server",
+        "Token",
+        "sk-...",
+        |server: &Server| server.url.clone(),
+        |server: &mut Server, value| server.url = value,
+        |_| Ok(()),
+    )
+    .with_secret(SecretMask::default());
+    let mut flow = WizardFlow::new(vec![Box::new(token)], WizardMode::Creating);
+    flow.item_mut().url = "hunter2".into();
+    model.wizard = Some(flow);
+    model.view_mode = ViewMode::Wizard;
+    let buffer = render_to_buffer(50, 16, |frame| {
+        generic_wizard_view(
+            &model,
+            &ServerList,
+            frame,
+            frame.area(),
+            &theme,
+            "New server",
+            "Servers",
+            &wizard_tabs(),
+            0,
+            1,
+        );
+    });
+    let screen: String = (0..buffer.area.height)
+        .flat_map(|y| (0..buffer.area.width).map(move |x| (x, y)))
+        .map(|pos| buffer[pos].symbol())
+        .collect();
+    assert!(!screen.contains("hunter2"));
+    assert!(screen.contains("•••••••"));
+}
+
+#[test]
+fn wizard_delete_confirmation() {
+    let theme = Theme::default();
+    let mut model = GenericWizardModel::<Server>::default();
+    model.open(servers());
+    model.pending_delete = Some("staging".into());
+    model.view_mode = ViewMode::Confirmation;
+    let buffer = render_to_buffer(50, 12, |frame| {
+        generic_wizard_view(
+            &model,
+            &ServerList,
+            frame,
+            frame.area(),
+            &theme,
+            "Servers",
+            "Servers",
+            &wizard_tabs(),
+            0,
+            0,
+        );
+    });
+    assert_buffer_snapshot!("wizard_delete_confirmation", &buffer);
+}
+
+#[test]
+fn compact_help_footer() {
+    let theme = Theme::default();
+    let keymap = Keymap::default();
+    let sections = help_sections(&keymap, &[context::PICKER]);
+    let buffer = render_to_buffer(50, 1, |frame| {
+        render_compact_help(frame, frame.area(), &theme, &keymap, &sections);
+    });
+    assert_buffer_snapshot!("compact_help_footer", &buffer);
+}
+
+#[test]
+fn help_overlay_filtered_by_search() {
+    let theme = Theme::default();
+    let keymap = Preset::Vim.keymap();
+    let sections = help_sections(&keymap, &[context::LIST, context::TABS]);
+    let mut overlay = HelpOverlay::new();
+    overlay.open();
+    for ch in "next".chars() {
+        overlay.handle_key(
+            &keymap,
+            KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE),
+        );
+    }
+    let buffer = render_to_buffer(40, 14, |frame| {
+        overlay.render(frame, frame.area(), &theme, &keymap, &sections);
+    });
+    assert_buffer_snapshot!("help_overlay_filtered_by_search", &buffer);
+}
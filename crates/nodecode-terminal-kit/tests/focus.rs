@@ -0,0 +1,168 @@
+//! Focus traversal and event routing through `FocusTree`.
+
+use nodecode_terminal_kit::components::component::UiComponent;
+use nodecode_terminal_kit::components::focus::FocusTree;
+use nodecode_terminal_kit::keymap::context;
+use nodecode_terminal_kit::theme::{Theme, ThemeState};
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::Rect;
+use ratatui::{Frame, Terminal};
+
+/// Emits `name:key` for its own key and Tab, and echoes clicks.
+struct Probe {
+    name: &'static str,
+    key: char,
+}
+
+impl Probe {
+    fn new(name: &'static str, key: char) -> Self {
+        Self { name, key }
+    }
+}
+
+impl UiComponent for Probe {
+    type Action = String;
+
+    fn update(&mut self, _action: String) {}
+
+    fn view(&mut self, _frame: &mut Frame, _area: Rect, _theme: &Theme) {}
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<String> {
+        match key.code {
+            KeyCode::Char(ch) if ch == self.key => Some(format!("{}:{ch}", self.name)),
+            KeyCode::Tab => Some(format!("{}:tab", self.name)),
+            _ => None,
+        }
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent, area: Rect) -> Option<String> {
+        Some(format!(
+            "{}:click@{},{}",
+            self.name,
+            mouse.column - area.x,
+            mouse.row - area.y
+        ))
+    }
+
+    fn key_contexts(&self) -> &[&str] {
+        &[context::TEXT_INPUT]
+    }
+}
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+fn click(column: u16, row: u16) -> MouseEvent {
+    MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column,
+        row,
+        modifiers: KeyModifiers::NONE,
+    }
+}
+
+/// form ─┬─ name
+///       ├─ buttons (group) ─┬─ ok
+///       │                   └─ cancel
+///       └─ notes
+fn form() -> FocusTree<&'static str, String> {
+    let mut tree = FocusTree::new();
+    tree.insert("form", None, Probe::new("form", 'f'));
+    tree.set_focusable("form", false);
+    tree.insert("name", Some("form"), Probe::new("name", 'n'));
+    tree.insert_group("buttons", Some("form"));
+    tree.insert("ok", Some("buttons"), Probe::new("ok", 'o'));
+    tree.insert("cancel", Some("buttons"), Probe::new("cancel", 'c'));
+    tree.insert("notes", Some("form"), Probe::new("notes", 'x'));
+    tree
+}
+
+#[test]
+fn tab_cycles_focus_in_tree_order() {
+    let mut tree = form();
+    assert_eq!(tree.focus_order(), ["name", "ok", "cancel", "notes"]);
+    assert_eq!(tree.focused(), Some("name"));
+
+    for expected in ["ok", "cancel", "notes", "name"] {
+        assert_eq!(tree.handle_key(key(KeyCode::Tab)), None);
+        assert_eq!(tree.focused(), Some(expected));
+    }
+    tree.handle_key(key(KeyCode::BackTab));
+    assert_eq!(tree.focused(), Some("notes"));
+
+    assert!(tree.contains_focus("form"));
+    assert!(!tree.contains_focus("buttons"));
+    assert_eq!(tree.theme_state("notes"), ThemeState::Active);
+    assert_eq!(tree.theme_state("name"), ThemeState::Normal);
+    assert!(!tree.focus("buttons"));
+    assert!(!tree.focus("form"));
+}
+
+#[test]
+fn unhandled_keys_bubble_to_parents() {
+    let mut tree = form();
+    tree.focus("ok");
+    assert_eq!(
+        tree.handle_key(key(KeyCode::Char('o'))),
+        Some("ok:o".into())
+    );
+    // `ok` ignores `f`; the group has no component, so `form` gets it.
+    assert_eq!(
+        tree.handle_key(key(KeyCode::Char('f'))),
+        Some("form:f".into())
+    );
+    assert_eq!(tree.handle_key(key(KeyCode::Char('n'))), None);
+    assert_eq!(tree.key_contexts(), [context::TEXT_INPUT, context::FOCUS]);
+}
+
+#[test]
+fn captured_tab_reaches_the_component() {
+    let mut tree = form();
+    tree.set_captures_tab("name", true);
+    assert_eq!(tree.handle_key(key(KeyCode::Tab)), Some("name:tab".into()));
+    assert_eq!(tree.focused(), Some("name"));
+}
+
+#[test]
+fn click_focuses_the_component_under_the_pointer() {
+    let mut tree = form();
+    let theme = Theme::default();
+    let mut terminal = Terminal::new(TestBackend::new(20, 6)).unwrap();
+    terminal
+        .draw(|frame| {
+            tree.render("form", frame, Rect::new(0, 0, 20, 6), &theme);
+            tree.render("name", frame, Rect::new(0, 0, 20, 1), &theme);
+            tree.set_area("buttons", Rect::new(0, 2, 20, 1));
+            tree.render("ok", frame, Rect::new(0, 2, 10, 1), &theme);
+            tree.render("cancel", frame, Rect::new(10, 2, 10, 1), &theme);
+        })
+        .unwrap();
+
+    assert_eq!(tree.hit_test(12, 2), Some("cancel"));
+    assert_eq!(
+        tree.handle_mouse(click(12, 2)),
+        Some("cancel:click@2,0".into())
+    );
+    assert_eq!(tree.focused(), Some("cancel"));
+
+    // Clicking empty form space leaves focus alone; `form` is not focusable.
+    assert_eq!(
+        tree.handle_mouse(click(3, 4)),
+        Some("form:click@3,4".into())
+    );
+    assert_eq!(tree.focused(), Some("cancel"));
+}
+
+#[test]
+fn removing_the_focused_subtree_moves_focus() {
+    let mut tree = form();
+    tree.focus("cancel");
+    tree.remove("buttons");
+    assert_eq!(tree.focus_order(), ["name", "notes"]);
+    assert_eq!(tree.focused(), Some("name"));
+    assert!(tree.component("ok").is_none());
+}
@@ -0,0 +1,136 @@
+//! Grapheme-cluster-aware wrapping and cursor math.
+
+use nodecode_terminal_kit::components::text_input::TextInput;
+use nodecode_terminal_kit::layout::text::{visual_column, wrapped_row_ranges};
+use proptest::prelude::*;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_segmentation::UnicodeSegmentation;
+
+const FAMILY: &str = "👨\u{200d}👩\u{200d}👧";
+const FLAG: &str = "🇯🇵";
+const E_ACUTE: &str = "e\u{301}";
+
+fn press(input: &mut TextInput, code: KeyCode, modifiers: KeyModifiers) {
+    input.handle_key(KeyEvent::new(code, modifiers));
+}
+
+fn is_grapheme_boundary(text: &str, offset: usize) -> bool {
+    offset == text.len() || text.grapheme_indices(true).any(|(idx, _)| idx == offset)
+}
+
+#[test]
+fn clusters_measure_as_drawn() {
+    assert_eq!(visual_column(FAMILY), 2);
+    assert_eq!(visual_column(FLAG), 2);
+    assert_eq!(visual_column(E_ACUTE), 1);
+    assert_eq!(visual_column("a\u{200b}b"), 2);
+
+    // Four flags are eight columns and wrap between clusters.
+    let flags = FLAG.repeat(4);
+    let rows = wrapped_row_ranges(&flags, 5);
+    assert_eq!(rows.len(), 2);
+    assert_eq!(&flags[rows[0].0..rows[0].1], FLAG.repeat(2));
+}
+
+#[test]
+fn editing_steps_over_whole_clusters() {
+    let text = format!("a{FAMILY}{E_ACUTE}");
+    let mut input = TextInput::with_text(text.clone());
+    assert_eq!(input.cursor_visual_position(20), (0, 4));
+
+    press(&mut input, KeyCode::Left, KeyModifiers::NONE);
+    assert_eq!(input.cursor(), 1 + FAMILY.len());
+    press(&mut input, KeyCode::Left, KeyModifiers::NONE);
+    assert_eq!(input.cursor(), 1);
+    assert_eq!(input.cursor_visual_position(20), (0, 1));
+
+    press(&mut input, KeyCode::Delete, KeyModifiers::NONE);
+    assert_eq!(input.text(), format!("a{E_ACUTE}"));
+    input.cursor_end();
+    press(&mut input, KeyCode::Backspace, KeyModifiers::NONE);
+    assert_eq!(input.text(), "a");
+
+    // Offsets inside a cluster snap to its start.
+    let mut input = TextInput::with_text(text);
+    input.set_cursor_byte_offset(3);
+    assert_eq!(input.cursor(), 1);
+}
+
+fn cluster() -> impl Strategy<Value = &'static str> {
+    prop::sample::select(vec![
+        "a",
+        "z",
+        " ",
+        "\n",
+        "/",
+        "漢",
+        FAMILY,
+        FLAG,
+        E_ACUTE,
+        "\u{200b}",
+        "☺\u{fe0f}",
+    ])
+}
+
+fn key() -> impl Strategy<Value = (KeyCode, KeyModifiers)> {
+    let none = KeyModifiers::NONE;
+    prop::sample::select(vec![
+        (KeyCode::Left, none),
+        (KeyCode::Right, none),
+        (KeyCode::Up, none),
+        (KeyCode::Down, none),
+        (KeyCode::Home, none),
+        (KeyCode::End, none),
+        (KeyCode::Backspace, none),
+        (KeyCode::Delete, none),
+        (KeyCode::Char('x'), none),
+        (KeyCode::Left, KeyModifiers::SHIFT),
+        (KeyCode::Right, KeyModifiers::SHIFT),
+        (KeyCode::Left, KeyModifiers::CONTROL),
+        (KeyCode::Right, KeyModifiers::CONTROL),
+        (KeyCode::Char('b'), KeyModifiers::CONTROL),
+        (KeyCode::Char('f'), KeyModifiers::CONTROL),
+        (KeyCode::Char('z'), KeyModifiers::CONTROL),
+    ])
+}
+
+proptest! {
+    #[test]
+    fn cursor_offsets_stay_on_grapheme_boundaries(
+        clusters in prop::collection::vec(cluster(), 0..24),
+        keys in prop::collection::vec(key(), 0..40),
+        width in 1u16..12,
+        click in (0usize..8, 0usize..16),
+    ) {
+        let mut input = TextInput::with_text(clusters.concat());
+        for (code, modifiers) in keys {
+            press(&mut input, code, modifiers);
+            let text = input.text().to_string();
+            prop_assert!(is_grapheme_boundary(&text, input.cursor()));
+            if let Some(selection) = input.selection() {
+                prop_assert!(is_grapheme_boundary(&text, selection.start));
+                prop_assert!(is_grapheme_boundary(&text, selection.end));
+            }
+        }
+
+        let text = input.text().to_string();
+        let at = input.offset_at_visual(click.0, click.1, width);
+        prop_assert!(is_grapheme_boundary(&text, at));
+        input.cursor_move_visual_vertical(true, width);
+        prop_assert!(is_grapheme_boundary(&text, input.cursor()));
+
+        let (_, col) = input.cursor_visual_position(width);
+        prop_assert!(col <= width);
+        for (start, end) in wrapped_row_ranges(&text, width) {
+            prop_assert!(is_grapheme_boundary(&text, start));
+            prop_assert!(is_grapheme_boundary(&text, end));
+            let row = &text[start..end];
+            // A cluster wider than the row still gets a row of its own,
+            // shared at most with zero-width clusters.
+            let visible = row.graphemes(true).filter(|g| visual_column(g) > 0).count();
+            if visible > 1 {
+                prop_assert!(visual_column(row) <= width as usize, "{:?} is too wide", row);
+            }
+        }
+    }
+}
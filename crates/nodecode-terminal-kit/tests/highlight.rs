@@ -0,0 +1,115 @@
+//! Token highlighting in `InputBox`.
+
+use std::path::PathBuf;
+
+use nodecode_terminal_kit::components::highlight::{
+    Highlight, Highlighter, Highlighters, InlineCodeHighlighter, SlashCommandHighlighter,
+};
+use nodecode_terminal_kit::components::input_box::InputBox;
+use nodecode_terminal_kit::components::text_input::{SecretMask, TextInput};
+use nodecode_terminal_kit::primitives::path::{MentionPathFormatter, PathDisplayConfig};
+use nodecode_terminal_kit::theme::{to_ratatui, Theme};
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Style};
+use ratatui::Terminal;
+
+fn render(input: &TextInput, width: u16, highlighter: &dyn Highlighter) -> Buffer {
+    let theme = Theme::default();
+    let mut terminal = Terminal::new(TestBackend::new(width, 3)).unwrap();
+    terminal
+        .draw(|frame| {
+            InputBox::new(input, &theme)
+                .prompt_override(Some(""))
+                .prompt_gap(0)
+                .padding_top(0)
+                .padding_bottom(0)
+                .highlighter(highlighter)
+                .render(frame, frame.area());
+        })
+        .unwrap();
+    terminal.backend().buffer().clone()
+}
+
+#[test]
+fn slash_commands_are_colored_and_unknown_ones_flagged() {
+    let theme = Theme::default();
+    let highlighter = SlashCommandHighlighter::new().commands(["help", "model"]);
+    let spans = highlighter.highlight("  /mo gpt", &theme);
+    assert_eq!(spans.len(), 1);
+    assert_eq!(spans[0].range, 2..5);
+    assert_eq!(spans[0].style.fg, Some(to_ratatui(theme.primary)));
+
+    let spans = highlighter.highlight("/nope", &theme);
+    assert_eq!(spans[0].style.fg, Some(to_ratatui(theme.error)));
+    assert!(highlighter.highlight("not /help", &theme).is_empty());
+}
+
+#[test]
+fn mentions_reuse_path_formatter_detection() {
+    let config = PathDisplayConfig {
+        base_dir: Some(PathBuf::from("/work/repo")),
+        ..PathDisplayConfig::default()
+    };
+    let formatter = MentionPathFormatter::with_config(config);
+    let text = "see @src/lib.rs, a@b and @/work/repo/Cargo.toml or /work/repo/x";
+    let ranges: Vec<&str> = formatter
+        .mention_ranges(text)
+        .into_iter()
+        .map(|range| &text[range])
+        .collect();
+    assert_eq!(
+        ranges,
+        ["@src/lib.rs", "@/work/repo/Cargo.toml", "/work/repo/x"]
+    );
+}
+
+#[test]
+fn highlights_split_across_wrapped_rows() {
+    let theme = Theme::default();
+    let input = TextInput::with_text("ab `code` x".into());
+    let buffer = render(&input, 6, &InlineCodeHighlighter);
+    let code = to_ratatui(theme.accent2);
+    // "ab " and "`code` x" wrap at width 6; the span keeps its color.
+    assert_ne!(buffer[(0, 0)].fg, code);
+    assert_eq!(buffer[(0, 1)].symbol(), "`");
+    assert_eq!(buffer[(0, 1)].fg, code);
+    assert_eq!(buffer[(5, 1)].fg, code);
+    assert_ne!(buffer[(0, 2)].fg, code);
+
+    // Invalid ranges from a custom highlighter are ignored.
+    struct Broken;
+    impl Highlighter for Broken {
+        fn highlight(&self, text: &str, _theme: &Theme) -> Vec<Highlight> {
+            vec![
+                Highlight::new(0..text.len() + 4, Style::default().fg(Color::Red)),
+                Highlight::new(1..1, Style::default().fg(Color::Red)),
+            ]
+        }
+    }
+    let buffer = render(&input, 6, &Broken);
+    assert_ne!(buffer[(0, 0)].fg, Color::Red);
+}
+
+#[test]
+fn selections_paint_over_highlights_and_secrets_stay_plain() {
+    let theme = Theme::default();
+    let highlighters = Highlighters::new()
+        .with(InlineCodeHighlighter)
+        .with(SlashCommandHighlighter::new().commands(["help"]));
+    let mut input = TextInput::with_text("/help me".into());
+    let buffer = render(&input, 12, &highlighters);
+    assert_eq!(buffer[(1, 0)].fg, to_ratatui(theme.primary));
+    assert_ne!(buffer[(6, 0)].fg, to_ratatui(theme.primary));
+
+    input.set_selection(0..2);
+    let buffer = render(&input, 12, &highlighters);
+    assert_eq!(buffer[(0, 0)].bg, to_ratatui(theme.selection));
+    assert_eq!(buffer[(2, 0)].fg, to_ratatui(theme.primary));
+
+    input.clear_selection();
+    input.set_secret(Some(SecretMask::default()));
+    let buffer = render(&input, 12, &highlighters);
+    assert_eq!(buffer[(0, 0)].symbol(), "•");
+    assert_ne!(buffer[(0, 0)].fg, to_ratatui(theme.primary));
+}
@@ -0,0 +1,125 @@
+//! Prompt history: recall, reverse search and persistence.
+
+use std::fs;
+
+use nodecode_terminal_kit::components::input_history::InputHistory;
+use nodecode_terminal_kit::components::text_input::TextInput;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+fn ctrl(ch: char) -> KeyEvent {
+    KeyEvent::new(KeyCode::Char(ch), KeyModifiers::CONTROL)
+}
+
+fn history(entries: &[&str]) -> InputHistory {
+    let mut history = InputHistory::new();
+    for entry in entries {
+        history.push(*entry).unwrap();
+    }
+    history
+}
+
+#[test]
+fn push_deduplicates_and_bounds_entries() {
+    let mut history = history(&["a", "b", "a", "  "]);
+    assert_eq!(history.entries(), ["b", "a"]);
+
+    history = history.with_max_entries(2);
+    history.push("c").unwrap();
+    assert_eq!(history.entries(), ["a", "c"]);
+}
+
+#[test]
+fn up_and_down_walk_entries_matching_the_typed_prefix() {
+    let mut history = history(&["git status", "ls", "git log", "git log"]);
+    let mut input = TextInput::with_text("git".into());
+
+    assert!(history.handle_key(&mut input, key(KeyCode::Up), 40));
+    assert_eq!(input.text(), "git log");
+    history.handle_key(&mut input, key(KeyCode::Up), 40);
+    assert_eq!(input.text(), "git status");
+    // Nothing older matches; the entry stays.
+    history.handle_key(&mut input, key(KeyCode::Up), 40);
+    assert_eq!(input.text(), "git status");
+
+    history.handle_key(&mut input, key(KeyCode::Down), 40);
+    assert_eq!(input.text(), "git log");
+    history.handle_key(&mut input, key(KeyCode::Down), 40);
+    assert_eq!(input.text(), "git");
+}
+
+#[test]
+fn up_moves_within_multiline_input_before_recalling() {
+    let mut history = history(&["one\ntwo\nthree"]);
+    let mut input = TextInput::with_text("one\ntwo".into());
+
+    assert!(history.handle_key(&mut input, key(KeyCode::Up), 40));
+    assert_eq!(input.text(), "one\ntwo");
+    history.handle_key(&mut input, key(KeyCode::Up), 40);
+    assert_eq!(input.text(), "one\ntwo\nthree");
+
+    // Other keys are left to the input.
+    assert!(!history.handle_key(&mut input, key(KeyCode::Char('x')), 40));
+}
+
+#[test]
+fn ctrl_r_searches_backwards_incrementally() {
+    let mut history = history(&["cargo build", "cargo test", "echo hi"]);
+    let mut input = TextInput::with_text("draft".into());
+
+    assert!(history.handle_key(&mut input, ctrl('r'), 40));
+    assert!(history.is_searching());
+    for ch in "cargo".chars() {
+        history.handle_key(&mut input, key(KeyCode::Char(ch)), 40);
+    }
+    assert_eq!(input.text(), "cargo test");
+    assert_eq!(
+        history.search_prompt().as_deref(),
+        Some("(reverse-i-search)`cargo': ")
+    );
+
+    history.handle_key(&mut input, ctrl('r'), 40);
+    assert_eq!(input.text(), "cargo build");
+    assert!(history.handle_key(&mut input, key(KeyCode::Enter), 40));
+    assert!(!history.is_searching());
+    assert_eq!(input.text(), "cargo build");
+
+    history.handle_key(&mut input, ctrl('r'), 40);
+    history.handle_key(&mut input, key(KeyCode::Char('z')), 40);
+    assert_eq!(
+        history.search_prompt().as_deref(),
+        Some("(failing reverse-i-search)`z': ")
+    );
+    history.handle_key(&mut input, key(KeyCode::Esc), 40);
+    assert_eq!(input.text(), "cargo build");
+    assert!(!history.is_searching());
+}
+
+#[test]
+fn history_persists_as_json_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("nested").join("history.jsonl");
+
+    let mut history = InputHistory::open(&path).unwrap();
+    assert!(history.is_empty());
+    history.push("first").unwrap();
+    history.push("multi\nline").unwrap();
+    history.push("first").unwrap();
+
+    let reopened = InputHistory::open(&path).unwrap();
+    assert_eq!(reopened.entries(), ["multi\nline", "first"]);
+    // Reopening compacted the duplicate away.
+    assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 2);
+
+    fs::write(
+        &path,
+        "{\"text\":\"kept\"}\nnot json\n{\"text\":\"also\"}\n",
+    )
+    .unwrap();
+    let reopened = InputHistory::open_with_limit(&path, 1).unwrap();
+    assert_eq!(reopened.entries(), ["also"]);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "{\"text\":\"also\"}\n");
+}
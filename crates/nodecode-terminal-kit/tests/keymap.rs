@@ -0,0 +1,339 @@
+//! Key binding parsing, lookup and conflict detection.
+
+use nodecode_terminal_kit::components::help::{
+    component_help, help_sections, hint_entries, hint_pair, hint_text, HelpSection,
+};
+use nodecode_terminal_kit::components::tabbed_dialog;
+use nodecode_terminal_kit::components::text_input::TextInput;
+use nodecode_terminal_kit::keymap::{
+    action, context, ConflictKind, KeyChord, KeySequence, Keymap, KeymapError, PendingKeys, Preset,
+    Resolution,
+};
+use nodecode_terminal_kit::layout::picker_kit;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+/// `chord` as a terminal without the enhanced keyboard protocol reports it:
+/// control-letter chords lose their shift.
+fn legacy(chord: KeyChord) -> KeyChord {
+    match chord.code {
+        KeyCode::Char(ch) if chord.modifiers.contains(KeyModifiers::CONTROL) => {
+            KeyChord::new(KeyCode::Char(ch.to_ascii_lowercase()), chord.modifiers)
+        }
+        _ => chord,
+    }
+}
+
+#[test]
+fn chords_parse_and_normalise() {
+    let ctrl_w: KeyChord = "Ctrl+w".parse().unwrap();
+    assert_eq!(
+        ctrl_w,
+        KeyChord::new(KeyCode::Char('w'), KeyModifiers::CONTROL)
+    );
+    assert_eq!(ctrl_w.to_string(), "ctrl+w");
+
+    let shift_g: KeyChord = "shift+g".parse().unwrap();
+    assert_eq!(shift_g, "G".parse().unwrap());
+    assert!(shift_g.matches(&KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT)));
+
+    let back: KeyChord = "shift+tab".parse().unwrap();
+    assert!(back.matches(&KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT)));
+    assert_eq!(back.to_string(), "shift+tab");
+
+    assert_eq!("space".parse::<KeyChord>().unwrap().to_string(), "space");
+    assert_eq!("f12".parse::<KeyChord>().unwrap().to_string(), "f12");
+    assert_eq!(
+        "hyper+x".parse::<KeyChord>(),
+        Err(KeymapError::UnknownModifier("hyper".into()))
+    );
+    assert_eq!(
+        "ctrl+nope".parse::<KeyChord>(),
+        Err(KeymapError::UnknownKey("nope".into()))
+    );
+
+    let seq: KeySequence = "g  g".parse().unwrap();
+    assert_eq!(seq.len(), 2);
+    assert_eq!(seq.to_string(), "g g");
+    assert_eq!("  ".parse::<KeySequence>(), Err(KeymapError::EmptyBinding));
+}
+
+#[test]
+fn pending_keys_resolve_multi_key_sequences() {
+    let keymap = Preset::Vim.keymap();
+    let mut pending = PendingKeys::new();
+
+    assert_eq!(
+        pending.resolve(&keymap, context::LIST, &key(KeyCode::Char('g'))),
+        Resolution::Pending
+    );
+    assert_eq!(
+        pending.resolve(&keymap, context::LIST, &key(KeyCode::Char('g'))),
+        Resolution::Action(action::JUMP_TOP)
+    );
+    assert!(pending.is_empty());
+
+    // A key that breaks the sequence is looked up on its own.
+    pending.resolve(&keymap, context::LIST, &key(KeyCode::Char('g')));
+    assert_eq!(
+        pending.resolve(&keymap, context::LIST, &key(KeyCode::Char('j'))),
+        Resolution::Action(action::SELECT_NEXT)
+    );
+    assert_eq!(
+        pending.resolve(&keymap, context::LIST, &key(KeyCode::Char('x'))),
+        Resolution::Unbound
+    );
+
+    // Single-key lookups ignore sequences.
+    assert_eq!(
+        keymap.action_for(context::LIST, &key(KeyCode::Char('g'))),
+        None
+    );
+}
+
+#[test]
+fn presets_are_conflict_free_and_keep_default_bindings() {
+    for preset in Preset::ALL {
+        let keymap = preset.keymap();
+        assert_eq!(keymap.conflicts(), Vec::new(), "{preset}");
+        assert_eq!(
+            keymap.action_for(context::PICKER, &key(KeyCode::Esc)),
+            Some(action::CLOSE)
+        );
+        assert_eq!(preset.name().parse::<Preset>(), Ok(preset));
+    }
+    assert_eq!(Keymap::default(), Preset::Default.keymap());
+}
+
+#[test]
+fn text_input_keys_do_not_arrive_as_program_keys() {
+    for preset in Preset::ALL {
+        let keymap = preset.keymap();
+        let program: Vec<_> = keymap
+            .bindings(context::PROGRAM)
+            .iter()
+            .map(|b| legacy(b.keys.chords()[0]))
+            .collect();
+        for binding in keymap.bindings(context::TEXT_INPUT) {
+            let chord = legacy(binding.keys.chords()[0]);
+            assert!(
+                !program.contains(&chord),
+                "{preset}: `{}` ({}) arrives as program key `{chord}`",
+                binding.keys,
+                binding.action
+            );
+        }
+    }
+}
+
+#[test]
+fn inputs_handle_keys_with_the_keymap_they_are_given() {
+    let alt_x = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT);
+    let custom = Keymap::default().with(
+        context::TEXT_INPUT,
+        "alt+x".parse().unwrap(),
+        action::DELETE_TO_LINE_START,
+    );
+    let mut plain = TextInput::with_text("draft".into());
+    let mut bound = TextInput::with_text("draft".into());
+
+    assert!(!plain.handle_key_with(&Keymap::default(), alt_x));
+    assert!(bound.handle_key_with(&custom, alt_x));
+    assert_eq!(plain.text(), "draft");
+    assert_eq!(bound.text(), "");
+}
+
+#[test]
+fn text_input_actions_stay_apart_without_the_enhanced_keyboard_protocol() {
+    for preset in Preset::ALL {
+        let keymap = preset.keymap();
+        let bindings = keymap.bindings(context::TEXT_INPUT);
+        for (idx, first) in bindings.iter().enumerate() {
+            for second in &bindings[idx + 1..] {
+                assert!(
+                    first.action == second.action
+                        || legacy(first.keys.chords()[0]) != legacy(second.keys.chords()[0]),
+                    "{preset}: `{}` ({}) and `{}` ({}) arrive as the same key",
+                    first.keys,
+                    first.action,
+                    second.keys,
+                    second.action
+                );
+            }
+        }
+        assert!(keymap.keys_for(context::TEXT_INPUT, action::REDO).count() > 0);
+    }
+}
+
+#[test]
+fn conflicts_report_duplicates_and_shadowed_prefixes() {
+    let mut keymap = Keymap::empty();
+    keymap.bind("list", "g", "jump_top").unwrap();
+    keymap.bind("list", "g g", "jump_bottom").unwrap();
+    keymap.bind("list", "ctrl+n", "select_next").unwrap();
+    keymap.bind("list", "ctrl+n", "page_down").unwrap();
+    keymap.bind("list", "ctrl+n", "select_next").unwrap();
+
+    let conflicts = keymap.conflicts();
+    assert_eq!(conflicts.len(), 2);
+    assert_eq!(conflicts[0].kind, ConflictKind::Prefix);
+    assert_eq!(conflicts[0].first.action, "jump_top");
+    assert_eq!(conflicts[1].kind, ConflictKind::Duplicate);
+    assert_eq!(
+        conflicts[1].to_string(),
+        "[list] `ctrl+n` is bound to both `select_next` and `page_down`"
+    );
+    assert!(matches!(keymap.validate(), Err(KeymapError::Conflicts(c)) if c.len() == 2));
+}
+
+#[test]
+fn suspend_keys_conflict_with_the_same_keys_in_other_contexts() {
+    let mut keymap = Keymap::default();
+    keymap
+        .bind(context::TEXT_INPUT, "ctrl+z", action::UNDO)
+        .unwrap();
+
+    let conflicts = keymap.conflicts();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].kind, ConflictKind::Suspend);
+    assert_eq!(
+        conflicts[0].to_string(),
+        "[text_input] `ctrl+z` (undo) is taken by `ctrl+z` (suspend) in [program]"
+    );
+
+    keymap.unbind(context::PROGRAM, action::SUSPEND);
+    assert!(keymap.validate().is_ok());
+}
+
+#[test]
+fn remap_steals_keys_from_other_actions() {
+    let mut keymap = Keymap::default();
+    keymap.remap(
+        context::PICKER,
+        action::CONFIRM,
+        vec!["esc".parse().unwrap(), "ctrl+y".parse().unwrap()],
+    );
+    assert_eq!(
+        keymap.action_for(context::PICKER, &key(KeyCode::Esc)),
+        Some(action::CONFIRM)
+    );
+    assert_eq!(
+        keymap.action_for(context::PICKER, &key(KeyCode::Enter)),
+        None
+    );
+    assert_eq!(keymap.keys_for(context::PICKER, action::CLOSE).count(), 0);
+    assert!(keymap.conflicts().is_empty());
+}
+
+#[test]
+fn toml_overrides_apply_on_top_of_a_preset() {
+    let keymap = Keymap::from_toml(
+        r#"
+        preset = "emacs"
+
+        [picker]
+        close = ["esc", "ctrl+c"]
+
+        [text_input]
+        delete_word_backward = []
+        "#,
+    )
+    .unwrap();
+
+    let ctrl = |ch| KeyEvent::new(KeyCode::Char(ch), KeyModifiers::CONTROL);
+    assert_eq!(
+        keymap.action_for(context::PICKER, &ctrl('c')),
+        Some(action::CLOSE)
+    );
+    // Remapping `close` replaces the preset's ctrl+g.
+    assert_eq!(keymap.action_for(context::PICKER, &ctrl('g')), None);
+    assert_eq!(
+        keymap.action_for(context::PICKER, &ctrl('n')),
+        Some(action::SELECT_NEXT)
+    );
+    assert_eq!(keymap.action_for(context::TEXT_INPUT, &ctrl('w')), None);
+}
+
+#[test]
+fn toml_errors_are_reported() {
+    assert_eq!(
+        Keymap::from_toml("preset = \"nano\""),
+        Err(KeymapError::UnknownPreset("nano".into()))
+    );
+    assert_eq!(
+        Keymap::from_toml("[picker]\nclose = 3"),
+        Err(KeymapError::InvalidValue("picker.close".into()))
+    );
+    assert!(matches!(
+        Keymap::from_toml("[picker\n"),
+        Err(KeymapError::Toml(_))
+    ));
+    assert!(matches!(
+        Keymap::from_toml("[list]\njump_top = \"g\"\njump_bottom = \"g g\""),
+        Err(KeymapError::Conflicts(_))
+    ));
+}
+
+#[test]
+fn help_follows_remapped_bindings() {
+    let mut keymap = Keymap::default();
+    let labels = [(action::SUBMIT, "next"), (action::CANCEL, "cancel")];
+    assert_eq!(
+        hint_text(&hint_entries(&keymap, context::WIZARD, &labels)),
+        "enter next  esc cancel"
+    );
+
+    keymap.remap(
+        context::WIZARD,
+        action::SUBMIT,
+        vec!["ctrl+s".parse().unwrap()],
+    );
+    keymap.unbind(context::WIZARD, action::CANCEL);
+    assert_eq!(
+        hint_text(&hint_entries(&keymap, context::WIZARD, &labels)),
+        "ctrl+s next"
+    );
+
+    let section = HelpSection::from_keymap(&Preset::Vim.keymap(), context::LIST);
+    let next = section
+        .items
+        .iter()
+        .find(|item| item.action == action::SELECT_NEXT)
+        .unwrap();
+    assert_eq!(next.keys_label(), "↓, j");
+    assert_eq!(next.description, "next item");
+}
+
+#[test]
+fn components_declare_the_contexts_their_help_comes_from() {
+    let keymap = Keymap::default();
+    let input = TextInput::new();
+    let contexts = |sections: Vec<HelpSection>| -> Vec<String> {
+        sections
+            .into_iter()
+            .map(|section| section.context)
+            .collect()
+    };
+    assert_eq!(
+        contexts(component_help(&input, &keymap)),
+        [context::TEXT_INPUT]
+    );
+    assert_eq!(
+        contexts(help_sections(&keymap, picker_kit::KEY_CONTEXTS)),
+        [context::PICKER, context::TEXT_INPUT]
+    );
+    assert_eq!(
+        contexts(help_sections(&keymap, tabbed_dialog::KEY_CONTEXTS)),
+        [context::TABS, context::LIST]
+    );
+
+    let mut keymap = Keymap::default();
+    let nav = (action::SELECT_PREV, action::SELECT_NEXT);
+    let entry = hint_pair(&keymap, context::LIST, nav, "navigate").unwrap();
+    assert_eq!(hint_text(&[entry]), "↑↓ navigate");
+    keymap.unbind(context::LIST, action::SELECT_PREV);
+    assert!(hint_pair(&keymap, context::LIST, nav, "navigate").is_none());
+}
@@ -0,0 +1,176 @@
+//! Incremental edits and cached wrapping in `TextInput`.
+
+use nodecode_terminal_kit::components::input_box::InputBox;
+use nodecode_terminal_kit::components::text_input::{Chip, SecretMask, TextInput};
+use nodecode_terminal_kit::layout::text::{
+    find_visual_row, visual_column, wrapped_row_ranges_atomic,
+};
+use nodecode_terminal_kit::theme::Theme;
+use proptest::prelude::*;
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::Terminal;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Rows and cursor position computed from scratch over the whole text.
+fn uncached(input: &TextInput, width: u16) -> (Vec<(usize, usize)>, (u16, u16)) {
+    let visible = input.visible_text();
+    let rows = wrapped_row_ranges_atomic(&visible, width, &input.visible_chip_ranges());
+    let cursor = input.visible_offset(input.cursor());
+    let row = find_visual_row(&rows, cursor);
+    let (start, end) = rows[row];
+    let col = visual_column(&visible[start..cursor.clamp(start, end)]).min(width as usize);
+    (rows, (row as u16, col as u16))
+}
+
+#[test]
+fn undo_and_redo_round_trip_a_large_buffer() {
+    let text = (0..2000)
+        .map(|idx| format!("line {idx} with some words to wrap"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut input = TextInput::with_text(text.clone());
+    input.set_cursor_byte_offset(text.len() / 2);
+    for ch in "hello\nworld".chars() {
+        input.insert_char(ch);
+    }
+    let len = input.text().len();
+    input.set_selection(10..len - 10);
+    input.delete_selection();
+    assert_eq!(input.text().len(), 20);
+    assert_eq!(input.wrapped_rows(30), uncached(&input, 30).0);
+
+    while input.undo() {}
+    assert_eq!(input.text(), text);
+    assert_eq!(input.wrapped_rows(30), uncached(&input, 30).0);
+    while input.redo() {}
+    assert_eq!(input.text().len(), 20);
+}
+
+#[test]
+fn only_the_rows_on_screen_are_drawn_from_the_middle_of_a_large_buffer() {
+    let text = (0..2000)
+        .map(|idx| format!("line {idx} with enough words to wrap once"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut input = TextInput::with_text(text.clone());
+    input.set_cursor_byte_offset(text.len() / 2);
+    input.insert_chip(Chip::pasted("a\nb"));
+    let theme = Theme::default();
+    let (width, height) = (20, 4);
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    let mut scroll = 0;
+    terminal
+        .draw(|frame| {
+            scroll = InputBox::new(&input, &theme)
+                .prompt_override(Some(""))
+                .prompt_gap(0)
+                .padding_top(0)
+                .padding_bottom(0)
+                .follow_cursor(true)
+                .render(frame, frame.area())
+                .scroll_offset as usize;
+        })
+        .unwrap();
+
+    let (rows, (cursor_row, _)) = uncached(&input, width);
+    assert!((scroll..scroll + height as usize).contains(&(cursor_row as usize)));
+    let visible = input.visible_text();
+    let buffer = terminal.backend().buffer();
+    for (y, &(start, end)) in rows[scroll..scroll + height as usize].iter().enumerate() {
+        let drawn: String = (0..width).map(|x| buffer[(x, y as u16)].symbol()).collect();
+        assert_eq!(drawn.trim_end(), visible[start..end].trim_end());
+    }
+}
+
+/// Every grapheme boundary of the text survives a trip through the cursor.
+fn offsets_round_trip(input: &TextInput) -> bool {
+    let mut probe = input.clone();
+    let text = input.text().to_string();
+    text.grapheme_indices(true)
+        .map(|(idx, _)| idx)
+        .chain([text.len()])
+        .all(|offset| {
+            probe.set_cursor_byte_offset(offset);
+            probe.cursor() == offset
+        })
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Key(KeyCode, KeyModifiers),
+    Paste(&'static str),
+    Chip,
+    Select(usize, usize),
+    Undo,
+    Redo,
+    Mask,
+    Width(u16),
+}
+
+fn step() -> impl Strategy<Value = Step> {
+    let none = KeyModifiers::NONE;
+    prop_oneof![
+        4 => prop::sample::select(vec![
+            (KeyCode::Char('a'), none),
+            (KeyCode::Char(' '), none),
+            (KeyCode::Enter, none),
+            (KeyCode::Backspace, none),
+            (KeyCode::Delete, none),
+            (KeyCode::Left, none),
+            (KeyCode::Right, none),
+            (KeyCode::Up, none),
+            (KeyCode::Down, none),
+            (KeyCode::Char('w'), KeyModifiers::CONTROL),
+            (KeyCode::Char('k'), KeyModifiers::CONTROL),
+        ])
+        .prop_map(|(code, modifiers)| Step::Key(code, modifiers)),
+        1 => prop::sample::select(vec!["x", "two\nlines", "漢字 ", "\n\n"]).prop_map(Step::Paste),
+        1 => Just(Step::Chip),
+        1 => (0usize..60, 0usize..60).prop_map(|(a, b)| Step::Select(a, b)),
+        1 => Just(Step::Undo),
+        1 => Just(Step::Redo),
+        1 => Just(Step::Mask),
+        1 => (1u16..12).prop_map(Step::Width),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn cached_rows_match_a_full_rewrap(
+        lines in prop::collection::vec("[a-z ]{0,14}", 1..6),
+        steps in prop::collection::vec(step(), 0..40),
+    ) {
+        let mut input = TextInput::with_text(lines.join("\n"));
+        let mut width = 6;
+        for step in steps {
+            match step {
+                Step::Key(code, modifiers) => {
+                    input.handle_key(KeyEvent::new(code, modifiers));
+                }
+                Step::Paste(text) => {
+                    input.paste(text);
+                }
+                Step::Chip => {
+                    input.insert_chip(Chip::pasted("a\nb"));
+                }
+                Step::Select(a, b) => input.set_selection(a.min(b)..a.max(b)),
+                Step::Undo => {
+                    input.undo();
+                }
+                Step::Redo => {
+                    input.redo();
+                }
+                Step::Mask => {
+                    let mask = (!input.is_secret()).then(SecretMask::default);
+                    input.set_secret(mask);
+                }
+                Step::Width(value) => width = value,
+            }
+            let (rows, cursor) = uncached(&input, width);
+            prop_assert_eq!(input.wrapped_rows(width), rows);
+            prop_assert_eq!(input.cursor_visual_position(width), cursor);
+            prop_assert!(offsets_round_trip(&input));
+        }
+    }
+}
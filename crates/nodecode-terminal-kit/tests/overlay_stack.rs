@@ -0,0 +1,153 @@
+//! Z-ordered modals: input routing, dismissal and dimming.
+
+use nodecode_terminal_kit::components::component::UiComponent;
+use nodecode_terminal_kit::components::overlay_stack::{
+    OverlayEvent, OverlayLayer, OverlayPlacement, OverlayStack,
+};
+use nodecode_terminal_kit::theme::{to_ratatui, Theme};
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{Frame, Terminal};
+
+/// Fills its area and emits its name for Enter.
+struct Modal(&'static str);
+
+impl UiComponent for Modal {
+    type Action = &'static str;
+
+    fn update(&mut self, _action: &'static str) {}
+
+    fn view(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        frame.render_widget(
+            Block::default().style(Style::default().bg(to_ratatui(theme.background_surface))),
+            area,
+        );
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<&'static str> {
+        (key.code == KeyCode::Enter).then_some(self.0)
+    }
+}
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+fn click(column: u16, row: u16) -> MouseEvent {
+    MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column,
+        row,
+        modifiers: KeyModifiers::NONE,
+    }
+}
+
+fn stack() -> OverlayStack<u8, &'static str> {
+    let mut stack = OverlayStack::new();
+    stack.push(OverlayLayer::new(1, Modal("picker")));
+    stack.push(
+        OverlayLayer::new(2, Modal("confirm"))
+            .placement(OverlayPlacement::Fixed(Rect::new(5, 2, 10, 3))),
+    );
+    stack
+}
+
+fn draw(stack: &mut OverlayStack<u8, &'static str>, theme: &Theme) -> Terminal<TestBackend> {
+    let mut terminal = Terminal::new(TestBackend::new(20, 8)).unwrap();
+    terminal
+        .draw(|frame| {
+            frame.render_widget(
+                Paragraph::new("content").style(Style::default().fg(to_ratatui(theme.foreground))),
+                frame.area(),
+            );
+            stack.render(frame, frame.area(), theme);
+        })
+        .unwrap();
+    terminal
+}
+
+#[test]
+fn input_reaches_only_the_top_layer_and_esc_pops_in_order() {
+    let mut stack = stack();
+    assert_eq!(
+        stack.handle_key(key(KeyCode::Enter)),
+        Some(OverlayEvent::Action("confirm"))
+    );
+    assert_eq!(
+        stack.handle_key(key(KeyCode::Char('x'))),
+        Some(OverlayEvent::Blocked)
+    );
+    assert_eq!(
+        stack.handle_key(key(KeyCode::Esc)),
+        Some(OverlayEvent::Dismissed(2))
+    );
+    assert_eq!(stack.top(), Some(1));
+    assert_eq!(
+        stack.handle_key(key(KeyCode::Esc)),
+        Some(OverlayEvent::Dismissed(1))
+    );
+    assert!(stack.is_empty());
+    assert_eq!(stack.handle_key(key(KeyCode::Esc)), None);
+}
+
+#[test]
+fn pushing_an_open_id_raises_it() {
+    let mut stack = stack();
+    stack.push(OverlayLayer::new(1, Modal("picker")).dismiss_on_esc(false));
+    assert_eq!(stack.ids().collect::<Vec<_>>(), [2, 1]);
+    assert_eq!(
+        stack.handle_key(key(KeyCode::Esc)),
+        Some(OverlayEvent::Blocked)
+    );
+    assert!(stack.close(2));
+    assert!(!stack.is_open(2));
+}
+
+#[test]
+fn clicks_outside_the_top_layer_dismiss_it() {
+    let theme = Theme::default();
+    let mut stack = stack();
+    draw(&mut stack, &theme);
+
+    assert_eq!(stack.handle_mouse(click(6, 3)), Some(OverlayEvent::Blocked));
+    assert_eq!(
+        stack.handle_mouse(click(0, 0)),
+        Some(OverlayEvent::Dismissed(2))
+    );
+
+    stack.push(
+        OverlayLayer::new(3, Modal("sticky"))
+            .placement(OverlayPlacement::Fixed(Rect::new(0, 0, 4, 1)))
+            .dismiss_on_click_outside(false),
+    );
+    draw(&mut stack, &theme);
+    assert_eq!(
+        stack.handle_mouse(click(10, 5)),
+        Some(OverlayEvent::Blocked)
+    );
+    assert_eq!(stack.top(), Some(3));
+}
+
+#[test]
+fn layers_dim_the_content_below_them() {
+    let theme = Theme::default();
+    let mut stack = stack();
+    let terminal = draw(&mut stack, &theme);
+    let buffer = terminal.backend().buffer();
+
+    let surface = to_ratatui(theme.background_surface);
+    // The top layer is drawn as is; the picker below it was dimmed once.
+    assert_eq!(buffer[(6, 3)].bg, surface);
+    assert_ne!(buffer[(0, 0)].bg, surface);
+    assert_ne!(buffer[(0, 0)].bg, ratatui::style::Color::Reset);
+
+    let mut undimmed = OverlayStack::new().with_dim(0.0);
+    undimmed.push(OverlayLayer::new(1u8, Modal("picker")));
+    let terminal = draw(&mut undimmed, &theme);
+    assert_eq!(terminal.backend().buffer()[(0, 0)].bg, surface);
+}
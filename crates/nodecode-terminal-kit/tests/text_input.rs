@@ -0,0 +1,211 @@
+//! `TextInput` editing behaviour.
+
+use nodecode_terminal_kit::components::input_box::InputBox;
+use nodecode_terminal_kit::components::text_input::{SecretMask, TextInput};
+use nodecode_terminal_kit::keymap::{action, context, Keymap};
+use nodecode_terminal_kit::theme::Theme;
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::style::Style;
+use ratatui::Terminal;
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+fn ctrl(ch: char) -> KeyEvent {
+    KeyEvent::new(KeyCode::Char(ch), KeyModifiers::CONTROL)
+}
+
+fn type_str(input: &mut TextInput, text: &str) {
+    for ch in text.chars() {
+        input.handle_key(key(KeyCode::Char(ch)));
+    }
+}
+
+#[test]
+fn typing_coalesces_into_word_sized_undo_steps() {
+    let mut input = TextInput::new();
+    type_str(&mut input, "hello big world");
+    assert!(input.handle_key(ctrl('_')));
+    assert_eq!(input.text(), "hello big ");
+    input.handle_key(ctrl('_'));
+    assert_eq!(input.text(), "hello ");
+    assert_eq!(input.cursor(), 6);
+
+    assert!(input.handle_key(ctrl('y')));
+    assert_eq!(input.text(), "hello big ");
+    assert!(input.redo());
+    assert_eq!(input.text(), "hello big world");
+    assert!(!input.redo());
+}
+
+#[test]
+fn moving_the_cursor_starts_a_new_undo_step() {
+    let mut input = TextInput::new();
+    type_str(&mut input, "ac");
+    input.handle_key(key(KeyCode::Left));
+    type_str(&mut input, "b");
+    assert_eq!(input.text(), "abc");
+    input.undo();
+    assert_eq!(input.text(), "ac");
+    assert_eq!(input.cursor(), 1);
+}
+
+#[test]
+fn set_text_paste_and_deletions_are_undoable() {
+    let mut input = TextInput::with_text("draft".into());
+    assert!(!input.can_undo());
+
+    input.insert_str(" pasted\ntext");
+    input.handle_key(ctrl('w'));
+    input.set_text("replaced".into());
+    assert_eq!(input.cursor(), "replaced".len());
+
+    input.undo();
+    assert_eq!(input.text(), "draft pasted\n");
+    input.undo();
+    assert_eq!(input.text(), "draft pasted\ntext");
+    input.undo();
+    assert_eq!(input.text(), "draft");
+    assert!(!input.undo());
+
+    // A new edit drops the redo branch.
+    input.insert_char('!');
+    assert!(!input.can_redo());
+}
+
+#[test]
+fn history_is_bounded() {
+    let mut input = TextInput::new();
+    input.set_history_limit(2);
+    for word in ["a", "b", "c", "d"] {
+        input.insert_str(word);
+    }
+    assert!(input.undo());
+    assert!(input.undo());
+    assert!(!input.undo());
+    assert_eq!(input.text(), "ab");
+
+    input.clear_history();
+    assert!(!input.can_redo());
+}
+
+fn shift(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::SHIFT)
+}
+
+#[test]
+fn shift_arrows_select_and_typing_replaces_the_selection() {
+    let mut input = TextInput::with_text("hello world".into());
+    for _ in 0..5 {
+        input.handle_key(shift(KeyCode::Left));
+    }
+    assert_eq!(input.selection(), Some(6..11));
+    assert_eq!(input.selected_text(), Some("world"));
+
+    type_str(&mut input, "there");
+    assert_eq!(input.text(), "hello there");
+    assert_eq!(input.selection(), None);
+    input.undo();
+    assert_eq!(input.text(), "hello world");
+
+    input.set_selection(0..5);
+    input.handle_key(key(KeyCode::Backspace));
+    assert_eq!(input.text(), " world");
+
+    // Moving without shift drops the selection.
+    input.set_selection(1..3);
+    input.handle_key(key(KeyCode::Left));
+    assert_eq!(input.selection(), None);
+    assert_eq!(input.text(), " world");
+}
+
+#[test]
+fn paste_normalises_line_endings_and_replaces_the_selection() {
+    let mut input = TextInput::with_text("a-b".into());
+    input.set_selection(1..2);
+    assert!(input.paste("1\r\n2"));
+    assert_eq!(input.text(), "a1\n2b");
+    input.undo();
+    assert_eq!(input.text(), "a-b");
+}
+
+#[test]
+fn secret_inputs_mask_the_value_and_keep_cursor_geometry() {
+    let mut input = TextInput::with_text("東京pw".into());
+    input.set_secret(Some(SecretMask::Char('*')));
+    assert_eq!(input.visible_text(), "****");
+    // Wide characters are masked to one column each.
+    assert_eq!(input.cursor_visual_position(10), (0, 4));
+    assert_eq!(input.cursor_visual_position(3), (1, 1));
+    input.handle_key(key(KeyCode::Left));
+    assert_eq!(input.cursor_visual_position(10), (0, 3));
+    assert_eq!(input.offset_at_visual(0, 1, 10), "東".len());
+
+    input.select_all();
+    assert_eq!(input.copy_selection(), Ok(false));
+    assert_eq!(input.cut_selection(), Ok(false));
+    assert_eq!(input.text(), "東京pw");
+
+    assert!(!input.handle_key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::ALT)));
+    assert!(input.is_revealed());
+    assert_eq!(input.visible_text(), "東京pw");
+    assert_eq!(input.cursor_visual_position(10), (0, 6));
+
+    input.set_secret(Some(SecretMask::Hidden));
+    assert!(!input.is_revealed());
+    assert_eq!(input.visible_text(), "");
+    assert_eq!(input.cursor_visual_position(10), (0, 0));
+}
+
+#[test]
+fn secret_deletions_leave_nothing_to_yank_back() {
+    // With redo unbound, ctrl+y falls through to the textarea's yank.
+    let mut keymap = Keymap::default();
+    keymap.unbind(context::TEXT_INPUT, action::REDO);
+    let mut plain = TextInput::with_text("user hunter2".into());
+    plain.handle_key_with(&keymap, ctrl('w'));
+    assert_eq!(plain.text(), "user ");
+    plain.handle_key_with(&keymap, ctrl('y'));
+    assert_eq!(plain.text(), "user hunter2");
+
+    let mut secret = TextInput::with_text("user hunter2".into());
+    secret.set_secret(Some(SecretMask::default()));
+    secret.handle_key_with(&keymap, ctrl('w'));
+    secret.handle_key_with(&keymap, ctrl('y'));
+    assert_eq!(secret.text(), "user ");
+}
+
+#[test]
+fn secret_search_fields_do_not_hand_out_their_text() {
+    let mut plain = TextInput::new();
+    assert_eq!(
+        plain.handle_search_key(key(KeyCode::Char('a'))),
+        Some("a".into())
+    );
+
+    let mut secret = TextInput::new();
+    secret.set_secret(Some(SecretMask::default()));
+    assert_eq!(secret.handle_search_key(key(KeyCode::Char('a'))), None);
+    assert_eq!(secret.text(), "a");
+}
+
+#[test]
+fn masked_secrets_render_without_the_value() {
+    let mut input = TextInput::with_text("hunter2".into());
+    input.set_secret(Some(SecretMask::default()));
+    let theme = Theme::default();
+    let mut terminal = Terminal::new(TestBackend::new(20, 3)).unwrap();
+    terminal
+        .draw(|frame| {
+            InputBox::new(&input, &theme)
+                .suggestion(Some("3"), Style::default())
+                .render(frame, frame.area());
+        })
+        .unwrap();
+    let row: String = (0..20)
+        .map(|x| terminal.backend().buffer()[(x, 1)].symbol())
+        .collect();
+    assert_eq!(row.trim_end(), "❯ •••••••");
+}
@@ -0,0 +1,199 @@
+//! Constrained entry and live validation for `TextInput`.
+
+use nodecode_terminal_kit::components::input_box::InputBox;
+use nodecode_terminal_kit::components::text_input::TextInput;
+use nodecode_terminal_kit::components::validation::{InputConstraints, InputMask, ValidationState};
+use nodecode_terminal_kit::theme::{to_ratatui, Theme};
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::Rect;
+use ratatui::Terminal;
+use regex::Regex;
+
+fn type_str(input: &mut TextInput, text: &str) {
+    for ch in text.chars() {
+        input.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
+    }
+}
+
+fn constrained(constraints: InputConstraints) -> TextInput {
+    let mut input = TextInput::new();
+    input.set_constraints(Some(constraints));
+    input
+}
+
+#[test]
+fn masks_insert_literals_and_reject_misfits() {
+    let mut input = constrained(InputConstraints::new().mask(InputMask::new("##:##")));
+    type_str(&mut input, "12");
+    assert!(input.validation().is_invalid());
+    type_str(&mut input, "3x45");
+    assert_eq!(input.text(), "12:34");
+    assert_eq!(input.cursor(), 5);
+    assert_eq!(input.validation(), ValidationState::Valid(None));
+
+    // Typed literals are kept rather than doubled.
+    input.clear();
+    type_str(&mut input, "09:1");
+    assert_eq!(input.text(), "09:1");
+    assert_eq!(
+        input.validation(),
+        ValidationState::Invalid("expected ##:##".into())
+    );
+}
+
+#[test]
+fn mask_edits_in_the_middle_shift_typed_characters_between_slots() {
+    let backspace = KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE);
+    let delete = KeyEvent::new(KeyCode::Delete, KeyModifiers::NONE);
+    let mut input = constrained(InputConstraints::new().mask(InputMask::new("##:##")));
+    type_str(&mut input, "1234");
+
+    // Backspace after the 2 pulls the later digits back over the colon.
+    input.set_cursor_byte_offset(2);
+    input.handle_key(backspace);
+    assert_eq!(input.text(), "13:4");
+    assert_eq!(input.cursor(), 1);
+    input.undo();
+    assert_eq!(input.text(), "12:34");
+
+    // Deleting just the colon takes the digit on that side with it.
+    input.set_cursor_byte_offset(3);
+    input.handle_key(backspace);
+    assert_eq!(input.text(), "13:4");
+    assert_eq!(input.cursor(), 1);
+    input.undo();
+    input.set_cursor_byte_offset(2);
+    input.handle_key(delete);
+    assert_eq!(input.text(), "12:4");
+    assert_eq!(input.cursor(), 2);
+    input.undo();
+
+    // Overtyping replaces in place; inserting into a full mask is refused.
+    input.set_selection(1..2);
+    type_str(&mut input, "9");
+    assert_eq!(input.text(), "19:34");
+    assert_eq!(input.cursor(), 2);
+    input.set_cursor_byte_offset(1);
+    type_str(&mut input, "5");
+    assert_eq!(input.text(), "19:34");
+    type_str(&mut input, "x");
+    assert_eq!(input.text(), "19:34");
+}
+
+#[test]
+fn length_and_character_filters_reject_whole_edits() {
+    let mut input = constrained(
+        InputConstraints::new()
+            .max_chars(4)
+            .allow(|ch| ch.is_ascii_hexdigit()),
+    );
+    type_str(&mut input, "beefg0");
+    assert_eq!(input.text(), "beef");
+    input.set_cursor_byte_offset(0);
+    assert!(!input.paste("00"));
+    assert_eq!(input.text(), "beef");
+    // Rejected edits leave no undo step behind.
+    input.undo();
+    assert_eq!(input.text(), "");
+}
+
+#[test]
+fn numbers_validate_ranges_live() {
+    let mut input = constrained(
+        InputConstraints::new()
+            .integer(-10..=99)
+            .valid_message("ok"),
+    );
+    assert_eq!(input.validation(), ValidationState::Idle);
+    type_str(&mut input, "-");
+    assert_eq!(
+        input.validation(),
+        ValidationState::Invalid("must be a whole number".into())
+    );
+    type_str(&mut input, "1-2.5");
+    assert_eq!(input.text(), "-125");
+    assert_eq!(
+        input.validation(),
+        ValidationState::Invalid("must be between -10 and 99".into())
+    );
+    input.set_cursor_byte_offset(1);
+    input.handle_key(KeyEvent::new(KeyCode::Delete, KeyModifiers::NONE));
+    input.handle_key(KeyEvent::new(KeyCode::Delete, KeyModifiers::NONE));
+    assert_eq!(input.text(), "-5");
+    assert_eq!(
+        input.validation(),
+        ValidationState::Valid(Some("ok".into()))
+    );
+
+    let mut decimal = constrained(InputConstraints::new().decimal(0.0..=1.0));
+    type_str(&mut decimal, "0.5.-");
+    assert_eq!(decimal.text(), "0.5");
+    assert!(!decimal.validation().is_invalid());
+}
+
+#[test]
+fn patterns_and_validators_report_through_input_box() {
+    let mut input = constrained(
+        InputConstraints::new()
+            .pattern(Regex::new(r"^sk-").unwrap(), "must start with sk-")
+            .validator(|text| {
+                if text.len() < 6 {
+                    Err("too short".into())
+                } else {
+                    Ok(())
+                }
+            }),
+    );
+    type_str(&mut input, "pk-1");
+    assert_eq!(
+        input.validation(),
+        ValidationState::Invalid("must start with sk-".into())
+    );
+    input.clear();
+    type_str(&mut input, "sk-1");
+    assert_eq!(input.validation().message(), Some("too short"));
+
+    let theme = Theme::default();
+    let mut terminal = Terminal::new(TestBackend::new(24, 5)).unwrap();
+    terminal
+        .draw(|frame| {
+            InputBox::new(&input, &theme)
+                .title_override(Some("Token"))
+                .validation(input.validation())
+                .render(frame, frame.area());
+        })
+        .unwrap();
+    let buffer = terminal.backend().buffer();
+    let row = |y: u16| (0..24).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+    assert!(row(0).contains("Token"));
+    assert!(row(2).contains("sk-1"));
+    assert!(row(4).contains("✗ too short"));
+    assert_eq!(buffer[(0, 0)].fg, to_ratatui(theme.error));
+}
+
+#[test]
+fn the_validation_border_is_drawn_before_the_first_keystroke() {
+    let mut input = constrained(InputConstraints::new().integer(0..=9));
+    let theme = Theme::default();
+    let mut terminal = Terminal::new(TestBackend::new(12, 5)).unwrap();
+    let mut draw = |input: &TextInput| {
+        let mut content = Rect::default();
+        terminal
+            .draw(|frame| {
+                content = InputBox::new(input, &theme)
+                    .validation(input.validation())
+                    .render(frame, frame.area())
+                    .content_area;
+            })
+            .unwrap();
+        (content, terminal.backend().buffer()[(0, 0)].fg)
+    };
+
+    let (idle, idle_border) = draw(&input);
+    assert_eq!(idle_border, to_ratatui(theme.border));
+    type_str(&mut input, "x1");
+    let (valid, valid_border) = draw(&input);
+    assert_eq!(valid, idle);
+    assert_eq!(valid_border, to_ratatui(theme.success));
+}
@@ -0,0 +1,135 @@
+//! Vim-style modal editing over `TextInput`.
+
+use nodecode_terminal_kit::components::text_input::{SecretMask, TextInput};
+use nodecode_terminal_kit::components::vim::{VimLayer, VimMode};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Feed keys written vim-style; `<esc>` is escape.
+fn keys(vim: &mut VimLayer, input: &mut TextInput, keys: &str) {
+    let mut rest = keys;
+    while !rest.is_empty() {
+        let (code, len) = if let Some(after) = rest.strip_prefix("<esc>") {
+            (KeyCode::Esc, rest.len() - after.len())
+        } else {
+            let ch = rest.chars().next().unwrap();
+            (KeyCode::Char(ch), ch.len_utf8())
+        };
+        vim.handle_key(input, KeyEvent::new(code, KeyModifiers::NONE));
+        rest = &rest[len..];
+    }
+}
+
+fn normal(text: &str) -> (VimLayer, TextInput) {
+    let mut input = TextInput::with_text(text.into());
+    let mut vim = VimLayer::new();
+    vim.set_mode(&mut input, VimMode::Normal);
+    input.set_cursor_byte_offset(0);
+    (vim, input)
+}
+
+#[test]
+fn starts_in_insert_and_esc_steps_back_onto_the_text() {
+    let mut input = TextInput::new();
+    let mut vim = VimLayer::new();
+    assert_eq!(vim.mode(), VimMode::Insert);
+    keys(&mut vim, &mut input, "hello<esc>");
+    assert_eq!(vim.mode(), VimMode::Normal);
+    assert_eq!(vim.mode().label(), "NORMAL");
+    assert_eq!(input.cursor(), 4);
+
+    keys(&mut vim, &mut input, "0x$");
+    assert_eq!(input.text(), "ello");
+    assert_eq!(input.cursor(), 3);
+    // Enter is left to the caller.
+    assert!(!vim.handle_key(
+        &mut input,
+        KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)
+    ));
+}
+
+#[test]
+fn motions_take_counts() {
+    let (mut vim, mut input) = normal("one two, three four");
+    keys(&mut vim, &mut input, "w");
+    assert_eq!(input.cursor(), 4);
+    keys(&mut vim, &mut input, "2w");
+    assert_eq!(input.cursor(), 9);
+    keys(&mut vim, &mut input, "e");
+    assert_eq!(input.cursor(), 13);
+    keys(&mut vim, &mut input, "3b");
+    assert_eq!(input.cursor(), 4);
+    keys(&mut vim, &mut input, "$");
+    assert_eq!(input.cursor(), 18);
+    keys(&mut vim, &mut input, "0");
+    assert_eq!(input.cursor(), 0);
+}
+
+#[test]
+fn operators_combine_with_motions_counts_and_repeat() {
+    let (mut vim, mut input) = normal("a b c d e f");
+    keys(&mut vim, &mut input, "d2w");
+    assert_eq!(input.text(), "c d e f");
+    assert_eq!(vim.register(), "a b ");
+    keys(&mut vim, &mut input, ".");
+    assert_eq!(input.text(), "e f");
+
+    keys(&mut vim, &mut input, "cwX<esc>");
+    assert_eq!(input.text(), "X f");
+    keys(&mut vim, &mut input, "w.");
+    assert_eq!(input.text(), "X X");
+
+    keys(&mut vim, &mut input, "0yeP");
+    assert_eq!(vim.register(), "X X");
+    assert_eq!(input.text(), "X XX X");
+    keys(&mut vim, &mut input, "uu");
+    assert_eq!(input.text(), "X f");
+}
+
+#[test]
+fn whole_line_operators_and_linewise_paste() {
+    let (mut vim, mut input) = normal("first\n  second\nthird");
+    keys(&mut vim, &mut input, "dd");
+    assert_eq!(input.text(), "  second\nthird");
+    assert_eq!(vim.register(), "first\n");
+    assert_eq!(input.cursor(), 2);
+
+    keys(&mut vim, &mut input, "jp");
+    // `j` is not a motion here; `p` puts the line below.
+    assert_eq!(input.text(), "  second\nfirst\nthird");
+    keys(&mut vim, &mut input, "yyP");
+    assert_eq!(input.text(), "  second\nfirst\nfirst\nthird");
+
+    keys(&mut vim, &mut input, "ccnew<esc>");
+    assert_eq!(input.text(), "  second\nnew\nfirst\nthird");
+    keys(&mut vim, &mut input, "2dd");
+    assert_eq!(input.text(), "  second\nthird");
+}
+
+#[test]
+fn visual_mode_selects_and_operates() {
+    let (mut vim, mut input) = normal("hello world");
+    keys(&mut vim, &mut input, "wv");
+    assert_eq!(vim.mode(), VimMode::Visual);
+    keys(&mut vim, &mut input, "e");
+    assert_eq!(input.selected_text(), Some("world"));
+    keys(&mut vim, &mut input, "y");
+    assert_eq!(vim.mode(), VimMode::Normal);
+    assert_eq!(vim.register(), "world");
+
+    keys(&mut vim, &mut input, "0vld");
+    assert_eq!(input.text(), "llo world");
+    keys(&mut vim, &mut input, "vecHE<esc>");
+    assert_eq!(input.text(), "HE world");
+}
+
+#[test]
+fn secret_inputs_are_not_yanked_into_the_register() {
+    let (mut vim, mut input) = normal("kept\nhunter2 swordfish");
+    keys(&mut vim, &mut input, "yy");
+    assert_eq!(vim.register(), "kept\n");
+
+    input.set_secret(Some(SecretMask::default()));
+    keys(&mut vim, &mut input, "jyywdwxvey0dd");
+    assert_eq!(input.text(), "kept");
+    assert_eq!(vim.register(), "kept\n");
+}
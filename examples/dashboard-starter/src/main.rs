@@ -61,6 +61,6 @@ impl Model for DashboardModel {
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let app = DashboardModel { reliability: 95 };
-    Program::new(app, ProgramConfig::new("Dashboard Starter")).run()?;
+    Program::new(app, ProgramConfig::new("Dashboard Starter")).run().into_result()?;
     Ok(())
 }
@@ -68,6 +68,6 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     input.set_placeholder("Project name");
     let app = FormModel { input };
 
-    Program::new(app, ProgramConfig::new("Form Starter")).run()?;
+    Program::new(app, ProgramConfig::new("Form Starter")).run().into_result()?;
     Ok(())
 }
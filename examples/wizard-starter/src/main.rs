@@ -77,6 +77,6 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         steps: vec!["Connect account", "Choose region", "Review", "Apply"],
     };
 
-    Program::new(app, ProgramConfig::new("Wizard Starter")).run()?;
+    Program::new(app, ProgramConfig::new("Wizard Starter")).run().into_result()?;
     Ok(())
 }
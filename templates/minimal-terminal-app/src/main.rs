@@ -58,6 +58,6 @@ impl Model for MinimalModel {
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let model = MinimalModel { value: 50 };
-    Program::new(model, ProgramConfig::new("Minimal Terminal App")).run()?;
+    Program::new(model, ProgramConfig::new("Minimal Terminal App")).run().into_result()?;
     Ok(())
 }
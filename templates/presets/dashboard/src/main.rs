@@ -61,6 +61,6 @@ impl Model for DashboardModel {
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let model = DashboardModel { reliability: 95 };
-    Program::new(model, ProgramConfig::new("{{title}}")).run()?;
+    Program::new(model, ProgramConfig::new("{{title}}")).run().into_result()?;
     Ok(())
 }
@@ -65,6 +65,6 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     input.set_placeholder("Project name");
 
     let model = FormModel { input };
-    Program::new(model, ProgramConfig::new("{{title}}")).run()?;
+    Program::new(model, ProgramConfig::new("{{title}}")).run().into_result()?;
     Ok(())
 }
@@ -76,6 +76,6 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         steps: vec!["Connect account", "Choose region", "Review", "Apply"],
     };
 
-    Program::new(model, ProgramConfig::new("{{title}}")).run()?;
+    Program::new(model, ProgramConfig::new("{{title}}")).run().into_result()?;
     Ok(())
 }